@@ -0,0 +1,415 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{
+    spanned::Spanned, Attribute, Data, DeriveInput, Error, Fields, GenericArgument, Ident, Lit,
+    Meta, NestedMeta, PathArguments, Result, Type,
+};
+
+// How a field of the command struct is passed on the command line.
+enum ArgumentKind {
+    // `-name`, sets the field to true if present.
+    Flag,
+    // `-name <value>`.
+    Named { optional: bool },
+    // `<value>`.
+    Positional { optional: bool },
+    // All the remaining positional arguments.
+    Remaining,
+}
+
+struct CommandArgument {
+    ident: Ident,
+    // The type of a single value, without the `Option` or `Vec` wrapper.
+    value_type: Type,
+    kind: ArgumentKind,
+    name: String,
+    description: String,
+    completion: Option<String>,
+}
+
+impl CommandArgument {
+    fn from_field(field: &syn::Field) -> Result<Self> {
+        let ident = field.ident.clone().expect("only named fields are collected");
+        let name = ident.to_string().trim_start_matches("r#").replace('_', "-");
+        let description = doc_string(&field.attrs);
+
+        let mut named = false;
+        let mut completion = None;
+
+        for meta in attribute_metas(&field.attrs, "arg")? {
+            match meta {
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("named") => named = true,
+                NestedMeta::Meta(Meta::NameValue(value)) if value.path.is_ident("completion") => {
+                    match value.lit {
+                        Lit::Str(lit) => completion = Some(lit.value()),
+                        lit => return Err(Error::new(lit.span(), "expected a string")),
+                    }
+                }
+                meta => return Err(Error::new(meta.span(), "expected one of named or completion")),
+            }
+        }
+
+        let (kind, value_type) = if is_type(&field.ty, "bool") {
+            if named {
+                return Err(Error::new(field.span(), "a bool field is a flag, it can't be named"));
+            }
+            (ArgumentKind::Flag, field.ty.clone())
+        } else if let Some(inner) = inner_type(&field.ty, "Option") {
+            let kind = if named {
+                ArgumentKind::Named { optional: true }
+            } else {
+                ArgumentKind::Positional { optional: true }
+            };
+            (kind, inner)
+        } else if let Some(inner) = inner_type(&field.ty, "Vec") {
+            if named {
+                return Err(Error::new(field.span(), "a Vec field can't be named"));
+            }
+            (ArgumentKind::Remaining, inner)
+        } else if named {
+            (ArgumentKind::Named { optional: false }, field.ty.clone())
+        } else {
+            (ArgumentKind::Positional { optional: false }, field.ty.clone())
+        };
+
+        if completion.is_some()
+            && !matches!(kind, ArgumentKind::Positional { .. } | ArgumentKind::Remaining)
+        {
+            return Err(Error::new(field.span(), "only positional arguments can be completed"));
+        }
+
+        Ok(CommandArgument { ident, value_type, kind, name, description, completion })
+    }
+
+    fn is_positional(&self) -> bool {
+        matches!(self.kind, ArgumentKind::Positional { .. } | ArgumentKind::Remaining)
+    }
+
+    fn option(&self) -> String {
+        format!("-{}", self.name)
+    }
+
+    fn usage(&self) -> String {
+        match self.kind {
+            ArgumentKind::Flag => format!("[-{}]", self.name),
+            ArgumentKind::Named { optional: true } => format!("[-{0} <{0}>]", self.name),
+            ArgumentKind::Named { optional: false } => format!("-{0} <{0}>", self.name),
+            ArgumentKind::Positional { optional: true } => format!("[<{}>]", self.name),
+            ArgumentKind::Positional { optional: false } => format!("<{}>", self.name),
+            ArgumentKind::Remaining => format!("[<{}>...]", self.name),
+        }
+    }
+
+    fn label(&self) -> String {
+        if self.is_positional() {
+            self.name.clone()
+        } else {
+            self.option()
+        }
+    }
+
+    // Expression that parses the string in `value` into the value type.
+    fn parse_value(&self, value: &Ident) -> TokenStream {
+        let value_type = &self.value_type;
+        let label = self.label();
+
+        quote! {
+            #value.parse::<#value_type>().map_err(|_| {
+                ::std::format!("invalid value \"{}\" for {}", #value, #label)
+            })?
+        }
+    }
+}
+
+// Collect the nested metas of all the attributes with the given name.
+fn attribute_metas(attributes: &[Attribute], name: &str) -> Result<Vec<NestedMeta>> {
+    let mut metas = Vec::new();
+
+    for attribute in attributes.iter().filter(|a| a.path.is_ident(name)) {
+        match attribute.parse_meta()? {
+            Meta::List(list) => metas.extend(list.nested),
+            meta => {
+                return Err(Error::new(meta.span(), format!("expected #[{}(...)]", name)));
+            }
+        }
+    }
+
+    Ok(metas)
+}
+
+// Join the doc comments of an item into a single line.
+fn doc_string(attributes: &[Attribute]) -> String {
+    attributes
+        .iter()
+        .filter(|a| a.path.is_ident("doc"))
+        .filter_map(|a| match a.parse_meta() {
+            Ok(Meta::NameValue(value)) => match value.lit {
+                Lit::Str(lit) => Some(lit.value().trim().to_owned()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn last_segment(ty: &Type) -> Option<&syn::PathSegment> {
+    match ty {
+        Type::Path(path) if path.qself.is_none() => path.path.segments.last(),
+        _ => None,
+    }
+}
+
+fn is_type(ty: &Type, name: &str) -> bool {
+    matches!(last_segment(ty), Some(s) if s.ident == name && s.arguments.is_empty())
+}
+
+// Get `T` out of a type of the form `Wrapper<T>`.
+fn inner_type(ty: &Type, wrapper: &str) -> Option<Type> {
+    let segment = last_segment(ty).filter(|s| s.ident == wrapper)?;
+
+    match &segment.arguments {
+        PathArguments::AngleBracketed(arguments) if arguments.args.len() == 1 => {
+            match arguments.args.first() {
+                Some(GenericArgument::Type(inner)) => Some(inner.clone()),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+pub(crate) fn derive(input: DeriveInput) -> Result<TokenStream> {
+    let struct_ident = &input.ident;
+
+    if !input.generics.params.is_empty() {
+        return Err(Error::new(input.generics.span(), "commands can't be generic"));
+    }
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            Fields::Unit => return derive_arguments(&input, Vec::new()),
+            _ => {
+                return Err(Error::new(struct_ident.span(), "expected a struct with named fields"))
+            }
+        },
+        _ => return Err(Error::new(struct_ident.span(), "expected a struct with named fields")),
+    };
+
+    let arguments = fields.iter().map(CommandArgument::from_field).collect::<Result<Vec<_>>>()?;
+
+    derive_arguments(&input, arguments)
+}
+
+fn derive_arguments(input: &DeriveInput, arguments: Vec<CommandArgument>) -> Result<TokenStream> {
+    let struct_ident = &input.ident;
+
+    let mut name = struct_ident.to_string().to_lowercase();
+    let mut description = doc_string(&input.attrs);
+
+    for meta in attribute_metas(&input.attrs, "command")? {
+        match meta {
+            NestedMeta::Meta(Meta::NameValue(value))
+                if value.path.is_ident("name") || value.path.is_ident("description") =>
+            {
+                let string = match &value.lit {
+                    Lit::Str(lit) => lit.value(),
+                    lit => return Err(Error::new(lit.span(), "expected a string")),
+                };
+
+                if value.path.is_ident("name") {
+                    name = string;
+                } else {
+                    description = string;
+                }
+            }
+            meta => return Err(Error::new(meta.span(), "expected one of name or description")),
+        }
+    }
+
+    // Optional positional arguments can only be followed by other optional
+    // ones, the remaining arguments have to come last.
+    let mut seen_optional = false;
+    let mut seen_remaining = false;
+
+    for argument in arguments.iter().filter(|a| a.is_positional()) {
+        if seen_remaining {
+            return Err(Error::new(
+                argument.ident.span(),
+                "no positional argument can follow a Vec argument",
+            ));
+        }
+
+        match argument.kind {
+            ArgumentKind::Positional { optional: false } if seen_optional => {
+                return Err(Error::new(
+                    argument.ident.span(),
+                    "a required positional argument can't follow an optional one",
+                ))
+            }
+            ArgumentKind::Positional { optional: true } => seen_optional = true,
+            ArgumentKind::Remaining => seen_remaining = true,
+            _ => (),
+        }
+    }
+
+    // Options come before the positional arguments in the usage.
+    let usage = arguments
+        .iter()
+        .filter(|a| !a.is_positional())
+        .chain(arguments.iter().filter(|a| a.is_positional()))
+        .map(|a| a.usage())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let width = arguments.iter().map(|a| a.label().len()).max().unwrap_or(0);
+    let arguments_description = arguments
+        .iter()
+        .filter(|a| !a.description.is_empty())
+        .map(|a| format!("{:>width$}: {}", a.label(), a.description, width = width))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let mut completion = Vec::new();
+
+    for argument in arguments.iter().filter(|a| a.is_positional()) {
+        match &argument.completion {
+            Some(c) => completion.push(c.clone()),
+            None => break,
+        }
+
+        if let ArgumentKind::Remaining = argument.kind {
+            completion.push("%*".to_owned());
+        }
+    }
+
+    let completion = completion.join(" ");
+    let completion = if completion.is_empty() {
+        quote! {}
+    } else {
+        quote! { .add_completion(#completion) }
+    };
+
+    let value = Ident::new("__value", proc_macro2::Span::call_site());
+
+    let flags: Vec<_> = arguments.iter().filter(|a| matches!(a.kind, ArgumentKind::Flag)).collect();
+    let flag_idents: Vec<_> = flags.iter().map(|a| &a.ident).collect();
+    let flag_options: Vec<_> = flags.iter().map(|a| a.option()).collect();
+
+    let named: Vec<_> =
+        arguments.iter().filter(|a| matches!(a.kind, ArgumentKind::Named { .. })).collect();
+    let named_idents: Vec<_> = named.iter().map(|a| &a.ident).collect();
+    let named_options: Vec<_> = named.iter().map(|a| a.option()).collect();
+
+    let conversions = arguments.iter().map(|argument| {
+        let ident = &argument.ident;
+        let parse = argument.parse_value(&value);
+        let usage = argument.usage();
+        let option = argument.option();
+
+        match argument.kind {
+            ArgumentKind::Flag => quote! {},
+            ArgumentKind::Named { optional: true } => quote! {
+                let #ident = match #ident {
+                    ::std::option::Option::Some(#value) => ::std::option::Option::Some(#parse),
+                    ::std::option::Option::None => ::std::option::Option::None,
+                };
+            },
+            ArgumentKind::Named { optional: false } => quote! {
+                let #ident = match #ident {
+                    ::std::option::Option::Some(#value) => #parse,
+                    ::std::option::Option::None => {
+                        return ::std::result::Result::Err(
+                            ::std::format!("missing option {}", #option)
+                        );
+                    }
+                };
+            },
+            ArgumentKind::Positional { optional: true } => quote! {
+                let #ident = match __positional.next() {
+                    ::std::option::Option::Some(#value) => ::std::option::Option::Some(#parse),
+                    ::std::option::Option::None => ::std::option::Option::None,
+                };
+            },
+            ArgumentKind::Positional { optional: false } => quote! {
+                let #ident = match __positional.next() {
+                    ::std::option::Option::Some(#value) => #parse,
+                    ::std::option::Option::None => {
+                        return ::std::result::Result::Err(
+                            ::std::format!("missing argument {}", #usage)
+                        );
+                    }
+                };
+            },
+            ArgumentKind::Remaining => quote! {
+                let mut #ident = ::std::vec::Vec::new();
+
+                for #value in __positional.by_ref() {
+                    #ident.push(#parse);
+                }
+            },
+        }
+    });
+
+    let idents = arguments.iter().map(|a| &a.ident);
+
+    Ok(quote! {
+        impl weechat::hooks::WeechatCommand for #struct_ident {
+            fn settings() -> weechat::hooks::CommandSettings {
+                weechat::hooks::CommandSettings::new(#name)
+                    .description(#description)
+                    .add_argument(#usage)
+                    .arguments_description(#arguments_description)
+                    #completion
+            }
+
+            #[allow(unused_mut)]
+            fn parse(
+                arguments: ::std::vec::Vec<::std::string::String>,
+            ) -> ::std::result::Result<Self, ::std::string::String> {
+                #( let mut #flag_idents = false; )*
+                #(
+                    let mut #named_idents: ::std::option::Option<::std::string::String> =
+                        ::std::option::Option::None;
+                )*
+                let mut __positional = ::std::vec::Vec::new();
+                let mut __arguments = arguments.into_iter();
+
+                while let ::std::option::Option::Some(__argument) = __arguments.next() {
+                    match __argument.as_str() {
+                        #( #flag_options => #flag_idents = true, )*
+                        #(
+                            #named_options => {
+                                #named_idents = match __arguments.next() {
+                                    ::std::option::Option::Some(#value) => {
+                                        ::std::option::Option::Some(#value)
+                                    }
+                                    ::std::option::Option::None => {
+                                        return ::std::result::Result::Err(
+                                            ::std::format!("missing value for {}", #named_options)
+                                        );
+                                    }
+                                };
+                            }
+                        )*
+                        _ => __positional.push(__argument),
+                    }
+                }
+
+                let mut __positional = __positional.into_iter();
+
+                #( #conversions )*
+
+                if let ::std::option::Option::Some(#value) = __positional.next() {
+                    return ::std::result::Result::Err(
+                        ::std::format!("unexpected argument \"{}\"", #value)
+                    );
+                }
+
+                ::std::result::Result::Ok(#struct_ident { #( #idents ),* })
+            }
+        }
+    })
+}
@@ -3,13 +3,15 @@
 extern crate proc_macro;
 use std::collections::HashMap;
 
+mod command;
+
 use proc_macro2::{Ident, Literal};
 use quote::quote;
 use syn::{
     parse::{Parse, ParseStream, Result},
     parse_macro_input,
     punctuated::Punctuated,
-    Error, LitStr,
+    DeriveInput, Error, LitStr,
 };
 
 struct WeechatPluginInfo {
@@ -19,6 +21,7 @@ struct WeechatPluginInfo {
     description: (usize, Literal),
     version: (usize, Literal),
     license: (usize, Literal),
+    priority: syn::LitInt,
 }
 
 enum WeechatVariable {
@@ -27,6 +30,7 @@ enum WeechatVariable {
     Description(syn::LitStr),
     Version(syn::LitStr),
     License(syn::LitStr),
+    Priority(syn::LitInt),
 }
 
 impl WeechatVariable {
@@ -46,6 +50,7 @@ impl WeechatVariable {
             WeechatVariable::Description(string) => WeechatVariable::to_pair(string),
             WeechatVariable::Version(string) => WeechatVariable::to_pair(string),
             WeechatVariable::License(string) => WeechatVariable::to_pair(string),
+            WeechatVariable::Priority(_) => unreachable!("the priority isn't a string"),
         }
     }
 
@@ -59,17 +64,17 @@ impl Parse for WeechatVariable {
     fn parse(input: ParseStream) -> Result<Self> {
         let key: Ident = input.parse()?;
         input.parse::<syn::Token![:]>()?;
-        let value = input.parse()?;
 
         match key.to_string().to_lowercase().as_ref() {
-            "name" => Ok(WeechatVariable::Name(value)),
-            "author" => Ok(WeechatVariable::Author(value)),
-            "description" => Ok(WeechatVariable::Description(value)),
-            "version" => Ok(WeechatVariable::Version(value)),
-            "license" => Ok(WeechatVariable::License(value)),
+            "name" => Ok(WeechatVariable::Name(input.parse()?)),
+            "author" => Ok(WeechatVariable::Author(input.parse()?)),
+            "description" => Ok(WeechatVariable::Description(input.parse()?)),
+            "version" => Ok(WeechatVariable::Version(input.parse()?)),
+            "license" => Ok(WeechatVariable::License(input.parse()?)),
+            "priority" => Ok(WeechatVariable::Priority(input.parse()?)),
             _ => Err(Error::new(
                 key.span(),
-                "expected one of name, author, description, version or license",
+                "expected one of name, author, description, version, license or priority",
             )),
         }
     }
@@ -94,6 +99,7 @@ impl Parse for WeechatPluginInfo {
                 WeechatVariable::Description(_) => variables.insert("description", *variable),
                 WeechatVariable::Version(_) => variables.insert("version", *variable),
                 WeechatVariable::License(_) => variables.insert("license", *variable),
+                WeechatVariable::Priority(_) => variables.insert("priority", *variable),
             };
         }
 
@@ -115,6 +121,10 @@ impl Parse for WeechatPluginInfo {
             license: variables
                 .remove("license")
                 .map_or_else(WeechatVariable::default_literal, |v| v.as_pair()),
+            priority: match variables.remove("priority") {
+                Some(WeechatVariable::Priority(priority)) => priority.clone(),
+                _ => syn::LitInt::new("1000", input.span()),
+            },
         })
     }
 }
@@ -124,6 +134,10 @@ impl Parse for WeechatPluginInfo {
 /// This configures the Weechat init and end method as well as additional plugin
 /// metadata.
 ///
+/// The `priority` decides the order in which plugins are loaded, plugins with a
+/// higher priority are loaded first. If no priority is given the Weechat
+/// default of 1000 is used.
+///
 /// # Example
 /// ```
 /// # use weechat::{plugin, Args, Weechat, Plugin};
@@ -139,12 +153,13 @@ impl Parse for WeechatPluginInfo {
 ///     author: "poljar",
 ///     description: "",
 ///     version: "0.1.0",
-///     license: "MIT"
+///     license: "MIT",
+///     priority: 1000
 /// );
 /// ```
 #[proc_macro]
 pub fn plugin(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    let WeechatPluginInfo { plugin, name, author, description, version, license } =
+    let WeechatPluginInfo { plugin, name, author, description, version, license, priority } =
         parse_macro_input!(input as WeechatPluginInfo);
 
     let (name_len, name) = name;
@@ -179,6 +194,10 @@ pub fn plugin(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
         #[no_mangle]
         pub static weechat_plugin_license: [u8; #license_len] = *#license;
 
+        #[doc(hidden)]
+        #[no_mangle]
+        pub static weechat_plugin_priority: weechat::libc::c_int = #priority;
+
         #[doc(hidden)]
         static mut __PLUGIN: Option<#plugin> = None;
 
@@ -248,3 +267,154 @@ pub fn plugin(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
 
     result.into()
 }
+
+/// Derive the `WeechatCommand` trait for a struct.
+///
+/// The fields of the struct become the arguments of the command, the
+/// arguments, the argument description and the completion of the command are
+/// generated from them. The doc comments of the fields are used as the
+/// description of the arguments. The type of the field decides how it's
+/// passed to the command:
+///
+/// * `bool` - A flag, `-name` sets the field to true.
+/// * `Option<T>` - An optional positional argument, or an optional option if
+///   the field is marked with `#[arg(named)]`.
+/// * `Vec<T>` - All the remaining positional arguments, this has to be the
+///   last positional argument.
+/// * `T` - A required positional argument, or a required option if the field
+///   is marked with `#[arg(named)]`.
+///
+/// Named options are passed as `-name <value>`, underscores in field names
+/// are replaced with dashes. Values are parsed using `FromStr`. Arguments
+/// that start with a dash but aren't a known option are treated as
+/// positional arguments, so negative numbers can be passed.
+///
+/// Positional arguments can be completed using `#[arg(completion = "...")]`,
+/// the completion is generated up to the first positional argument that
+/// doesn't set one. Options aren't completed.
+///
+/// The name of the command defaults to the lowercase name of the struct and
+/// its description to the doc comment of the struct, both can be set with
+/// `#[command(name = "...", description = "...")]`.
+///
+/// # Example
+/// ```
+/// # use weechat::hooks::WeechatCommand;
+/// # let args = |args: &[&str]| args.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+/// /// Search the messages of a buffer.
+/// #[derive(Debug, PartialEq, WeechatCommand)]
+/// #[command(name = "search")]
+/// struct Search {
+///     /// Match the case of the pattern.
+///     case_sensitive: bool,
+///     /// The maximal number of results.
+///     #[arg(named)]
+///     limit: Option<usize>,
+///     /// The buffer that should be searched.
+///     #[arg(completion = "%(buffers_names)")]
+///     buffer: String,
+///     /// Skip this many results, negative values count from the end.
+///     offset: Option<i32>,
+///     /// The words to search for.
+///     words: Vec<String>,
+/// }
+///
+/// let search = Search::parse(args(&[
+///     "-limit", "10", "core.weechat", "-2", "foo", "-case-sensitive", "bar",
+/// ]));
+///
+/// assert_eq!(
+///     search,
+///     Ok(Search {
+///         case_sensitive: true,
+///         limit: Some(10),
+///         buffer: "core.weechat".to_owned(),
+///         offset: Some(-2),
+///         words: vec!["foo".to_owned(), "bar".to_owned()],
+///     })
+/// );
+///
+/// assert_eq!(
+///     Search::parse(args(&["core.weechat"])),
+///     Ok(Search {
+///         case_sensitive: false,
+///         limit: None,
+///         buffer: "core.weechat".to_owned(),
+///         offset: None,
+///         words: vec![],
+///     })
+/// );
+///
+/// assert_eq!(Search::parse(args(&[])), Err("missing argument <buffer>".to_owned()));
+/// assert_eq!(
+///     Search::parse(args(&["core.weechat", "-limit"])),
+///     Err("missing value for -limit".to_owned())
+/// );
+/// assert_eq!(
+///     Search::parse(args(&["-limit", "many", "core.weechat"])),
+///     Err("invalid value \"many\" for -limit".to_owned())
+/// );
+/// assert_eq!(
+///     Search::parse(args(&["core.weechat", "first"])),
+///     Err("invalid value \"first\" for offset".to_owned())
+/// );
+/// ```
+///
+/// Named options that aren't wrapped in an `Option` have to be passed, and
+/// arguments that don't belong to any field are rejected:
+///
+/// ```
+/// # use weechat::hooks::WeechatCommand;
+/// # let args = |args: &[&str]| args.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+/// /// Kick a nick from the channel.
+/// #[derive(Debug, PartialEq, WeechatCommand)]
+/// struct Kick {
+///     /// The reason for the kick.
+///     #[arg(named)]
+///     reason: String,
+///     /// The nick that should be kicked.
+///     nick: String,
+/// }
+///
+/// assert_eq!(
+///     Kick::parse(args(&["alice", "-reason", "spam"])),
+///     Ok(Kick { reason: "spam".to_owned(), nick: "alice".to_owned() })
+/// );
+/// assert_eq!(Kick::parse(args(&["alice"])), Err("missing option -reason".to_owned()));
+/// assert_eq!(
+///     Kick::parse(args(&["-reason", "spam", "alice", "bob"])),
+///     Err("unexpected argument \"bob\"".to_owned())
+/// );
+/// ```
+///
+/// Positional arguments are assigned in order, so a required positional
+/// argument can't follow an optional one:
+///
+/// ```compile_fail
+/// # use weechat::hooks::WeechatCommand;
+/// #[derive(WeechatCommand)]
+/// struct Kick {
+///     channel: Option<String>,
+///     nick: String,
+/// }
+/// ```
+///
+/// And no positional argument can follow a `Vec` argument:
+///
+/// ```compile_fail
+/// # use weechat::hooks::WeechatCommand;
+/// #[derive(WeechatCommand)]
+/// struct Search {
+///     words: Vec<String>,
+///     buffer: String,
+/// }
+/// ```
+#[proc_macro_derive(WeechatCommand, attributes(command, arg))]
+pub fn weechat_command(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    match command::derive(input) {
+        Ok(tokens) => tokens.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
@@ -23,3 +23,22 @@ pub const WEECHAT_CONFIG_OPTION_SET_OPTION_NOT_FOUND: c_int = -1;
 pub const WEECHAT_CONFIG_READ_OK: c_int = 0;
 pub const WEECHAT_CONFIG_READ_MEMORY_ERROR: c_int = -1;
 pub const WEECHAT_CONFIG_READ_FILE_NOT_FOUND: c_int = -2;
+
+/* connect status for connection hooked */
+pub const WEECHAT_HOOK_CONNECT_OK: c_int = 0;
+pub const WEECHAT_HOOK_CONNECT_ADDRESS_NOT_FOUND: c_int = 1;
+pub const WEECHAT_HOOK_CONNECT_IP_ADDRESS_NOT_FOUND: c_int = 2;
+pub const WEECHAT_HOOK_CONNECT_CONNECTION_REFUSED: c_int = 3;
+pub const WEECHAT_HOOK_CONNECT_PROXY_ERROR: c_int = 4;
+pub const WEECHAT_HOOK_CONNECT_LOCAL_HOSTNAME_ERROR: c_int = 5;
+pub const WEECHAT_HOOK_CONNECT_GNUTLS_INIT_ERROR: c_int = 6;
+pub const WEECHAT_HOOK_CONNECT_GNUTLS_HANDSHAKE_ERROR: c_int = 7;
+pub const WEECHAT_HOOK_CONNECT_MEMORY_ERROR: c_int = 8;
+pub const WEECHAT_HOOK_CONNECT_TIMEOUT: c_int = 9;
+pub const WEECHAT_HOOK_CONNECT_SOCKET_ERROR: c_int = 10;
+
+/* flags for string_split function */
+pub const WEECHAT_STRING_SPLIT_STRIP_LEFT: c_int = 1 << 0;
+pub const WEECHAT_STRING_SPLIT_STRIP_RIGHT: c_int = 1 << 1;
+pub const WEECHAT_STRING_SPLIT_COLLAPSE_SEPS: c_int = 1 << 2;
+pub const WEECHAT_STRING_SPLIT_KEEP_EOL: c_int = 1 << 3;
@@ -0,0 +1,11 @@
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+
+    // The print functions of the mocked Weechat context are variadic, stable
+    // Rust can't define those so they are implemented in C.
+    #[cfg(feature = "testing")]
+    {
+        println!("cargo:rerun-if-changed=src/testing/printf.c");
+        cc::Build::new().file("src/testing/printf.c").compile("weechat_mock");
+    }
+}
@@ -40,8 +40,8 @@ use weechat::{
     buffer::Buffer,
     config,
     hooks::{
-        Command, CommandCallback, CommandRun, CommandRunCallback, CommandSettings,
-        ModifierCallback, ModifierData, ModifierHook,
+        Command, CommandCallback, CommandRun, CommandRunCallback, CommandRunContext,
+        CommandSettings, ModifierCallback, ModifierData, ModifierHook,
     },
     infolist::InfolistVariable,
     plugin, Args, Plugin, Prefix, ReturnCode, Weechat,
@@ -435,14 +435,14 @@ impl Hooks {
 
         // Disable buffer commands while in go mode.
         let buffer_command =
-            CommandRun::new("2000|/buffer *", |_: &Weechat, _: &Buffer, _: Cow<str>| {
+            CommandRun::new("2000|/buffer *", |_: &Weechat, _: &CommandRunContext| {
                 ReturnCode::OkEat
             })
             .expect("Can't override buffer command");
 
         // Disable window commands while in go mode.
         let window_command =
-            CommandRun::new("2000|/window *", |_: &Weechat, _: &Buffer, _: Cow<str>| {
+            CommandRun::new("2000|/window *", |_: &Weechat, _: &CommandRunContext| {
                 ReturnCode::OkEat
             })
             .expect("Can't override window command");
@@ -563,12 +563,14 @@ impl ModifierCallback for InnerGo {
 
 /// Callback for our `/input` command override.
 impl CommandRunCallback for InnerGo {
-    fn callback(&mut self, weechat: &Weechat, _: &Buffer, command: Cow<str>) -> ReturnCode {
+    fn callback(&mut self, weechat: &Weechat, context: &CommandRunContext) -> ReturnCode {
+        let command = context.command();
+
         if command.starts_with("/input search_text") || command.starts_with("/input jump") {
             return ReturnCode::OkEat;
         }
 
-        match command.as_ref() {
+        match command {
             "/input return" => {
                 self.stop(weechat, true);
                 ReturnCode::OkEat
@@ -0,0 +1,102 @@
+use std::{cell::RefCell, rc::Rc};
+
+use weechat_sys::t_gui_buffer;
+
+use crate::{
+    buffer::Buffer,
+    data_map::DataMap,
+    hooks::{SignalData, SignalHook},
+    ReturnCode, Weechat,
+};
+
+thread_local! {
+    static BUFFER_DATA: DataMap<*mut t_gui_buffer> = DataMap::new();
+    static CLOSING_HOOK: RefCell<Option<SignalHook>> = const { RefCell::new(None) };
+}
+
+/// Make sure that the data of closed buffers gets dropped.
+fn ensure_closing_hook() -> Result<(), ()> {
+    CLOSING_HOOK.with(|hook| {
+        let mut hook = hook.borrow_mut();
+
+        if hook.is_none() {
+            *hook = Some(SignalHook::new(
+                "buffer_closing",
+                |_: &Weechat, _: &str, data: Option<SignalData>| {
+                    if let Some(SignalData::Buffer(buffer)) = data {
+                        BUFFER_DATA.with(|data| data.remove(&buffer.ptr()));
+                    }
+                    ReturnCode::Ok
+                },
+            )?);
+        }
+
+        Ok(())
+    })
+}
+
+/// Drop all the attached buffer data, called when the plugin is unloaded.
+pub(crate) fn free_buffer_data() {
+    BUFFER_DATA.with(|data| data.clear());
+    let hook = CLOSING_HOOK.with(|hook| hook.borrow_mut().take());
+    drop(hook);
+}
+
+impl Buffer<'_> {
+    /// Attach data to the buffer.
+    ///
+    /// Only one value can be attached to a buffer, previously attached data is
+    /// replaced. The data is dropped once the buffer is closed.
+    ///
+    /// Returns an error if the data couldn't be attached.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The data that should be attached to the buffer.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use std::cell::RefCell;
+    /// # use weechat::Weechat;
+    /// # let weechat = unsafe { Weechat::weechat() };
+    /// # let buffer = weechat.current_buffer();
+    /// struct RoomState {
+    ///     unread: RefCell<u32>,
+    /// }
+    ///
+    /// buffer.set_data(RoomState { unread: RefCell::new(0) }).unwrap();
+    ///
+    /// if let Some(state) = buffer.data::<RoomState>() {
+    ///     *state.unread.borrow_mut() += 1;
+    /// }
+    /// ```
+    pub fn set_data<T: 'static>(&self, data: T) -> Result<(), ()> {
+        ensure_closing_hook()?;
+        BUFFER_DATA.with(|buffer_data| buffer_data.insert(self.ptr(), data));
+
+        Ok(())
+    }
+
+    /// Get the data that was attached to the buffer.
+    ///
+    /// Returns `None` if no data is attached to the buffer, if the attached
+    /// data has a different type or if the buffer was closed.
+    ///
+    /// The data is returned as an `Rc` instead of a `Ref` guard. A guard
+    /// would keep the buffer data registry borrowed while it's alive, so
+    /// replacing the data, closing the buffer or accessing the data of another
+    /// buffer from a nested callback would panic. Use a `Cell` or a `RefCell`
+    /// inside of the data if it needs to be modified.
+    pub fn data<T: 'static>(&self) -> Option<Rc<T>> {
+        BUFFER_DATA.with(|data| data.get(&self.ptr()))
+    }
+
+    /// Remove the data that was attached to the buffer.
+    ///
+    /// Returns the data if data of the given type was attached, data of a
+    /// different type is left attached.
+    pub fn take_data<T: 'static>(&self) -> Option<Rc<T>> {
+        BUFFER_DATA.with(|data| data.take(&self.ptr()))
+    }
+}
@@ -0,0 +1,168 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{
+    hooks::{SignalData, SignalHook},
+    ReturnCode, Weechat,
+};
+
+/// A browser like history of the buffers the user viewed.
+///
+/// The history is fed by a `buffer_switch` signal hook, the hook is removed
+/// when the object is dropped. Buffers are remembered by their full name,
+/// buffers that were closed in the meantime are skipped when moving through
+/// the history.
+pub struct BufferHistory {
+    state: Rc<RefCell<HistoryState>>,
+    _hook: SignalHook,
+}
+
+struct HistoryState {
+    entries: Vec<String>,
+    position: usize,
+    max_entries: usize,
+    // Set while the history itself switches the buffer, the switch shouldn't
+    // be recorded.
+    navigating: bool,
+}
+
+impl HistoryState {
+    fn push(&mut self, full_name: String) {
+        if self.entries.get(self.position) == Some(&full_name) {
+            return;
+        }
+
+        // Visiting a new buffer drops the forward history, like in a browser.
+        self.entries.truncate(self.position + 1);
+        self.entries.push(full_name);
+
+        if self.entries.len() > self.max_entries {
+            let excess = self.entries.len() - self.max_entries;
+            self.entries.drain(..excess);
+        }
+
+        self.position = self.entries.len() - 1;
+    }
+}
+
+impl BufferHistory {
+    /// Start recording the buffers the user views.
+    ///
+    /// The current buffer is the first entry of the history.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_entries` - The maximal number of buffers that are remembered,
+    ///   the oldest entries are dropped first. A value of 0 is treated as 1.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use weechat::{Weechat, Args};
+    /// # use weechat::buffer::{Buffer, BufferHistory};
+    /// # use weechat::hooks::{Command, CommandSettings};
+    /// let history = BufferHistory::new(50).expect("Can't create the buffer history");
+    ///
+    /// let command = Command::new(
+    ///     CommandSettings::new("back"),
+    ///     move |_: &Weechat, buffer: &Buffer, _: Args| {
+    ///         if !history.back() {
+    ///             buffer.print("No previous buffer");
+    ///         }
+    ///     },
+    /// );
+    /// ```
+    pub fn new(max_entries: usize) -> Result<Self, ()> {
+        Weechat::check_thread();
+        let weechat = unsafe { Weechat::weechat() };
+
+        let state = Rc::new(RefCell::new(HistoryState {
+            entries: vec![weechat.current_buffer().full_name().to_string()],
+            position: 0,
+            max_entries: max_entries.max(1),
+            navigating: false,
+        }));
+
+        let hook_state = state.clone();
+        let hook = SignalHook::new(
+            "buffer_switch",
+            move |_: &Weechat, _: &str, data: Option<SignalData>| {
+                if let Some(SignalData::Buffer(buffer)) = data {
+                    let mut state = hook_state.borrow_mut();
+
+                    if !state.navigating {
+                        state.push(buffer.full_name().to_string());
+                    }
+                }
+
+                ReturnCode::Ok
+            },
+        )?;
+
+        Ok(BufferHistory { state, _hook: hook })
+    }
+
+    /// Switch to the previously viewed buffer.
+    ///
+    /// Returns false if there is no previous buffer that is still open.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    pub fn back(&self) -> bool {
+        let position = self.state.borrow().position;
+        self.switch_to_first((0..position).rev())
+    }
+
+    /// Switch to the buffer that was viewed before going back.
+    ///
+    /// Returns false if there is no next buffer that is still open.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    pub fn forward(&self) -> bool {
+        let (position, len) = {
+            let state = self.state.borrow();
+            (state.position, state.entries.len())
+        };
+
+        self.switch_to_first(position + 1..len)
+    }
+
+    /// Get the full names of the buffers in the history, the oldest entry
+    /// comes first.
+    pub fn entries(&self) -> Vec<String> {
+        self.state.borrow().entries.clone()
+    }
+
+    // Switch to the first buffer of the given entries that is still open.
+    fn switch_to_first(&self, positions: impl Iterator<Item = usize>) -> bool {
+        Weechat::check_thread();
+        let weechat = unsafe { Weechat::weechat() };
+
+        for position in positions {
+            let full_name = self.state.borrow().entries[position].clone();
+
+            if let Some(buffer) = weechat.buffer_search("==", &full_name) {
+                {
+                    let mut state = self.state.borrow_mut();
+                    state.position = position;
+                    state.navigating = true;
+                }
+
+                // The switch fires our own signal hook, the state can't be
+                // borrowed while it runs.
+                buffer.switch_to();
+                self.state.borrow_mut().navigating = false;
+
+                return true;
+            }
+        }
+
+        false
+    }
+}
@@ -0,0 +1,154 @@
+use std::time::Duration;
+
+use crate::{
+    buffer::{Buffer, BufferLine},
+    hooks::{RemainingCalls, TimerHook},
+    Weechat,
+};
+
+/// A limit for the number of lines of a buffer, the limit is removed when the
+/// object is dropped.
+pub struct LinesLimit {
+    _timer: TimerHook,
+}
+
+// A copy of a line that is printed again after the buffer was cleared.
+struct SavedLine {
+    date: i64,
+    tags: Vec<String>,
+    prefix: String,
+    message: String,
+}
+
+impl<'a> Buffer<'a> {
+    /// Limit the number of lines the buffer keeps.
+    ///
+    /// Weechat only supports a global limit for the number of lines of a
+    /// buffer, see the `weechat.history.max_buffer_lines_number` option. To
+    /// limit a single buffer, the buffer is checked once per second, if it
+    /// has more lines than the limit the buffer is cleared and the most recent
+    /// lines are printed again. The read marker stays at the same line, if it
+    /// was on one of the removed lines all the lines are marked as unread.
+    ///
+    /// The lines are printed again with the `no_log` and `notify_none` tags,
+    /// print hooks will be called for them a second time.
+    ///
+    /// Returns an error if the buffer has free content.
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - The number of lines the buffer should keep.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use weechat::buffer::BufferBuilder;
+    /// let buffer_handle = BufferBuilder::new("debug").build().unwrap();
+    /// let buffer = buffer_handle.upgrade().unwrap();
+    ///
+    /// let limit = buffer.set_lines_limit(1000).expect("Can't limit the buffer");
+    /// ```
+    pub fn set_lines_limit(&self, limit: usize) -> Result<LinesLimit, ()> {
+        if self.has_free_content() {
+            return Err(());
+        }
+
+        let full_name = self.full_name().to_string();
+
+        let timer = TimerHook::new(
+            Duration::from_secs(1),
+            0,
+            0,
+            move |weechat: &Weechat, _: RemainingCalls| {
+                if let Some(buffer) = weechat.buffer_search("==", &full_name) {
+                    buffer.trim_lines(limit);
+                }
+            },
+        )?;
+
+        Ok(LinesLimit { _timer: timer })
+    }
+
+    fn trim_lines(&self, limit: usize) {
+        if self.num_lines() as usize <= limit {
+            return;
+        }
+
+        let weechat = self.weechat();
+        let own_lines = self.own_lines();
+
+        let (mut line, last_read_line, first_line_not_read) = unsafe {
+            let hdata = weechat.hdata_get("lines");
+
+            (
+                weechat.hdata_pointer(hdata, own_lines, "last_line"),
+                weechat.hdata_pointer(hdata, own_lines, "last_read_line"),
+                weechat.hdata_char(hdata, own_lines, "first_line_not_read") != 0,
+            )
+        };
+
+        let line_hdata = unsafe { weechat.hdata_get("line") };
+        let mut saved = Vec::with_capacity(limit);
+        let mut read_marker = None;
+
+        // Walk backwards from the last line, remembering where the read
+        // marker is.
+        while !line.is_null() && saved.len() < limit {
+            if line == last_read_line {
+                read_marker = Some(saved.len());
+            }
+
+            let data = unsafe { weechat.hdata_pointer(line_hdata, line, "data") };
+            let buffer_line = BufferLine::from_ptr(Weechat::from_ptr(weechat.ptr), data);
+
+            saved.push(SavedLine {
+                date: buffer_line.date(),
+                tags: buffer_line.tags().iter().map(|t| t.to_string()).collect(),
+                prefix: buffer_line.prefix().into_owned(),
+                message: buffer_line.message().into_owned(),
+            });
+
+            line = unsafe { weechat.hdata_move(line_hdata, line, -1) };
+        }
+
+        saved.reverse();
+
+        // The number of read lines, the read marker is placed after them.
+        let read_lines = match read_marker {
+            Some(index) => Some(saved.len() - index),
+            None if !last_read_line.is_null() || first_line_not_read => Some(0),
+            None => None,
+        };
+
+        self.clear();
+
+        for (index, line) in saved.iter().enumerate() {
+            if read_lines == Some(index) {
+                self.set("unread", "1");
+            }
+
+            let tags: Vec<&str> = line
+                .tags
+                .iter()
+                .map(|t| t.as_str())
+                .chain(["no_log", "notify_none"].iter().copied())
+                .collect();
+
+            self.print_date_tags(line.date, &tags, &format!("{}\t{}", line.prefix, line.message));
+
+            // Restore the original tags of the line.
+            if let Some(printed) = self.lines().next_back() {
+                let tags: Vec<&str> = line.tags.iter().map(|t| t.as_str()).collect();
+                printed.set_tags(&tags);
+            }
+        }
+
+        if read_lines == Some(saved.len()) {
+            self.set("unread", "1");
+        }
+    }
+}
@@ -89,6 +89,14 @@ pub struct BufferLine<'a> {
 }
 
 impl<'a> BufferLine<'a> {
+    pub(crate) fn from_ptr(weechat: Weechat, line_data_pointer: *mut c_void) -> Self {
+        BufferLine { weechat, line_data_pointer, buffer: PhantomData }
+    }
+
+    pub(crate) fn ptr(&self) -> *mut c_void {
+        self.line_data_pointer
+    }
+
     fn hdata(&self) -> *mut t_hdata {
         unsafe { self.weechat.hdata_get("line_data") }
     }
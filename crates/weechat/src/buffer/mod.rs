@@ -1,18 +1,25 @@
 //! Weechat Buffer module containing Buffer and Nick types.
 
+mod data;
+mod history;
+mod limit;
 mod lines;
 mod nick;
 mod nickgroup;
+mod tags;
 mod window;
 
 use std::{
     borrow::Cow,
     cell::Cell,
     cmp::{Ord, Ordering},
+    collections::HashMap,
     ffi::{c_void, CStr},
     marker::PhantomData,
+    ops::Range,
     ptr,
     rc::Rc,
+    time::Duration,
 };
 
 #[cfg(feature = "async")]
@@ -21,16 +28,23 @@ use async_trait::async_trait;
 use futures::future::LocalBoxFuture;
 use libc::{c_char, c_int};
 use weechat_sys::{
-    t_gui_buffer, t_gui_nick, t_hdata, t_weechat_plugin, WEECHAT_RC_ERROR, WEECHAT_RC_OK,
+    t_gui_buffer, t_gui_nick, t_gui_window, t_hdata, t_weechat_plugin, WEECHAT_RC_ERROR,
+    WEECHAT_RC_OK,
 };
 
+pub(crate) use crate::buffer::data::free_buffer_data;
 pub use crate::buffer::{
+    history::BufferHistory,
+    limit::LinesLimit,
     lines::{BufferLine, BufferLines, LineData},
     nick::{Nick, NickSettings},
     nickgroup::NickGroup,
+    tags::{NotifyLevel, Tags},
     window::Window,
 };
-use crate::{LossyCString, Weechat};
+use crate::{
+    hooks::CommandRunGuard, take_weechat_string, Casemapping, LossyCString, Unsupported, Weechat,
+};
 
 /// A Weechat buffer.
 ///
@@ -87,9 +101,11 @@ pub(crate) struct InnerBuffer<'a> {
     pub(crate) closing: Rc<Cell<bool>>,
 }
 
+/// Buffers are equal if they point to the same underlying Weechat buffer, see
+/// `Buffer::ptr_eq()`.
 impl PartialEq for Buffer<'_> {
     fn eq(&self, other: &Buffer) -> bool {
-        self.ptr() == other.ptr()
+        self.ptr_eq(other)
     }
 }
 
@@ -259,6 +275,102 @@ pub struct BufferBuilderAsync {
     pub(crate) name: String,
     pub(crate) input_callback: Option<Box<dyn BufferInputCallbackAsync>>,
     pub(crate) close_callback: Option<Box<dyn BufferCloseCallback>>,
+    pub(crate) properties: Vec<(String, String)>,
+    pub(crate) merge_with: Option<String>,
+}
+
+/// A line that should be printed with `Buffer::print_batch()`.
+#[derive(Debug, Clone)]
+pub struct PrintLine {
+    date: i64,
+    tags: Vec<String>,
+    message: String,
+}
+
+impl PrintLine {
+    /// Create a new line that will be printed with the current date and no
+    /// tags.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The message of the line, a prefix can be set by
+    ///   separating it from the message with a tab character.
+    pub fn new<M: Into<String>>(message: M) -> Self {
+        PrintLine { date: 0, tags: Vec::new(), message: message.into() }
+    }
+
+    /// Set the date of the line.
+    ///
+    /// # Arguments
+    ///
+    /// * `date` - A unix time-stamp representing the date of the message, 0
+    ///   means now.
+    pub fn date(mut self, date: i64) -> Self {
+        self.date = date;
+        self
+    }
+
+    /// Add a tag to the line.
+    ///
+    /// # Arguments
+    ///
+    /// * `tag` - The tag that should be added.
+    pub fn add_tag<T: Into<String>>(mut self, tag: T) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+}
+
+/// Options for running a command with `Buffer::run_command_with_options()`.
+#[derive(Debug, Clone, Default)]
+pub struct CommandOptions {
+    commands: Option<String>,
+    delay: Option<Duration>,
+    no_command_run: bool,
+}
+
+impl CommandOptions {
+    /// Create new default command options.
+    pub fn new() -> Self {
+        CommandOptions::default()
+    }
+
+    /// Restrict the commands that are allowed to run.
+    ///
+    /// # Arguments
+    ///
+    /// * `commands` - A comma separated list of allowed commands, the list is
+    ///   passed to Weechat verbatim so wildcards and exclusions are supported,
+    ///   e.g. `*,!exec`.
+    pub fn commands<C: Into<String>>(mut self, commands: C) -> Self {
+        self.commands = Some(commands.into());
+        self
+    }
+
+    /// Delay the execution of the command.
+    ///
+    /// Older Weechat versions ignore this option.
+    ///
+    /// # Arguments
+    ///
+    /// * `delay` - How long Weechat should wait before running the command.
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+
+    /// Don't trigger the `CommandRun` hooks of this plugin for the command.
+    ///
+    /// This is useful to re-issue a command from within a `CommandRun`
+    /// callback without ending up in a loop. Hooks of other plugins and
+    /// scripts are still run.
+    ///
+    /// Delayed commands run after `Buffer::run_command_with_options()`
+    /// returns, this option has no effect on them.
+    pub fn no_command_run(mut self) -> Self {
+        self.no_command_run = true;
+        self
+    }
 }
 
 /// Builder for the creation of a buffer.
@@ -266,6 +378,8 @@ pub struct BufferBuilder {
     pub(crate) name: String,
     pub(crate) input_callback: Option<Box<dyn BufferInputCallback>>,
     pub(crate) close_callback: Option<Box<dyn BufferCloseCallback>>,
+    pub(crate) properties: Vec<(String, String)>,
+    pub(crate) merge_with: Option<String>,
 }
 
 #[cfg(feature = "async")]
@@ -312,7 +426,13 @@ impl BufferBuilderAsync {
     /// buffer.print("Hello world");
     /// ```
     pub fn new(name: &str) -> Self {
-        BufferBuilderAsync { name: name.to_owned(), input_callback: None, close_callback: None }
+        BufferBuilderAsync {
+            name: name.to_owned(),
+            input_callback: None,
+            close_callback: None,
+            properties: Vec::new(),
+            merge_with: None,
+        }
     }
 
     /// Set the buffer input callback.
@@ -337,6 +457,51 @@ impl BufferBuilderAsync {
         self
     }
 
+    /// Set properties that the buffer should get once it's created.
+    ///
+    /// The properties are set right after the buffer is created, so the
+    /// `buffer_opened` signal is sent before they are applied. Weechat 4.3.0
+    /// added `buffer_new_props()` which applies them while the buffer is
+    /// created, the bindings are generated from an older plugin header that
+    /// doesn't contain the function, so it can't be used.
+    ///
+    /// # Arguments
+    ///
+    /// * `properties` - A list of property names and values, the same
+    ///   properties that `Buffer::set_properties()` accepts can be used.
+    pub fn properties(mut self, properties: &[(&str, &str)]) -> Self {
+        self.properties.extend(properties.iter().map(|(p, v)| ((*p).to_owned(), (*v).to_owned())));
+        self
+    }
+
+    /// Set the number the buffer should be moved to once it's created.
+    ///
+    /// Buffers that already use this number, or a higher one, are shifted by
+    /// one.
+    ///
+    /// # Arguments
+    ///
+    /// * `number` - The number the buffer should get.
+    pub fn number(mut self, number: u16) -> Self {
+        self.properties.push(("number".to_owned(), number.to_string()));
+        self
+    }
+
+    /// Merge the buffer with an existing buffer once it's created.
+    ///
+    /// The buffer takes the number of the target buffer, which overrides a
+    /// number set with `number()`. Nothing is merged if the target buffer
+    /// doesn't exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `full_name` - The full name of the buffer the new buffer should be
+    ///   merged with, e.g. `myplugin.main`.
+    pub fn merge_with(mut self, full_name: &str) -> Self {
+        self.merge_with = Some(full_name.to_owned());
+        self
+    }
+
     /// Build the configured buffer.
     pub fn build(self) -> Result<BufferHandle, ()> {
         Weechat::buffer_new_with_async(self)
@@ -384,7 +549,13 @@ impl BufferBuilder {
     /// buffer.print("Hello world");
     /// ```
     pub fn new(name: &str) -> Self {
-        BufferBuilder { name: name.to_owned(), input_callback: None, close_callback: None }
+        BufferBuilder {
+            name: name.to_owned(),
+            input_callback: None,
+            close_callback: None,
+            properties: Vec::new(),
+            merge_with: None,
+        }
     }
 
     /// Set the buffer input callback.
@@ -408,6 +579,51 @@ impl BufferBuilder {
         self
     }
 
+    /// Set properties that the buffer should get once it's created.
+    ///
+    /// The properties are set right after the buffer is created, so the
+    /// `buffer_opened` signal is sent before they are applied. Weechat 4.3.0
+    /// added `buffer_new_props()` which applies them while the buffer is
+    /// created, the bindings are generated from an older plugin header that
+    /// doesn't contain the function, so it can't be used.
+    ///
+    /// # Arguments
+    ///
+    /// * `properties` - A list of property names and values, the same
+    ///   properties that `Buffer::set_properties()` accepts can be used.
+    pub fn properties(mut self, properties: &[(&str, &str)]) -> Self {
+        self.properties.extend(properties.iter().map(|(p, v)| ((*p).to_owned(), (*v).to_owned())));
+        self
+    }
+
+    /// Set the number the buffer should be moved to once it's created.
+    ///
+    /// Buffers that already use this number, or a higher one, are shifted by
+    /// one.
+    ///
+    /// # Arguments
+    ///
+    /// * `number` - The number the buffer should get.
+    pub fn number(mut self, number: u16) -> Self {
+        self.properties.push(("number".to_owned(), number.to_string()));
+        self
+    }
+
+    /// Merge the buffer with an existing buffer once it's created.
+    ///
+    /// The buffer takes the number of the target buffer, which overrides a
+    /// number set with `number()`. Nothing is merged if the target buffer
+    /// doesn't exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `full_name` - The full name of the buffer the new buffer should be
+    ///   merged with, e.g. `myplugin.main`.
+    pub fn merge_with(mut self, full_name: &str) -> Self {
+        self.merge_with = Some(full_name.to_owned());
+        self
+    }
+
     /// Build the configured buffer.
     pub fn build(self) -> Result<BufferHandle, ()> {
         Weechat::buffer_new(self)
@@ -464,6 +680,75 @@ impl Weechat {
         }
     }
 
+    /// Get an iterator over all the buffers, in the order of their numbers.
+    pub fn buffers(&self) -> impl Iterator<Item = Buffer<'_>> {
+        let hdata = unsafe { self.hdata_get("buffer") };
+        let mut ptr = unsafe { self.hdata_get_list(hdata, "gui_buffers") };
+
+        std::iter::from_fn(move || {
+            if ptr.is_null() {
+                return None;
+            }
+
+            let buffer = self.buffer_from_ptr(ptr as *mut t_gui_buffer);
+            ptr = unsafe { self.hdata_move(hdata, ptr, 1) };
+
+            Some(buffer)
+        })
+    }
+
+    /// Mark all the buffers as read.
+    ///
+    /// The read marker of every buffer is moved to the bottom and the
+    /// buffers are removed from the hotlist. The buffer that is currently
+    /// displayed is never added to the hotlist by Weechat, for it only the
+    /// read marker is moved.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use weechat::Weechat;
+    /// # let weechat = unsafe { Weechat::weechat() };
+    /// weechat.mark_all_read();
+    /// ```
+    pub fn mark_all_read(&self) {
+        for buffer in self.buffers() {
+            buffer.set_read_marker_bottom();
+        }
+    }
+
+    /// Get an iterator over all the windows, in the order of their numbers.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use weechat::Weechat;
+    /// # let weechat = unsafe { Weechat::weechat() };
+    /// let buffer = weechat.current_buffer();
+    ///
+    /// for window in weechat.windows() {
+    ///     if window.displays(&buffer) {
+    ///         Weechat::print(&format!("Buffer shown in window {}", window.number()));
+    ///     }
+    /// }
+    /// ```
+    pub fn windows(&self) -> impl Iterator<Item = Window<'_>> {
+        let hdata = unsafe { self.hdata_get("window") };
+        let mut ptr = unsafe { self.hdata_get_list(hdata, "gui_windows") };
+
+        std::iter::from_fn(move || {
+            if ptr.is_null() {
+                return None;
+            }
+
+            let window =
+                Window { weechat: self.ptr, ptr: ptr as *mut t_gui_window, phantom: PhantomData };
+            ptr = unsafe { self.hdata_move(hdata, ptr, 1) };
+
+            Some(window)
+        })
+    }
+
     /// Get the main/core buffer.
     pub fn core_buffer(&self) -> Buffer {
         let buffer_search = self.get().buffer_search_main.unwrap();
@@ -590,6 +875,12 @@ impl Weechat {
             unsafe { &mut *(buffer_pointers_ref as *mut BufferPointersAsync) };
 
         let buffer = weechat.buffer_from_ptr(buf_ptr);
+        buffer.set_properties(&builder.properties);
+
+        if let Some(target) = builder.merge_with.and_then(|n| weechat.buffer_search("==", &n)) {
+            buffer.merge(&target);
+        }
+
         let buffer_cell = Rc::new(Cell::new(buf_ptr));
 
         pointers.buffer_cell = Some(buffer_cell.clone());
@@ -603,69 +894,11 @@ impl Weechat {
     }
 
     fn buffer_new(builder: BufferBuilder) -> Result<BufferHandle, ()> {
-        unsafe extern "C" fn c_input_cb(
-            pointer: *const c_void,
-            _data: *mut c_void,
-            buffer: *mut t_gui_buffer,
-            input_data: *const c_char,
-        ) -> c_int {
-            let input_data = CStr::from_ptr(input_data).to_string_lossy();
-
-            let pointers: &mut BufferPointers = { &mut *(pointer as *mut BufferPointers) };
-
-            let weechat = Weechat::from_ptr(pointers.weechat);
-            let buffer = weechat.buffer_from_ptr(buffer);
-
-            let ret = if let Some(ref mut cb) = pointers.input_cb.as_mut() {
-                cb.callback(&weechat, &buffer, input_data).is_ok()
-            } else {
-                true
-            };
-
-            if ret {
-                WEECHAT_RC_OK
-            } else {
-                WEECHAT_RC_ERROR
-            }
-        }
-
-        unsafe extern "C" fn c_close_cb(
-            pointer: *const c_void,
-            _data: *mut c_void,
-            buffer: *mut t_gui_buffer,
-        ) -> c_int {
-            // We use from_raw() here so that the box gets freed at the end
-            // of this scope.
-            let pointers = Box::from_raw(pointer as *mut BufferPointers);
-            let weechat = Weechat::from_ptr(pointers.weechat);
-            let buffer = weechat.buffer_from_ptr(buffer);
-            buffer.mark_as_closing();
-
-            let ret = if let Some(mut cb) = pointers.close_cb {
-                cb.callback(&weechat, &buffer).is_ok()
-            } else {
-                true
-            };
-
-            // Invalidate the buffer pointer now.
-            pointers
-                .buffer_cell
-                .as_ref()
-                .expect("Buffer cell wasn't initialized properly")
-                .replace(ptr::null_mut());
-
-            if ret {
-                WEECHAT_RC_OK
-            } else {
-                WEECHAT_RC_ERROR
-            }
-        }
-
         Weechat::check_thread();
         let weechat = unsafe { Weechat::weechat() };
 
         let c_input_cb: Option<WeechatInputCbT> = match builder.input_callback {
-            Some(_) => Some(c_input_cb),
+            Some(_) => Some(c_buffer_input_cb),
             None => None,
         };
 
@@ -690,7 +923,7 @@ impl Weechat {
                 c_input_cb,
                 buffer_pointers_ref as *const _ as *const c_void,
                 ptr::null_mut(),
-                Some(c_close_cb),
+                Some(c_buffer_close_cb),
                 buffer_pointers_ref as *const _ as *const c_void,
                 ptr::null_mut(),
             )
@@ -705,6 +938,12 @@ impl Weechat {
             unsafe { &mut *(buffer_pointers_ref as *mut BufferPointers) };
 
         let buffer = weechat.buffer_from_ptr(buf_ptr);
+        buffer.set_properties(&builder.properties);
+
+        if let Some(target) = builder.merge_with.and_then(|n| weechat.buffer_search("==", &n)) {
+            buffer.merge(&target);
+        }
+
         let buffer_cell = Rc::new(Cell::new(buf_ptr));
 
         pointers.buffer_cell = Some(buffer_cell.clone());
@@ -718,6 +957,75 @@ impl Weechat {
     }
 }
 
+unsafe extern "C" fn c_buffer_input_cb(
+    pointer: *const c_void,
+    _data: *mut c_void,
+    buffer: *mut t_gui_buffer,
+    input_data: *const c_char,
+) -> c_int {
+    let input_data = CStr::from_ptr(input_data).to_string_lossy();
+
+    let pointers = pointer as *mut BufferPointers;
+
+    let weechat = Weechat::from_ptr((*pointers).weechat);
+    let buffer = weechat.buffer_from_ptr(buffer);
+
+    // The callback is taken out while it runs, it may replace itself using
+    // Buffer::set_input_callback() or close the buffer which frees the
+    // pointers struct.
+    let buffer_cell =
+        (*pointers).buffer_cell.clone().expect("Buffer cell wasn't initialized properly");
+    let mut callback = (*pointers).input_cb.take();
+
+    let ret = if let Some(cb) = callback.as_mut() {
+        cb.callback(&weechat, &buffer, input_data).is_ok()
+    } else {
+        true
+    };
+
+    if !buffer_cell.get().is_null() && (*pointers).input_cb.is_none() {
+        (*pointers).input_cb = callback;
+    }
+
+    if ret {
+        WEECHAT_RC_OK
+    } else {
+        WEECHAT_RC_ERROR
+    }
+}
+
+unsafe extern "C" fn c_buffer_close_cb(
+    pointer: *const c_void,
+    _data: *mut c_void,
+    buffer: *mut t_gui_buffer,
+) -> c_int {
+    // We use from_raw() here so that the box gets freed at the end
+    // of this scope.
+    let pointers = Box::from_raw(pointer as *mut BufferPointers);
+    let weechat = Weechat::from_ptr(pointers.weechat);
+    let buffer = weechat.buffer_from_ptr(buffer);
+    buffer.mark_as_closing();
+
+    let ret = if let Some(mut cb) = pointers.close_cb {
+        cb.callback(&weechat, &buffer).is_ok()
+    } else {
+        true
+    };
+
+    // Invalidate the buffer pointer now.
+    pointers
+        .buffer_cell
+        .as_ref()
+        .expect("Buffer cell wasn't initialized properly")
+        .replace(ptr::null_mut());
+
+    if ret {
+        WEECHAT_RC_OK
+    } else {
+        WEECHAT_RC_ERROR
+    }
+}
+
 pub(crate) type WeechatInputCbT = unsafe extern "C" fn(
     pointer: *const c_void,
     data: *mut c_void,
@@ -748,6 +1056,20 @@ impl Buffer<'_> {
         }
     }
 
+    /// Check if two buffer objects refer to the same Weechat buffer.
+    ///
+    /// This compares the identity of the buffers, not their content. Weechat
+    /// may reuse the memory of a closed buffer for a new one, so a buffer
+    /// object that outlived its buffer may compare equal to an unrelated
+    /// buffer.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The buffer that should be compared to this one.
+    pub fn ptr_eq(&self, other: &Buffer) -> bool {
+        self.ptr() == other.ptr()
+    }
+
     fn is_closing(&self) -> bool {
         self.inner.is_closing()
     }
@@ -769,6 +1091,25 @@ impl Buffer<'_> {
         }
     }
 
+    /// Display a message on a specific line of a buffer with free content.
+    ///
+    /// # Arguments
+    ///
+    /// * `y` - The line number, starting at 0. A negative value displays the
+    ///   message after the last line.
+    ///
+    /// * `message` - The message that will be displayed, an empty message
+    ///   clears the line.
+    pub fn print_y(&self, y: i32, message: &str) {
+        let weechat = self.weechat();
+        let printf_y = weechat.get().printf_y.unwrap();
+
+        let fmt_str = LossyCString::new("%s");
+        let message = LossyCString::new(message);
+
+        unsafe { printf_y(self.ptr(), y, fmt_str.as_ptr(), message.as_ptr()) }
+    }
+
     /// Display a message on the buffer with attached date and tags
     ///
     /// # Arguments
@@ -793,43 +1134,239 @@ impl Buffer<'_> {
         }
     }
 
-    /// Search for a nicklist group by name
+    /// Display a message on the buffer without affecting the hotlist or the
+    /// logs.
     ///
-    /// # Arguments
+    /// The line is printed with the `notify_none` and `no_log` tags, useful for
+    /// status lines.
     ///
-    /// * `name` - The name of the nicklist that should be searched for.
+    /// # Arguments
     ///
-    /// Returns a NickGroup if one is found, None otherwise.
-    pub fn search_nicklist_group(&self, name: &str) -> Option<NickGroup> {
-        let weechat = self.weechat();
-
-        let nicklist_search_group = weechat.get().nicklist_search_group.unwrap();
-
-        let name = LossyCString::new(name);
-
-        let group = unsafe { nicklist_search_group(self.ptr(), ptr::null_mut(), name.as_ptr()) };
+    /// * `message` - The message that will be displayed.
+    pub fn print_quiet(&self, message: &str) {
+        self.print_date_tags(0, &["notify_none", "no_log"], message);
+    }
 
-        if group.is_null() {
-            None
-        } else {
-            Some(NickGroup {
-                ptr: group,
-                buf_ptr: self.ptr(),
-                weechat_ptr: self.weechat().ptr,
-                buffer: PhantomData,
-            })
-        }
+    /// Display a message on the buffer as a highlight.
+    ///
+    /// The line is printed with the `notify_highlight` tag, the buffer is added
+    /// to the hotlist with the highlight level. The notify level of the
+    /// buffer that the user configured still applies, e.g. nothing is added
+    /// to the hotlist if notifications for the buffer are disabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The message that will be displayed.
+    pub fn print_highlight(&self, message: &str) {
+        self.print_date_tags(0, &["notify_highlight"], message);
     }
 
-    /// Search for a nick in the whole nicklist.
+    /// Evaluate a Weechat expression in the context of the buffer.
+    ///
+    /// The buffer is available in the expression as `buffer`, e.g.
+    /// `${buffer.full_name}` or `${buffer.local_variables.nick}`.
+    ///
+    /// Returns `None` if the expression can't be evaluated.
     ///
     /// # Arguments
     ///
-    /// * `nick` - The name of the nick that should be found.
+    /// * `expression` - The expression that should be evaluated.
     ///
-    /// Returns a `Nick` if one is found, None otherwise.
-    pub fn search_nick(&self, nick: &str) -> Option<Nick> {
-        let weechat = self.weechat();
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use weechat::Weechat;
+    /// # let weechat = unsafe { Weechat::weechat() };
+    /// # let buffer = weechat.current_buffer();
+    /// let name = buffer.eval_expression("${buffer.full_name}");
+    /// ```
+    pub fn eval_expression(&self, expression: &str) -> Option<String> {
+        self.eval_expression_with(expression, None, None, HashMap::new())
+    }
+
+    /// Evaluate a Weechat expression in the context of the buffer with
+    /// additional pointers and variables.
+    ///
+    /// Besides the buffer, the window is available as `window` and the line
+    /// as `line_data`, e.g. `${window.win_width}` or `${line_data.prefix}`.
+    ///
+    /// Returns `None` if the expression can't be evaluated.
+    ///
+    /// # Arguments
+    ///
+    /// * `expression` - The expression that should be evaluated.
+    ///
+    /// * `window` - A window that should be available in the expression.
+    ///
+    /// * `line` - A line that should be available in the expression.
+    ///
+    /// * `variables` - Variables that should be available in the expression.
+    pub fn eval_expression_with(
+        &self,
+        expression: &str,
+        window: Option<&Window>,
+        line: Option<&BufferLine>,
+        variables: HashMap<&str, &str>,
+    ) -> Option<String> {
+        let weechat = self.weechat();
+
+        let string_eval_expression = weechat.get().string_eval_expression.unwrap();
+        let hashtable_free = weechat.get().hashtable_free.unwrap();
+
+        let mut pointers = vec![("buffer", self.ptr() as *mut c_void)];
+        pointers.extend(window.map(|w| ("window", w.ptr as *mut c_void)));
+        pointers.extend(line.map(|l| ("line_data", l.ptr())));
+
+        let expression = LossyCString::new(expression);
+        let pointers = weechat.pointers_to_weechat(&pointers);
+        let extra_vars = weechat.hashmap_to_weechat(variables);
+
+        unsafe {
+            let result =
+                string_eval_expression(expression.as_ptr(), pointers, extra_vars, ptr::null_mut());
+
+            hashtable_free(pointers);
+            hashtable_free(extra_vars);
+
+            take_weechat_string(result)
+        }
+    }
+
+    /// Display a message on the buffer with the given time in front of it.
+    ///
+    /// This is meant for buffers that don't display the time of each line,
+    /// see `Buffer::set_time_display()`, the time is formatted by the caller
+    /// and becomes part of the prefix of the line.
+    ///
+    /// # Arguments
+    ///
+    /// * `time` - The formatted time that should be displayed.
+    ///
+    /// * `message` - The message that will be displayed, a prefix can be set
+    ///   by separating it from the message with a tab character.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use weechat::Weechat;
+    /// # let weechat = unsafe { Weechat::weechat() };
+    /// # let buffer = weechat.current_buffer();
+    /// buffer.set_time_display(false);
+    /// buffer.print_with_time("12:00", "--\tLunch time");
+    /// ```
+    pub fn print_with_time(&self, time: &str, message: &str) {
+        let message = match message.split_once('\t') {
+            Some((prefix, message)) => format!("{} {}\t{}", time, prefix, message),
+            None => format!("{}\t{}", time, message),
+        };
+
+        self.print(&message);
+    }
+
+    /// Display many lines on the buffer at once.
+    ///
+    /// The lines are printed with a `notify_none` tag in place of their own
+    /// notify tag, so they don't update the hotlist one by one. Once the
+    /// batch is done the buffer is added to the hotlist a single time using
+    /// the highest notify level found in the tags of the printed lines,
+    /// lines without a notify tag count as a low priority line. Highlights
+    /// that Weechat detects in the messages aren't considered, tag such lines
+    /// with `notify_highlight`.
+    ///
+    /// # Arguments
+    ///
+    /// * `lines` - The lines that should be printed.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use weechat::Weechat;
+    /// # use weechat::buffer::PrintLine;
+    /// # let weechat = unsafe { Weechat::weechat() };
+    /// # let buffer = weechat.current_buffer();
+    /// let log = vec![(1577836800, "alice\tHello"), (1577836860, "bob\tHi")];
+    ///
+    /// buffer.print_batch(log.into_iter().map(|(date, message)| {
+    ///     PrintLine::new(message).date(date).add_tag("notify_message")
+    /// }));
+    /// ```
+    pub fn print_batch(&self, lines: impl IntoIterator<Item = PrintLine>) {
+        let mut priority = None;
+
+        for line in lines {
+            // The hotlist priorities, lines without a notify tag are added
+            // with a low priority.
+            let line_priority = match line.tags.iter().find_map(|t| NotifyLevel::from_tag(t)) {
+                None => Some(0),
+                Some(NotifyLevel::None) => None,
+                Some(NotifyLevel::Message) => Some(1),
+                Some(NotifyLevel::Private) => Some(2),
+                Some(NotifyLevel::Highlight) => Some(3),
+            };
+
+            priority = priority.max(line_priority);
+
+            let tags: Vec<&str> = line
+                .tags
+                .iter()
+                .map(|t| t.as_str())
+                .filter(|t| NotifyLevel::from_tag(t).is_none())
+                .chain(std::iter::once("notify_none"))
+                .collect();
+
+            self.print_date_tags(line.date, &tags, &line.message);
+        }
+
+        if let Some(priority) = priority {
+            self.set("hotlist", &priority.to_string());
+        }
+    }
+
+    /// Search for a nicklist group by name
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the nicklist that should be searched for.
+    ///
+    /// Returns a NickGroup if one is found, None otherwise.
+    pub fn search_nicklist_group(&self, name: &str) -> Option<NickGroup> {
+        let weechat = self.weechat();
+
+        let nicklist_search_group = weechat.get().nicklist_search_group.unwrap();
+
+        let name = LossyCString::new(name);
+
+        let group = unsafe { nicklist_search_group(self.ptr(), ptr::null_mut(), name.as_ptr()) };
+
+        if group.is_null() {
+            None
+        } else {
+            Some(NickGroup {
+                ptr: group,
+                buf_ptr: self.ptr(),
+                weechat_ptr: self.weechat().ptr,
+                buffer: PhantomData,
+            })
+        }
+    }
+
+    /// Get the number of nicks in the nicklist.
+    pub fn nick_count(&self) -> i32 {
+        self.get_integer("nicklist_nicks_count")
+    }
+
+    /// Search for a nick in the whole nicklist.
+    ///
+    /// Nicks are compared using the nick comparison of the buffer, IRC
+    /// buffers for example compare nicks using the casemapping of the server.
+    ///
+    /// # Arguments
+    ///
+    /// * `nick` - The name of the nick that should be found.
+    ///
+    /// Returns a `Nick` if one is found, None otherwise.
+    pub fn search_nick(&self, nick: &str) -> Option<Nick> {
+        let weechat = self.weechat();
         let nick = Buffer::search_nick_helper(&weechat, self.ptr(), nick, None);
 
         if nick.is_null() {
@@ -844,6 +1381,80 @@ impl Buffer<'_> {
         }
     }
 
+    /// Search for a nick in the whole nicklist ignoring the case of the nick.
+    ///
+    /// # Arguments
+    ///
+    /// * `nick` - The name of the nick that should be found.
+    ///
+    /// * `casemapping` - The casemapping that decides which nicks are equal,
+    ///   IRC servers announce it in the `CASEMAPPING` ISUPPORT token and use
+    ///   `Casemapping::Rfc1459` if they don't.
+    ///
+    /// Returns a `Nick` if one is found, None otherwise.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use weechat::{Casemapping, Weechat};
+    /// # let weechat = unsafe { Weechat::weechat() };
+    /// # let buffer = weechat.current_buffer();
+    /// let nick = buffer.search_nick_ignore_case("[Nick]", Casemapping::Rfc1459);
+    /// ```
+    pub fn search_nick_ignore_case(
+        &self,
+        nick: &str,
+        casemapping: Casemapping,
+    ) -> Option<Nick<'_>> {
+        let weechat = self.weechat();
+        let nick = weechat.nick_casefold(nick, casemapping);
+
+        self.nicks().find(|n| weechat.nick_casefold(&n.name(), casemapping) == nick)
+    }
+
+    /// Iterate over all the nicks in the nicklist of the buffer.
+    ///
+    /// Nicks from all groups, including nested ones, are returned in the
+    /// order they are displayed in the nicklist.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use weechat::Weechat;
+    /// # let weechat = unsafe { Weechat::weechat() };
+    /// # let buffer = weechat.current_buffer();
+    /// for nick in buffer.nicks() {
+    ///     Weechat::print(&format!("{}{}", nick.prefix(), nick.name()));
+    /// }
+    /// ```
+    pub fn nicks(&self) -> impl Iterator<Item = Nick<'_>> {
+        let weechat = self.weechat();
+        let nicklist_get_next_item = weechat.get().nicklist_get_next_item.unwrap();
+        let weechat_ptr = weechat.ptr;
+        let buffer_ptr = self.ptr();
+
+        let mut group_ptr = ptr::null_mut();
+        let mut nick_ptr = ptr::null_mut();
+
+        std::iter::from_fn(move || loop {
+            unsafe { nicklist_get_next_item(buffer_ptr, &mut group_ptr, &mut nick_ptr) };
+
+            if group_ptr.is_null() && nick_ptr.is_null() {
+                return None;
+            }
+
+            if !nick_ptr.is_null() {
+                return Some(Nick {
+                    ptr: nick_ptr,
+                    buf_ptr: buffer_ptr,
+                    weechat_ptr,
+                    buffer: PhantomData,
+                });
+            }
+        })
+        .fuse()
+    }
+
     fn search_nick_helper(
         weechat: &Weechat,
         buffer_ptr: *mut t_gui_buffer,
@@ -1023,6 +1634,174 @@ impl Buffer<'_> {
         unsafe { buffer_set(self.ptr(), option.as_ptr(), value.as_ptr()) };
     }
 
+    /// Set multiple buffer properties at once.
+    ///
+    /// The properties are applied in the given order.
+    ///
+    /// # Arguments
+    ///
+    /// * `properties` - A list of property names and values, the names can be
+    ///   any property accepted by the Weechat `buffer_set()` function, e.g.
+    ///   `title`, `type`, `notify` or `localvar_set_xxx`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use weechat::Weechat;
+    /// # let weechat = unsafe { Weechat::weechat() };
+    /// # let buffer = weechat.current_buffer();
+    /// buffer.set_properties(&[
+    ///     ("title", "My buffer"),
+    ///     ("notify", "1"),
+    ///     ("localvar_set_type", "private"),
+    /// ]);
+    /// ```
+    pub fn set_properties<P: AsRef<str>, V: AsRef<str>>(&self, properties: &[(P, V)]) {
+        for (property, value) in properties {
+            self.set(property.as_ref(), value.as_ref());
+        }
+    }
+
+    /// Set a pointer property of the buffer.
+    ///
+    /// This is a thin wrapper around the Weechat `buffer_set_pointer()`
+    /// function, the valid properties are `close_callback`,
+    /// `close_callback_pointer`, `close_callback_data`, `input_callback`,
+    /// `input_callback_pointer`, `input_callback_data`, `nickcmp_callback`,
+    /// `nickcmp_callback_pointer` and `nickcmp_callback_data`.
+    ///
+    /// Passing a null pointer clears the property.
+    ///
+    /// # Arguments
+    ///
+    /// * `property` - The name of the pointer property that should be set.
+    ///
+    /// * `value` - The new pointer value of the property.
+    ///
+    /// # Safety
+    ///
+    /// Weechat will call and dereference the given pointers, callbacks need
+    /// to have the C signature Weechat expects for the property and the
+    /// callback pointers need to stay valid for as long as the callback is
+    /// set on the buffer. Replacing the callbacks of a buffer that was
+    /// created using a `BufferBuilder` will leak the callback data, use
+    /// `Buffer::set_input_callback()` to replace the input callback of such
+    /// buffers.
+    pub unsafe fn set_pointer(&self, property: &str, value: *mut c_void) {
+        let weechat = self.weechat();
+
+        let buffer_set_pointer = weechat.get().buffer_set_pointer.unwrap();
+        let property = LossyCString::new(property);
+
+        buffer_set_pointer(self.ptr(), property.as_ptr(), value);
+    }
+
+    /// Get a pointer property of the buffer.
+    ///
+    /// This is a thin wrapper around the Weechat `buffer_get_pointer()`
+    /// function, the valid properties are `plugin`,
+    /// `text_search_regex_compiled`, `text_search_ptr_history`,
+    /// `highlight_disable_regex_compiled` and `highlight_regex_compiled`.
+    /// Other fields of the buffer, e.g. `own_lines`, can be read using the
+    /// `buffer` hdata.
+    ///
+    /// Returns a null pointer if the property is unknown or has no value.
+    ///
+    /// # Arguments
+    ///
+    /// * `property` - The name of the pointer property.
+    pub fn get_pointer(&self, property: &str) -> *mut c_void {
+        let weechat = self.weechat();
+
+        let buffer_get_pointer = weechat.get().buffer_get_pointer.unwrap();
+        let property = LossyCString::new(property);
+
+        unsafe { buffer_get_pointer(self.ptr(), property.as_ptr()) }
+    }
+
+    /// Get a pointer to the plugin that owns the buffer.
+    ///
+    /// Returns a null pointer for buffers that are owned by Weechat itself,
+    /// e.g. the core buffer.
+    pub fn plugin_ptr(&self) -> *mut t_weechat_plugin {
+        self.get_pointer("plugin") as *mut t_weechat_plugin
+    }
+
+    /// Is the buffer owned by the current plugin.
+    pub fn is_own(&self) -> bool {
+        self.plugin_ptr() == self.weechat().ptr
+    }
+
+    /// Replace the input callback of the buffer.
+    ///
+    /// This only works for buffers that were created by this plugin using a
+    /// `BufferBuilder`, the callback data is owned by the buffer and freed
+    /// when the buffer is closed.
+    ///
+    /// The callback may be replaced from inside of the running input
+    /// callback, the old callback is dropped once it returns.
+    ///
+    /// Returns an error if the buffer wasn't created using a `BufferBuilder`
+    /// of this plugin.
+    ///
+    /// # Arguments
+    ///
+    /// * `callback` - A function or a struct that implements the
+    ///   BufferInputCallback trait, it will be called when the user enters
+    ///   something in the buffer input.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use std::borrow::Cow;
+    /// # use weechat::Weechat;
+    /// # use weechat::buffer::{Buffer, BufferBuilder};
+    /// # let weechat = unsafe { Weechat::weechat() };
+    /// let buffer_handle = BufferBuilder::new("test_buffer")
+    ///     .build()
+    ///     .expect("Can't create new buffer");
+    ///
+    /// let buffer = buffer_handle.upgrade().expect("Buffer was closed");
+    ///
+    /// buffer
+    ///     .set_input_callback(|_: &Weechat, buffer: &Buffer, input: Cow<str>| {
+    ///         buffer.print(&input);
+    ///         Ok(())
+    ///     })
+    ///     .expect("Buffer wasn't created by this plugin");
+    /// ```
+    pub fn set_input_callback(
+        &self,
+        callback: impl BufferInputCallback + 'static,
+    ) -> Result<(), ()> {
+        let weechat = self.weechat();
+        let hdata = self.hdata_pointer();
+
+        let (close_callback, pointers) = unsafe {
+            (
+                weechat.hdata_pointer(hdata, self.ptr() as *mut c_void, "close_callback"),
+                weechat.hdata_pointer(hdata, self.ptr() as *mut c_void, "close_callback_pointer"),
+            )
+        };
+
+        // Only buffers created by Weechat::buffer_new() have our
+        // BufferPointers struct as their callback pointer.
+        if close_callback != c_buffer_close_cb as *mut c_void || pointers.is_null() {
+            return Err(());
+        }
+
+        let pointers: &mut BufferPointers = unsafe { &mut *(pointers as *mut BufferPointers) };
+        pointers.input_cb = Some(Box::new(callback));
+
+        unsafe {
+            self.set_pointer("input_callback_pointer", pointers as *mut _ as *mut c_void);
+            self.set_pointer("input_callback_data", ptr::null_mut());
+            self.set_pointer("input_callback", c_buffer_input_cb as *mut c_void);
+        }
+
+        Ok(())
+    }
+
     fn get_string(&self, property: &str) -> Option<Cow<str>> {
         let weechat = self.weechat();
 
@@ -1069,6 +1848,79 @@ impl Buffer<'_> {
         self.set(&format!("localvar_set_{}", property), value)
     }
 
+    /// Remove a buffer localvar.
+    ///
+    /// Nothing is done if the localvar doesn't exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `property` - The name of the localvar that should be removed.
+    pub fn remove_localvar(&self, property: &str) {
+        self.set(&format!("localvar_del_{}", property), "")
+    }
+
+    /// Get an iterator over all the localvars of the buffer, sorted by name.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use weechat::Weechat;
+    /// # let weechat = unsafe { Weechat::weechat() };
+    /// # let buffer = weechat.current_buffer();
+    /// for (name, _) in buffer.localvars().filter(|(name, _)| name.starts_with("myplugin_")) {
+    ///     buffer.remove_localvar(&name);
+    /// }
+    /// ```
+    pub fn localvars(&self) -> impl Iterator<Item = (String, String)> {
+        let weechat = self.weechat();
+        let hdata = self.hdata_pointer();
+
+        let mut localvars: Vec<(String, String)> = unsafe {
+            let hashtable =
+                weechat.hdata_hashtable(hdata, self.ptr() as *mut c_void, "local_variables");
+
+            if hashtable.is_null() {
+                Vec::new()
+            } else {
+                weechat.hashtable_to_map(hashtable).into_iter().collect()
+            }
+        };
+
+        localvars.sort();
+
+        localvars.into_iter()
+    }
+
+    /// Replace the local variables of the buffer in a string.
+    ///
+    /// Local variables are referenced using `$name`, variables that aren't
+    /// set on the buffer are left as they are.
+    ///
+    /// # Arguments
+    ///
+    /// * `string` - The string in which the local variables should be
+    ///   replaced.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use weechat::Weechat;
+    /// # let weechat = unsafe { Weechat::weechat() };
+    /// # let buffer = weechat.current_buffer();
+    /// let status = buffer.replace_localvars("now in $channel on $server");
+    /// ```
+    pub fn replace_localvars(&self, string: &str) -> String {
+        let weechat = self.weechat();
+        let replace_local_var = weechat.get().buffer_string_replace_local_var.unwrap();
+
+        let c_string = LossyCString::new(string);
+
+        unsafe {
+            let result = replace_local_var(self.ptr(), c_string.as_ptr());
+            take_weechat_string(result).unwrap_or_else(|| string.to_owned())
+        }
+    }
+
     /// Get the full name of the buffer.
     pub fn full_name(&self) -> Cow<str> {
         self.get_string("full_name").unwrap()
@@ -1104,6 +1956,9 @@ impl Buffer<'_> {
 
     /// Set the short_name of the buffer.
     ///
+    /// The short name is passed to Weechat unmodified, it may contain Weechat
+    /// color codes which are rendered in the buffer list.
+    ///
     /// # Arguments
     ///
     /// * `name` - The new short name that should be set.
@@ -1116,6 +1971,40 @@ impl Buffer<'_> {
         self.get_string("plugin").unwrap()
     }
 
+    fn localvar_type_is(&self, buffer_type: &str) -> bool {
+        matches!(self.get_localvar("type"), Some(t) if t == buffer_type)
+    }
+
+    /// Is this buffer a channel buffer.
+    ///
+    /// This checks the `type` localvar which is set by the IRC plugin and by
+    /// other plugins that follow its conventions, buffers that don't set the
+    /// localvar are never channel buffers.
+    pub fn is_channel(&self) -> bool {
+        self.localvar_type_is("channel")
+    }
+
+    /// Is this buffer a private conversation buffer.
+    ///
+    /// This checks the `type` localvar, buffers that don't set the localvar
+    /// are never private buffers.
+    pub fn is_private(&self) -> bool {
+        self.localvar_type_is("private")
+    }
+
+    /// Is this buffer a server buffer.
+    ///
+    /// This checks the `type` localvar, buffers that don't set the localvar
+    /// are never server buffers.
+    pub fn is_server(&self) -> bool {
+        self.localvar_type_is("server")
+    }
+
+    /// Is this buffer owned by the Weechat core, e.g. the core buffer.
+    pub fn is_core(&self) -> bool {
+        self.plugin_name() == "core"
+    }
+
     /// Hide time for all lines in the buffer.
     pub fn disable_time_for_each_line(&self) {
         self.set("time_for_each_line", "0");
@@ -1126,6 +2015,24 @@ impl Buffer<'_> {
         self.set("nicklist", "0")
     }
 
+    /// Is the time displayed in front of each line of the buffer.
+    pub fn time_display(&self) -> bool {
+        self.get_integer("time_for_each_line") != 0
+    }
+
+    /// Set if the time should be displayed in front of each line of the
+    /// buffer.
+    ///
+    /// Weechat redraws the buffer, the setting applies to the lines that were
+    /// already printed as well. Other buffers keep displaying the time.
+    ///
+    /// # Arguments
+    ///
+    /// * `display` - Should the time be displayed.
+    pub fn set_time_display(&self, display: bool) {
+        self.set("time_for_each_line", if display { "1" } else { "0" });
+    }
+
     /// Enable displaying of groups in the nicklist.
     pub fn enable_nicklist_groups(&self) {
         self.set("nicklist_display_groups", "1")
@@ -1137,17 +2044,55 @@ impl Buffer<'_> {
     }
 
     /// Enable the nicklist for this buffer.
+    ///
+    /// The nicklist is displayed by a bar that contains the `buffer_nicklist`
+    /// item, by default this is the `nicklist` bar. If the user removed the
+    /// item from all bars or hid the bar, the nicklist isn't shown even if
+    /// it's enabled for the buffer.
+    ///
+    /// The width of the nicklist is the size of the bar, it's shared with
+    /// all other buffers and can be changed with `Bar::search("nicklist")`
+    /// and `Bar::set("size", ...)`, a size of 0 lets the bar fit its content.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use weechat::Weechat;
+    /// # let weechat = unsafe { Weechat::weechat() };
+    /// # let buffer = weechat.current_buffer();
+    /// buffer.enable_nicklist();
+    /// buffer.disable_nicklist_groups();
+    /// ```
     pub fn enable_nicklist(&self) {
         self.set("nicklist", "1")
     }
 
+    /// Is the nicklist enabled for this buffer.
+    pub fn is_nicklist_enabled(&self) -> bool {
+        self.get_integer("nicklist") == 1
+    }
+
+    /// Set whether nicks in the nicklist of this buffer are searched case
+    /// sensitively.
+    ///
+    /// # Arguments
+    ///
+    /// * `case_sensitive` - Should the case of nicks matter.
+    pub fn set_nicklist_case_sensitive(&self, case_sensitive: bool) {
+        self.set("nicklist_case_sensitive", if case_sensitive { "1" } else { "0" });
+    }
+
     /// Get the title of the buffer
-    pub fn title(&self) {
-        self.get_string("title");
+    pub fn title(&self) -> Cow<'_, str> {
+        self.get_string("title").unwrap_or_default()
     }
 
     /// Set the title of the buffer.
     ///
+    /// The title is passed to Weechat unmodified, it may contain Weechat
+    /// color codes created with `Weechat::color()`. An empty string removes
+    /// the title.
+    ///
     /// # Arguments
     ///
     /// * `title` - The new title that will be set.
@@ -1155,6 +2100,69 @@ impl Buffer<'_> {
         self.set("title", title);
     }
 
+    /// Set the title of the buffer using the given colors.
+    ///
+    /// # Arguments
+    ///
+    /// * `foreground` - The name of the foreground color of the title.
+    ///
+    /// * `background` - The name of the background color of the title, an
+    ///   empty string keeps the background color of the title bar.
+    ///
+    /// * `text` - The text of the title.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use weechat::Weechat;
+    /// # let weechat = unsafe { Weechat::weechat() };
+    /// # let buffer = weechat.current_buffer();
+    /// buffer.set_title_colored("lightgreen", "", "connected");
+    /// ```
+    pub fn set_title_colored(&self, foreground: &str, background: &str, text: &str) {
+        let color = if background.is_empty() {
+            Weechat::color(foreground).to_string()
+        } else {
+            Weechat::color_pair(foreground, background)
+        };
+
+        self.set_title(&format!("{}{}{}", color, text, Weechat::color("reset")));
+    }
+
+    /// Restrict highlights to lines with the given tags.
+    ///
+    /// Lines without any of the tags are never highlighted, e.g. restricting
+    /// highlights to `irc_privmsg` prevents joins and other system lines from
+    /// highlighting. An empty slice removes the restriction.
+    ///
+    /// # Arguments
+    ///
+    /// * `tags` - The tags a line needs to have to be highlighted, each
+    ///   element may contain multiple tags separated by `+` that must all be
+    ///   present.
+    pub fn set_highlight_tags_restrict(&self, tags: &[&str]) {
+        self.set("highlight_tags_restrict", &tags.join(","));
+    }
+
+    /// Enable or disable the filters for this buffer.
+    ///
+    /// This overrides the global filter state that is toggled with
+    /// `/filter toggle` for this buffer only, lines that are hidden by
+    /// filters are shown again while the filters are disabled. The filters
+    /// themselves aren't modified.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Should the filters be applied to the buffer.
+    pub fn set_filter_enabled(&self, enabled: bool) {
+        self.set("filter", if enabled { "1" } else { "0" });
+    }
+
+    /// Are the filters enabled for this buffer.
+    pub fn is_filter_enabled(&self) -> bool {
+        self.get_integer("filter") == 1
+    }
+
     /// Disable logging for this buffer.
     pub fn disable_log(&self) {
         self.set("localvar_set_no_log", "1");
@@ -1168,6 +2176,112 @@ impl Buffer<'_> {
         unsafe { buffer_clear(self.ptr()) }
     }
 
+    /// Place the read marker after the latest line of the buffer.
+    ///
+    /// Lines that are printed afterwards are displayed below the read marker
+    /// and are considered unread. Nothing is done if the buffer is empty.
+    pub fn set_unread(&self) {
+        if self.num_lines() == 0 {
+            return;
+        }
+
+        self.set("unread", "1");
+    }
+
+    /// Mark the whole buffer as read.
+    ///
+    /// This moves the read marker to the bottom of the buffer and removes the
+    /// buffer from the hotlist, e.g. if the buffer was read on another
+    /// device.
+    pub fn set_read_marker_bottom(&self) {
+        self.set_unread();
+        self.clear_hotlist();
+    }
+
+    /// Remove the buffer from the hotlist.
+    ///
+    /// This resets the message and highlight counts of the buffer in the
+    /// hotlist.
+    pub fn clear_hotlist(&self) {
+        self.set("hotlist", "-1");
+    }
+
+    /// Clear a range of lines of the buffer.
+    ///
+    /// On buffers with free content the lines in the range are overwritten
+    /// with empty lines, other lines are left untouched. On formatted buffers
+    /// the whole buffer is cleared, the same as `Buffer::clear()`.
+    ///
+    /// # Arguments
+    ///
+    /// * `lines` - The range of line numbers that should be cleared, line
+    ///   numbers start at 0.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use weechat::Weechat;
+    /// # let weechat = unsafe { Weechat::weechat() };
+    /// # let buffer = weechat.current_buffer();
+    /// buffer.clear_lines(5..11);
+    /// ```
+    pub fn clear_lines(&self, lines: Range<i32>) {
+        if self.has_free_content() {
+            for y in lines {
+                self.print_y(y, "");
+            }
+        } else {
+            self.clear();
+        }
+    }
+
+    /// Clear buffer contents without resetting the scroll position.
+    ///
+    /// Only buffers with free content can be cleared silently, every line is
+    /// overwritten with an empty line and stays in place, so windows that
+    /// display the buffer keep their scroll position.
+    ///
+    /// The lines of formatted buffers can't be kept, they are cleared the same
+    /// as with `Buffer::clear()` or the `/buffer clear` command and the scroll
+    /// position is reset.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use weechat::Weechat;
+    /// # use weechat::buffer::BufferBuilder;
+    /// # let weechat = unsafe { Weechat::weechat() };
+    /// let handle = BufferBuilder::new("dashboard")
+    ///     .properties(&[("type", "free")])
+    ///     .build()
+    ///     .expect("Can't create the dashboard");
+    /// let dashboard = handle.upgrade().unwrap();
+    ///
+    /// dashboard.print_y(0, "Status: connected");
+    /// dashboard.print_y(1, "Unread: 3");
+    ///
+    /// // The rows are overwritten with empty lines, the line count and the
+    /// // scroll position stay the same.
+    /// dashboard.clear_silent();
+    /// assert_eq!(dashboard.num_lines(), 2);
+    ///
+    /// // A formatted buffer loses its lines, like with `/buffer clear`.
+    /// let core = weechat.core_buffer();
+    /// core.clear_silent();
+    /// assert_eq!(core.num_lines(), 0);
+    /// ```
+    pub fn clear_silent(&self) {
+        if self.has_free_content() {
+            self.clear_lines(0..self.num_lines());
+        } else {
+            self.clear();
+        }
+    }
+
+    fn has_free_content(&self) -> bool {
+        self.get_integer("type") == 1
+    }
+
     /// Close the buffer.
     ///
     /// Note that this will only queue up the buffer to be closed. The close
@@ -1213,6 +2327,7 @@ impl Buffer<'_> {
     /// If enabled multiple lines separated by a newline are sent as a single
     /// string to the input callback. If disabled each line will be sent to the
     /// input callback separately.
+    #[deprecated(note = "use Buffer::set_multiline_input() which checks the Weechat version")]
     pub fn enable_multiline(&self) {
         self.set("input_multiline", &1.to_string());
     }
@@ -1221,10 +2336,62 @@ impl Buffer<'_> {
     ///
     /// If enabled multiple lines separated by a newline are sent as a single
     /// string to the input callback. If disabled each line will be sent to the
+    #[deprecated(note = "use Buffer::set_multiline_input() which checks the Weechat version")]
     pub fn disable_multiline(&self) {
         self.set("input_multiline", &0.to_string());
     }
 
+    /// Enable or disable multiline input for the buffer.
+    ///
+    /// If enabled, text that is pasted into the input of the buffer is sent
+    /// as a single string containing newlines to the input callback instead of
+    /// one call per line.
+    ///
+    /// Returns an error if the running Weechat version doesn't support
+    /// multiline input, this requires Weechat 4.0.0 or newer.
+    ///
+    /// # Arguments
+    ///
+    /// * `enable` - Should multiline input be enabled.
+    pub fn set_multiline_input(&self, enable: bool) -> Result<(), Unsupported> {
+        Unsupported::check("input_multiline", (4, 0, 0))?;
+        self.set("input_multiline", if enable { "1" } else { "0" });
+
+        Ok(())
+    }
+
+    /// Set a custom prompt that is displayed in front of the input of the
+    /// buffer.
+    ///
+    /// Color codes in the prompt are rendered, use `Weechat::color()` to
+    /// color it. An empty prompt removes the custom prompt.
+    ///
+    /// Returns an error if the running Weechat version doesn't support custom
+    /// prompts, this requires Weechat 4.3.0 or newer.
+    ///
+    /// # Arguments
+    ///
+    /// * `prompt` - The prompt that should be displayed.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use weechat::Weechat;
+    /// # let weechat = unsafe { Weechat::weechat() };
+    /// # let buffer = weechat.current_buffer();
+    /// let prompt = format!("{}py>>>{} ", Weechat::color("green"), Weechat::color("reset"));
+    ///
+    /// if buffer.set_prompt(&prompt).is_err() {
+    ///     buffer.print("Custom prompts need Weechat 4.3.0 or newer");
+    /// }
+    /// ```
+    pub fn set_prompt(&self, prompt: &str) -> Result<(), Unsupported> {
+        Unsupported::check("input_prompt", (4, 3, 0))?;
+        self.set("input_prompt", prompt);
+
+        Ok(())
+    }
+
     /// Is multiline support enabled for this buffer.
     pub fn is_multiline_enabled(&self) -> bool {
         self.get_integer("input_multiline") == 1
@@ -1255,6 +2422,28 @@ impl Buffer<'_> {
         }
     }
 
+    /// Get the buffers that are merged with this buffer.
+    ///
+    /// Merged buffers share the same number, the returned list includes the
+    /// buffer itself. A buffer that isn't merged returns a list containing
+    /// only itself.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use weechat::Weechat;
+    /// # let weechat = unsafe { Weechat::weechat() };
+    /// # let buffer = weechat.current_buffer();
+    /// for merged in buffer.merged_with() {
+    ///     merged.set_unread();
+    /// }
+    /// ```
+    pub fn merged_with(&self) -> Vec<Buffer<'_>> {
+        let number = self.number();
+
+        self.weechat().buffers().filter(|buffer| buffer.number() == number).collect()
+    }
+
     /// Unmerge the buffer if it's merged with other buffers, the buffer will be
     /// moved to the current buffer number + 1.
     pub fn unmerge(&self) {
@@ -1307,6 +2496,61 @@ impl Buffer<'_> {
         }
     }
 
+    /// Run the given command in the buffer using the given options.
+    ///
+    /// # Arguments
+    ///
+    /// * `command` - The command that should run.
+    ///
+    /// * `options` - Options controlling how the command is run.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use weechat::Weechat;
+    /// # use weechat::buffer::CommandOptions;
+    /// # let weechat = unsafe { Weechat::weechat() };
+    /// # let buffer = weechat.current_buffer();
+    /// let options = CommandOptions::new().commands("*,!exec").no_command_run();
+    ///
+    /// buffer.run_command_with_options("/join #weechat", &options);
+    /// ```
+    pub fn run_command_with_options(
+        &self,
+        command: &str,
+        options: &CommandOptions,
+    ) -> Result<(), ()> {
+        let weechat = self.weechat();
+        let command_options = weechat.get().command_options.unwrap();
+
+        let delay = options.delay.map(|d| d.as_millis().to_string());
+        let mut hashmap = HashMap::new();
+
+        if let Some(commands) = &options.commands {
+            hashmap.insert("commands", commands.as_str());
+        }
+
+        if let Some(delay) = &delay {
+            hashmap.insert("delay", delay.as_str());
+        }
+
+        let command = LossyCString::new(command);
+        let hashtable = weechat.hashmap_to_weechat(hashmap);
+
+        let ret = {
+            let _guard = options.no_command_run.then(CommandRunGuard::new);
+
+            unsafe { command_options(weechat.ptr, self.ptr(), command.as_ptr(), hashtable) }
+        };
+
+        unsafe { weechat.get().hashtable_free.unwrap()(hashtable) };
+
+        match ret {
+            WEECHAT_RC_OK => Ok(()),
+            _ => Err(()),
+        }
+    }
+
     fn hdata_pointer(&self) -> *mut t_hdata {
         let weechat = self.weechat();
 
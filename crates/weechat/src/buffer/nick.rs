@@ -122,4 +122,13 @@ impl<'a> Nick<'a> {
     pub fn prefix_color(&self) -> Cow<str> {
         self.get_string("prefix_color").unwrap()
     }
+
+    /// Is the nick visible in the nicklist.
+    pub fn visible(&self) -> bool {
+        let weechat = self.get_weechat();
+        let get_integer = weechat.get().nicklist_nick_get_integer.unwrap();
+        let c_property = LossyCString::new("visible");
+
+        unsafe { get_integer(self.buf_ptr, self.ptr, c_property.as_ptr()) != 0 }
+    }
 }
@@ -0,0 +1,141 @@
+use std::borrow::Cow;
+
+/// The notify level of a line, decides how a line affects the hotlist.
+///
+/// Lines without a notify tag are added to the hotlist with a low priority,
+/// Weechat has no tag for that level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum NotifyLevel {
+    /// The line is never added to the hotlist.
+    None,
+    /// A message from a user.
+    Message,
+    /// A private message.
+    Private,
+    /// A highlight.
+    Highlight,
+}
+
+impl NotifyLevel {
+    /// Get the tag that sets this notify level on a line.
+    pub fn tag(&self) -> &'static str {
+        match self {
+            NotifyLevel::None => "notify_none",
+            NotifyLevel::Message => "notify_message",
+            NotifyLevel::Private => "notify_private",
+            NotifyLevel::Highlight => "notify_highlight",
+        }
+    }
+
+    pub(crate) fn from_tag(tag: &str) -> Option<Self> {
+        match tag {
+            "notify_none" => Some(NotifyLevel::None),
+            "notify_message" => Some(NotifyLevel::Message),
+            "notify_private" => Some(NotifyLevel::Private),
+            "notify_highlight" => Some(NotifyLevel::Highlight),
+            _ => None,
+        }
+    }
+}
+
+/// The tags of a line.
+///
+/// Weechat and its plugins attach tags to lines following some conventions,
+/// e.g. `nick_xxx` holds the nick of the sender and `irc_privmsg` marks an IRC
+/// message, this type makes those conventions queryable.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Tags<'a> {
+    tags: Vec<Cow<'a, str>>,
+}
+
+impl<'a> Tags<'a> {
+    /// Parse a comma separated list of tags.
+    ///
+    /// # Arguments
+    ///
+    /// * `tags` - The tags as a comma separated string, an empty string
+    ///   results in an empty set of tags.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use weechat::buffer::Tags;
+    /// let tags = Tags::parse("irc_privmsg,notify_message,nick_alice,log1");
+    ///
+    /// assert!(tags.has("irc_privmsg"));
+    /// assert_eq!(tags.nick(), Some("alice"));
+    /// assert_eq!(tags.log_level(), Some(1));
+    /// ```
+    pub fn parse(tags: &'a str) -> Self {
+        Tags { tags: tags.split(',').filter(|t| !t.is_empty()).map(Cow::from).collect() }
+    }
+
+    /// Does the list of tags contain the given tag.
+    ///
+    /// # Arguments
+    ///
+    /// * `tag` - The tag that should be searched for.
+    pub fn has(&self, tag: &str) -> bool {
+        self.iter().any(|t| t == tag)
+    }
+
+    /// Get the nick of the sender of the line, taken from the `nick_xxx` tag.
+    pub fn nick(&self) -> Option<&str> {
+        self.value("nick_")
+    }
+
+    /// Get the host of the sender of the line, taken from the `host_xxx` tag.
+    pub fn host(&self) -> Option<&str> {
+        self.value("host_")
+    }
+
+    /// Get the color of the nick in the prefix, taken from the
+    /// `prefix_nick_xxx` tag.
+    pub fn prefix_nick_color(&self) -> Option<&str> {
+        self.value("prefix_nick_")
+    }
+
+    /// Get the notify level of the line, taken from the `notify_xxx` tag.
+    ///
+    /// Returns `None` if the line doesn't have a notify tag.
+    pub fn notify_level(&self) -> Option<NotifyLevel> {
+        self.iter().find_map(NotifyLevel::from_tag)
+    }
+
+    /// Get the log level of the line, taken from the `logN` tag.
+    ///
+    /// Returns `None` if the line doesn't have a log level tag.
+    pub fn log_level(&self) -> Option<u8> {
+        self.iter().find_map(|t| t.strip_prefix("log").and_then(|l| l.parse().ok()))
+    }
+
+    /// Should the line be excluded from the logs, set by the `no_log` tag.
+    pub fn no_log(&self) -> bool {
+        self.has("no_log")
+    }
+
+    /// Get an iterator over the tags.
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.tags.iter().map(|t| t.as_ref())
+    }
+
+    /// Get the number of tags.
+    pub fn len(&self) -> usize {
+        self.tags.len()
+    }
+
+    /// Is the list of tags empty.
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty()
+    }
+
+    fn value(&self, prefix: &str) -> Option<&str> {
+        self.iter().find_map(|t| t.strip_prefix(prefix))
+    }
+}
+
+impl<'a> From<Vec<Cow<'a, str>>> for Tags<'a> {
+    fn from(tags: Vec<Cow<'a, str>>) -> Self {
+        Tags { tags: tags.into_iter().filter(|t| !t.is_empty()).collect() }
+    }
+}
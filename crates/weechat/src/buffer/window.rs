@@ -1,6 +1,6 @@
 use std::{marker::PhantomData, ptr};
 
-use weechat_sys::{t_gui_window, t_weechat_plugin};
+use weechat_sys::{t_gui_buffer, t_gui_window, t_weechat_plugin};
 
 use super::Buffer;
 use crate::{LossyCString, Weechat};
@@ -28,6 +28,21 @@ impl<'a> Window<'a> {
         self.get_integer(property) == 1
     }
 
+    /// Is the given buffer displayed in this window.
+    ///
+    /// # Arguments
+    ///
+    /// * `buffer` - The buffer that should be checked.
+    pub fn displays(&self, buffer: &Buffer) -> bool {
+        let weechat = Weechat::from_ptr(self.weechat);
+        let get_pointer = weechat.get().window_get_pointer.unwrap();
+        let property = LossyCString::new("buffer");
+
+        let buffer_ptr = unsafe { get_pointer(self.ptr, property.as_ptr()) };
+
+        buffer_ptr as *mut t_gui_buffer == buffer.ptr()
+    }
+
     /// The number of the window.
     pub fn number(&self) -> i32 {
         self.get_integer("number")
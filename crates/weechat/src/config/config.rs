@@ -39,7 +39,7 @@ pub struct Conf {
 }
 
 /// Status for updating options
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum OptionChanged {
     /// The option was successfully changed.
     Changed = weechat_sys::WEECHAT_CONFIG_OPTION_SET_OK_CHANGED as isize,
@@ -137,6 +137,50 @@ impl Weechat {
         Some(Config::option_from_type_and_ptr(self.ptr, ptr, option_type.as_ref()))
     }
 
+    /// Search an option with a full name and set its value.
+    ///
+    /// The change callback of the option is run if the value changed.
+    ///
+    /// Returns `OptionChanged::NotFound` if no option with the given name
+    /// exists.
+    ///
+    /// # Arguments
+    ///
+    /// * `option_name` - The full name of the option that should be set
+    ///   (format: "file.section.option").
+    ///
+    /// * `value` - The new value of the option, Weechat parses the string
+    ///   depending on the option type.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use weechat::Weechat;
+    /// # use weechat::config::OptionChanged;
+    /// # let weechat = unsafe { Weechat::weechat() };
+    /// match weechat.config_set("myplugin.look.enabled", "on") {
+    ///     OptionChanged::Changed => Weechat::print("Enabled"),
+    ///     OptionChanged::Unchanged => Weechat::print("Already enabled"),
+    ///     _ => Weechat::print("Can't enable the plugin"),
+    /// }
+    /// ```
+    pub fn config_set(&self, option_name: &str, value: &str) -> OptionChanged {
+        let config_get = self.get().config_get.unwrap();
+        let option_set = self.get().config_option_set.unwrap();
+
+        let name = LossyCString::new(option_name);
+        let ptr = unsafe { config_get(name.as_ptr()) };
+
+        if ptr.is_null() {
+            return OptionChanged::NotFound;
+        }
+
+        let value = LossyCString::new(value);
+        let ret = unsafe { option_set(ptr, value.as_ptr(), 1) };
+
+        OptionChanged::from_int(ret)
+    }
+
     /// Get value of a plugin option
     pub fn get_plugin_option(&self, option: &str) -> Option<Cow<str>> {
         let config_get_plugin = self.get().config_get_plugin.unwrap();
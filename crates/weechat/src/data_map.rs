@@ -0,0 +1,46 @@
+use std::{any::Any, cell::RefCell, collections::HashMap, hash::Hash, rc::Rc};
+
+/// Type erased storage for data that plugins attach to Weechat objects.
+///
+/// The map is meant to live in a thread local, values are never dropped while
+/// the map is borrowed since their destructors might access the map again.
+pub(crate) struct DataMap<K> {
+    data: RefCell<HashMap<K, Rc<dyn Any>>>,
+}
+
+impl<K: Eq + Hash> DataMap<K> {
+    pub(crate) fn new() -> Self {
+        Self { data: RefCell::new(HashMap::new()) }
+    }
+
+    /// Store the data under the given key, replacing previously stored data.
+    pub(crate) fn insert<T: 'static>(&self, key: K, data: T) {
+        let old_data = self.data.borrow_mut().insert(key, Rc::new(data));
+        drop(old_data);
+    }
+
+    /// Get the data stored under the given key if it has the given type.
+    pub(crate) fn get<T: 'static>(&self, key: &K) -> Option<Rc<T>> {
+        let data = self.data.borrow().get(key).cloned()?;
+        data.downcast().ok()
+    }
+
+    /// Remove the data stored under the given key if it has the given type.
+    pub(crate) fn take<T: 'static>(&self, key: &K) -> Option<Rc<T>> {
+        self.get::<T>(key)?;
+        let data = self.data.borrow_mut().remove(key)?;
+        data.downcast().ok()
+    }
+
+    /// Remove the data stored under the given key.
+    pub(crate) fn remove(&self, key: &K) {
+        let data = self.data.borrow_mut().remove(key);
+        drop(data);
+    }
+
+    /// Remove all the stored data.
+    pub(crate) fn clear(&self) {
+        let data = self.data.replace(HashMap::new());
+        drop(data);
+    }
+}
@@ -0,0 +1,157 @@
+use std::{
+    borrow::Cow,
+    ffi::CStr,
+    fmt,
+    mem::{self, ManuallyDrop},
+    os::raw::c_char,
+};
+
+use weechat_sys::t_weechat_plugin;
+
+use crate::Weechat;
+
+/// A growable string that is allocated by Weechat.
+///
+/// Building content that is handed over to Weechat in a `DynString` avoids
+/// copying the content into a C string once it's done, e.g. bar items created
+/// with `BarItem::new_dyn()` pass the string to Weechat as is.
+///
+/// The string is freed when the object is dropped.
+///
+/// # Example
+///
+/// ```no_run
+/// # use std::fmt::Write;
+/// # use weechat::DynString;
+/// let mut string = DynString::new(64).expect("Can't allocate string");
+///
+/// string.push_str("Hello").expect("Can't append to string");
+/// write!(string, " {}", "world").expect("Can't append to string");
+///
+/// assert_eq!(string.as_str(), "Hello world");
+/// ```
+pub struct DynString {
+    ptr: *mut *mut c_char,
+    weechat: *mut t_weechat_plugin,
+    // Scratch space to create null terminated strings without allocating.
+    scratch: Vec<u8>,
+}
+
+impl DynString {
+    /// Allocate a new empty string.
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity` - The number of bytes that should be allocated up front,
+    ///   the string grows automatically if needed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    pub fn new(capacity: usize) -> Result<DynString, ()> {
+        Weechat::check_thread();
+        let weechat = unsafe { Weechat::weechat() };
+
+        let string_dyn_alloc = weechat.get().string_dyn_alloc.unwrap();
+
+        // Weechat needs room for the null terminator.
+        let ptr = unsafe { string_dyn_alloc(capacity.max(1) as i32) };
+
+        if ptr.is_null() {
+            Err(())
+        } else {
+            Ok(DynString { ptr, weechat: weechat.ptr, scratch: Vec::new() })
+        }
+    }
+
+    fn weechat(&self) -> Weechat {
+        Weechat::from_ptr(self.weechat)
+    }
+
+    fn fill_scratch(&mut self, string: &str) -> *const c_char {
+        self.scratch.clear();
+        self.scratch.extend(string.bytes().filter(|b| *b != 0));
+        self.scratch.push(0);
+
+        self.scratch.as_ptr() as *const c_char
+    }
+
+    /// Append a string.
+    ///
+    /// # Arguments
+    ///
+    /// * `string` - The string that should be appended.
+    pub fn push_str(&mut self, string: &str) -> Result<(), ()> {
+        let string_dyn_concat = self.weechat().get().string_dyn_concat.unwrap();
+        let string = self.fill_scratch(string);
+
+        match unsafe { string_dyn_concat(self.ptr, string) } {
+            0 => Err(()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Replace the content of the string.
+    ///
+    /// # Arguments
+    ///
+    /// * `string` - The new content of the string.
+    pub fn set(&mut self, string: &str) -> Result<(), ()> {
+        let string_dyn_copy = self.weechat().get().string_dyn_copy.unwrap();
+        let string = self.fill_scratch(string);
+
+        match unsafe { string_dyn_copy(self.ptr, string) } {
+            0 => Err(()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Remove the content of the string, the allocated memory is kept.
+    pub fn clear(&mut self) {
+        let _ = self.set("");
+    }
+
+    /// Get the content of the string.
+    pub fn as_str(&self) -> Cow<'_, str> {
+        unsafe { CStr::from_ptr(*self.ptr).to_string_lossy() }
+    }
+
+    /// Get the length of the string in bytes.
+    pub fn len(&self) -> usize {
+        unsafe { CStr::from_ptr(*self.ptr).to_bytes().len() }
+    }
+
+    /// Is the string empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Free the dynamic string structure and return the C string, the caller
+    /// is responsible to free it.
+    pub(crate) fn into_raw(self) -> *mut c_char {
+        let mut this = ManuallyDrop::new(self);
+        drop(mem::take(&mut this.scratch));
+
+        let string_dyn_free = this.weechat().get().string_dyn_free.unwrap();
+        unsafe { string_dyn_free(this.ptr, 0) }
+    }
+}
+
+impl fmt::Write for DynString {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.push_str(s).map_err(|_| fmt::Error)
+    }
+}
+
+impl fmt::Debug for DynString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("DynString").field(&self.as_str()).finish()
+    }
+}
+
+impl Drop for DynString {
+    fn drop(&mut self) {
+        let string_dyn_free = self.weechat().get().string_dyn_free.unwrap();
+        unsafe { string_dyn_free(self.ptr, 1) };
+    }
+}
@@ -1,6 +1,10 @@
-use std::{collections::HashMap, ffi::c_void, os::raw::c_char};
+use std::{
+    collections::HashMap,
+    ffi::{c_void, CStr},
+    os::raw::c_char,
+};
 
-use weechat_sys::{t_hashtable, WEECHAT_HASHTABLE_STRING};
+use weechat_sys::{t_hashtable, WEECHAT_HASHTABLE_POINTER, WEECHAT_HASHTABLE_STRING};
 
 use crate::{LossyCString, Weechat};
 
@@ -27,4 +31,63 @@ impl Weechat {
 
         hashtable
     }
+
+    /// Create a Weechat hashtable with string keys and pointer values, e.g.
+    /// the pointers for `string_eval_expression()`.
+    pub(crate) fn pointers_to_weechat(&self, pointers: &[(&str, *mut c_void)]) -> *mut t_hashtable {
+        let hashtable_new = self.get().hashtable_new.unwrap();
+
+        let key_type: *const c_char = WEECHAT_HASHTABLE_STRING as *const _ as *const c_char;
+        let value_type: *const c_char = WEECHAT_HASHTABLE_POINTER as *const _ as *const c_char;
+
+        let hashtable = unsafe { hashtable_new(8, key_type, value_type, None, None) };
+
+        for (key, value) in pointers {
+            let key = LossyCString::new(*key);
+
+            unsafe {
+                self.get().hashtable_set.unwrap()(hashtable, key.as_ptr() as *const c_void, *value);
+            }
+        }
+
+        hashtable
+    }
+
+    /// Copy the content of a Weechat hashtable with string keys and values
+    /// into a `HashMap`.
+    ///
+    /// The hashtable isn't freed.
+    pub(crate) unsafe fn hashtable_to_map(
+        &self,
+        hashtable: *mut t_hashtable,
+    ) -> HashMap<String, String> {
+        unsafe extern "C" fn c_map_cb(
+            data: *mut c_void,
+            _hashtable: *mut t_hashtable,
+            key: *const c_char,
+            value: *const c_char,
+        ) {
+            let map: &mut HashMap<String, String> = &mut *(data as *mut HashMap<String, String>);
+
+            if key.is_null() {
+                return;
+            }
+
+            let key = CStr::from_ptr(key).to_string_lossy().into_owned();
+            let value = if value.is_null() {
+                String::new()
+            } else {
+                CStr::from_ptr(value).to_string_lossy().into_owned()
+            };
+
+            map.insert(key, value);
+        }
+
+        let hashtable_map_string = self.get().hashtable_map_string.unwrap();
+        let mut map = HashMap::new();
+
+        hashtable_map_string(hashtable, Some(c_map_cb), &mut map as *mut _ as *mut c_void);
+
+        map
+    }
 }
@@ -5,7 +5,7 @@ use std::{
     os::raw::c_char,
 };
 
-use weechat_sys::t_hdata;
+use weechat_sys::{t_hashtable, t_hdata};
 
 use crate::{LossyCString, Weechat};
 
@@ -18,6 +18,14 @@ impl Weechat {
         hdata_get(self.ptr, name.as_ptr())
     }
 
+    pub(crate) unsafe fn hdata_get_list(&self, hdata: *mut t_hdata, name: &str) -> *mut c_void {
+        let hdata_get_list = self.get().hdata_get_list.unwrap();
+
+        let name = LossyCString::new(name);
+
+        hdata_get_list(hdata, name.as_ptr())
+    }
+
     pub(crate) unsafe fn hdata_pointer(
         &self,
         hdata: *mut t_hdata,
@@ -30,6 +38,18 @@ impl Weechat {
         hdata_pointer(hdata, pointer, name.as_ptr())
     }
 
+    pub(crate) unsafe fn hdata_hashtable(
+        &self,
+        hdata: *mut t_hdata,
+        pointer: *mut c_void,
+        name: &str,
+    ) -> *mut t_hashtable {
+        let hdata_hashtable = self.get().hdata_hashtable.unwrap();
+        let name = LossyCString::new(name);
+
+        hdata_hashtable(hdata, pointer, name.as_ptr())
+    }
+
     pub(crate) unsafe fn hdata_integer(
         &self,
         hdata: *mut t_hdata,
@@ -1,11 +1,14 @@
-//! Bar items are used to display status information in Weechat.
+//! Bars and bar items are used to display status information in Weechat.
 use core::ptr;
-use std::os::raw::c_void;
+use std::{cell::RefCell, os::raw::c_void, rc::Rc, time::Duration};
 
 use libc::c_char;
-use weechat_sys::{t_gui_bar_item, t_gui_buffer, t_gui_window, t_hashtable, t_weechat_plugin};
+use weechat_sys::{
+    t_gui_bar, t_gui_bar_item, t_gui_buffer, t_gui_window, t_hashtable, t_weechat_plugin,
+};
 
-use crate::{buffer::Buffer, LossyCString, Weechat};
+use super::{RemainingCalls, TimerHook};
+use crate::{buffer::Buffer, DynString, LossyCString, Weechat};
 
 /// Trait for the bar item callback
 ///
@@ -31,9 +34,41 @@ impl<T: FnMut(&Weechat, &Buffer) -> String + 'static> BarItemCallback for T {
     }
 }
 
+/// Trait for the bar item callback that writes into a `DynString`.
+///
+/// A blanket implementation for pure `FnMut` functions exists, if data needs to
+/// be passed to the callback implement this over your struct.
+pub trait DynBarItemCallback: 'static {
+    /// The callback that should be called after the bar items
+    /// is marked to be updated.
+    ///
+    /// # Arguments
+    ///
+    /// * `weeechat` - A reference to the weechat context.
+    ///
+    /// * `buffer` - The currently visible buffer.
+    ///
+    /// * `content` - An empty string that the content of the bar item
+    ///   should be written to.
+    fn callback(&mut self, weechat: &Weechat, buffer: &Buffer, content: &mut DynString);
+}
+
+impl<T: FnMut(&Weechat, &Buffer, &mut DynString) + 'static> DynBarItemCallback for T {
+    fn callback(&mut self, weechat: &Weechat, buffer: &Buffer, content: &mut DynString) {
+        self(weechat, buffer, content)
+    }
+}
+
+enum BarItemCb {
+    String(Box<dyn BarItemCallback>),
+    Dyn(Box<dyn DynBarItemCallback>),
+}
+
 struct BarItemCbData {
-    callback: Box<dyn BarItemCallback>,
+    callback: BarItemCb,
     weechat_ptr: *mut t_weechat_plugin,
+    // Length of the last content, used to size the next allocation.
+    last_len: usize,
 }
 
 /// A handle to a bar item. The bar item is automatically removed when the
@@ -84,6 +119,43 @@ impl BarItem {
     // buffers, we need to do something about the multiple ownership that may
     // come from this.
     pub fn new(name: &str, callback: impl BarItemCallback) -> Result<BarItem, ()> {
+        BarItem::new_helper(name, BarItemCb::String(Box::new(callback)))
+    }
+
+    /// Create a new bar item whose content is written into a `DynString`.
+    ///
+    /// The content is passed to Weechat without being copied, this is useful
+    /// for big bar items that are updated frequently.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the new bar item.
+    ///
+    /// * `callback` - The callback that should be called after the bar items
+    ///   is marked to be updated.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use std::fmt::Write;
+    /// # use weechat::{DynString, Weechat};
+    /// # use weechat::buffer::Buffer;
+    /// # use weechat::hooks::BarItem;
+    /// let item = BarItem::new_dyn(
+    ///     "rust_lines",
+    ///     |_: &Weechat, buffer: &Buffer, content: &mut DynString| {
+    ///         let _ = write!(content, "{} lines", buffer.num_lines());
+    ///     },
+    /// );
+    /// ```
+    pub fn new_dyn(name: &str, callback: impl DynBarItemCallback) -> Result<BarItem, ()> {
+        BarItem::new_helper(name, BarItemCb::Dyn(Box::new(callback)))
+    }
+
+    fn new_helper(name: &str, callback: BarItemCb) -> Result<BarItem, ()> {
         unsafe extern "C" fn c_item_cb(
             pointer: *const c_void,
             _data: *mut c_void,
@@ -96,18 +168,32 @@ impl BarItem {
             let weechat = Weechat::from_ptr(data.weechat_ptr);
             let buffer = weechat.buffer_from_ptr(buffer);
 
-            let cb_trait = &mut data.callback;
+            match &mut data.callback {
+                BarItemCb::String(cb) => {
+                    let ret = cb.callback(&weechat, &buffer);
+
+                    // Weechat wants a malloc'ed string
+                    libc::strdup(LossyCString::new(ret).as_ptr())
+                }
+                BarItemCb::Dyn(cb) => {
+                    let mut content = match DynString::new(data.last_len + 1) {
+                        Ok(c) => c,
+                        Err(_) => return ptr::null_mut(),
+                    };
 
-            let ret = cb_trait.callback(&weechat, &buffer);
+                    cb.callback(&weechat, &buffer, &mut content);
+                    data.last_len = content.len();
 
-            // Weechat wants a malloc'ed string
-            libc::strdup(LossyCString::new(ret).as_ptr())
+                    // The string was allocated by Weechat, it can be handed
+                    // over as is.
+                    content.into_raw()
+                }
+            }
         }
         Weechat::check_thread();
         let weechat = unsafe { Weechat::weechat() };
 
-        let data =
-            Box::new(BarItemCbData { callback: Box::new(callback), weechat_ptr: weechat.ptr });
+        let data = Box::new(BarItemCbData { callback, weechat_ptr: weechat.ptr, last_len: 0 });
 
         let data_ref = Box::leak(data);
         let bar_item_new = weechat.get().bar_item_new.unwrap();
@@ -143,3 +229,519 @@ impl BarItem {
         Weechat::bar_item_update(&self.name);
     }
 }
+
+struct AnimatorState {
+    frames: Vec<String>,
+    idle_frame: String,
+    frame: usize,
+    interval: Duration,
+    timer: Option<TimerHook>,
+}
+
+impl AnimatorState {
+    fn current(&self) -> &str {
+        if self.timer.is_some() && !self.frames.is_empty() {
+            &self.frames[self.frame % self.frames.len()]
+        } else {
+            &self.idle_frame
+        }
+    }
+}
+
+/// A bar item that cycles through a list of frames, e.g. a loading spinner.
+///
+/// The item shows a static frame until `start()` is called, the animation
+/// runs until `stop()` is called. The bar item and the timer driving the
+/// animation are removed when the object is dropped.
+///
+/// # Example
+/// ```no_run
+/// # use std::time::Duration;
+/// # use weechat::hooks::Animator;
+/// let spinner = Animator::new(
+///     "rust_fetch_status",
+///     &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"],
+///     Duration::from_millis(100),
+/// )
+/// .expect("Can't create the spinner");
+///
+/// spinner.start().expect("Can't start the spinner");
+///
+/// // Once the fetch is done.
+/// spinner.stop_with("✓");
+/// ```
+pub struct Animator {
+    state: Rc<RefCell<AnimatorState>>,
+    item: BarItem,
+}
+
+impl Animator {
+    /// Create a new animated bar item.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the new bar item.
+    ///
+    /// * `frames` - The frames that the bar item cycles through while the
+    ///   animation is running.
+    ///
+    /// * `interval` - The time each frame is shown.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    pub fn new(name: &str, frames: &[&str], interval: Duration) -> Result<Animator, ()> {
+        let state = Rc::new(RefCell::new(AnimatorState {
+            frames: frames.iter().map(|f| f.to_string()).collect(),
+            idle_frame: String::new(),
+            frame: 0,
+            interval,
+            timer: None,
+        }));
+
+        let weak = Rc::downgrade(&state);
+        let item = BarItem::new(name, move |_: &Weechat, _: &Buffer| {
+            weak.upgrade()
+                .and_then(|s| s.try_borrow().ok().map(|s| s.current().to_owned()))
+                .unwrap_or_default()
+        })?;
+
+        Ok(Animator { state, item })
+    }
+
+    /// Start the animation from the first frame.
+    ///
+    /// Starting an animation that is already running restarts it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    pub fn start(&self) -> Result<(), ()> {
+        let name = self.item.name.clone();
+        let weak = Rc::downgrade(&self.state);
+        let interval = self.state.borrow().interval;
+
+        let timer = TimerHook::new(interval, 0, 0, move |_: &Weechat, _: RemainingCalls| {
+            if let Some(state) = weak.upgrade() {
+                let mut state = state.borrow_mut();
+                state.frame = state.frame.wrapping_add(1);
+            }
+
+            Weechat::bar_item_update(&name);
+        })?;
+
+        let previous_timer = {
+            let mut state = self.state.borrow_mut();
+            state.frame = 0;
+            state.timer.replace(timer)
+        };
+
+        drop(previous_timer);
+        self.item.update();
+
+        Ok(())
+    }
+
+    /// Stop the animation and show the static frame.
+    ///
+    /// The static frame is empty unless it was set with `stop_with()`.
+    pub fn stop(&self) {
+        let timer = self.state.borrow_mut().timer.take();
+        drop(timer);
+
+        self.item.update();
+    }
+
+    /// Stop the animation and show the given static frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame` - The frame that should be shown until the animation is
+    ///   started again, e.g. a checkmark once a task finished.
+    pub fn stop_with(&self, frame: &str) {
+        self.state.borrow_mut().idle_frame = frame.to_owned();
+        self.stop();
+    }
+
+    /// Is the animation currently running.
+    pub fn is_running(&self) -> bool {
+        self.state.borrow().timer.is_some()
+    }
+}
+
+/// The type of a bar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BarType {
+    /// The bar is displayed once, outside of the windows.
+    Root,
+    /// The bar is displayed in every window.
+    Window,
+}
+
+impl BarType {
+    fn as_str(&self) -> &'static str {
+        match self {
+            BarType::Root => "root",
+            BarType::Window => "window",
+        }
+    }
+}
+
+/// The position of a bar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BarPosition {
+    /// The bar is displayed at the top.
+    Top,
+    /// The bar is displayed at the bottom.
+    Bottom,
+    /// The bar is displayed on the left side.
+    Left,
+    /// The bar is displayed on the right side.
+    Right,
+}
+
+impl BarPosition {
+    fn as_str(&self) -> &'static str {
+        match self {
+            BarPosition::Top => "top",
+            BarPosition::Bottom => "bottom",
+            BarPosition::Left => "left",
+            BarPosition::Right => "right",
+        }
+    }
+}
+
+/// Settings describing a new Weechat bar that should be created.
+///
+/// The fields of this struct accept the same string formats that are described
+/// in the Weechat API documentation, unset fields use the Weechat defaults.
+pub struct BarSettings {
+    name: String,
+    hidden: bool,
+    priority: i32,
+    bar_type: BarType,
+    condition: String,
+    position: BarPosition,
+    filling_top_bottom: String,
+    filling_left_right: String,
+    size: u32,
+    size_max: u32,
+    color_fg: String,
+    color_delim: String,
+    color_bg: String,
+    color_bg_inactive: String,
+    separator: bool,
+    items: Vec<String>,
+}
+
+impl BarSettings {
+    /// Create new bar settings.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name that the bar should get.
+    pub fn new<P: Into<String>>(name: P) -> Self {
+        BarSettings {
+            name: name.into(),
+            hidden: false,
+            priority: 0,
+            bar_type: BarType::Root,
+            condition: "".to_owned(),
+            position: BarPosition::Top,
+            filling_top_bottom: "horizontal".to_owned(),
+            filling_left_right: "vertical".to_owned(),
+            size: 0,
+            size_max: 0,
+            color_fg: "default".to_owned(),
+            color_delim: "default".to_owned(),
+            color_bg: "default".to_owned(),
+            color_bg_inactive: "default".to_owned(),
+            separator: false,
+            items: Vec::new(),
+        }
+    }
+
+    /// Set if the bar should be hidden.
+    pub fn hidden(mut self, hidden: bool) -> Self {
+        self.hidden = hidden;
+        self
+    }
+
+    /// Set the priority of the bar, bars with a higher priority are displayed
+    /// first.
+    pub fn priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Set the type of the bar.
+    pub fn bar_type(mut self, bar_type: BarType) -> Self {
+        self.bar_type = bar_type;
+        self
+    }
+
+    /// Set the condition for displaying the bar, this is only used for bars
+    /// of the window type.
+    ///
+    /// # Arguments
+    ///
+    /// * `condition` - One of `active`, `inactive`, `nicklist` or an
+    ///   expression that is evaluated.
+    pub fn condition<C: Into<String>>(mut self, condition: C) -> Self {
+        self.condition = condition.into();
+        self
+    }
+
+    /// Set the position of the bar.
+    pub fn position(mut self, position: BarPosition) -> Self {
+        self.position = position;
+        self
+    }
+
+    /// Set how the items are filled when the bar is at the top or at the
+    /// bottom.
+    ///
+    /// # Arguments
+    ///
+    /// * `filling` - One of `horizontal`, `vertical`, `columns_horizontal` or
+    ///   `columns_vertical`.
+    pub fn filling_top_bottom<F: Into<String>>(mut self, filling: F) -> Self {
+        self.filling_top_bottom = filling.into();
+        self
+    }
+
+    /// Set how the items are filled when the bar is on the left or on the
+    /// right.
+    ///
+    /// # Arguments
+    ///
+    /// * `filling` - One of `horizontal`, `vertical`, `columns_horizontal` or
+    ///   `columns_vertical`.
+    pub fn filling_left_right<F: Into<String>>(mut self, filling: F) -> Self {
+        self.filling_left_right = filling.into();
+        self
+    }
+
+    /// Set the size of the bar, 0 means that the size is computed
+    /// automatically.
+    pub fn size(mut self, size: u32) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Set the maximum size of the bar, 0 means that there is no limit.
+    pub fn size_max(mut self, size_max: u32) -> Self {
+        self.size_max = size_max;
+        self
+    }
+
+    /// Set the default foreground color of the bar text.
+    pub fn color_fg<C: Into<String>>(mut self, color: C) -> Self {
+        self.color_fg = color.into();
+        self
+    }
+
+    /// Set the color of the delimiters in the bar.
+    pub fn color_delim<C: Into<String>>(mut self, color: C) -> Self {
+        self.color_delim = color.into();
+        self
+    }
+
+    /// Set the background color of the bar.
+    pub fn color_bg<C: Into<String>>(mut self, color: C) -> Self {
+        self.color_bg = color.into();
+        self
+    }
+
+    /// Set the background color of the bar if it's displayed in a window that
+    /// isn't active.
+    pub fn color_bg_inactive<C: Into<String>>(mut self, color: C) -> Self {
+        self.color_bg_inactive = color.into();
+        self
+    }
+
+    /// Set if a separator line should be displayed between the bar and the
+    /// windows.
+    pub fn separator(mut self, separator: bool) -> Self {
+        self.separator = separator;
+        self
+    }
+
+    /// Add an item to the bar.
+    ///
+    /// Items are referenced by name, the bar item doesn't need to exist when
+    /// the bar is created.
+    ///
+    /// # Arguments
+    ///
+    /// * `item` - The name of the bar item that should be added, items can be
+    ///   grouped using `+` to display them without a space between them.
+    pub fn add_item<T: Into<String>>(mut self, item: T) -> Self {
+        self.items.push(item.into());
+        self
+    }
+}
+
+/// A handle to a bar. Bars that were created using `Bar::new()` are
+/// automatically removed when the object is dropped.
+pub struct Bar {
+    ptr: *mut t_gui_bar,
+    weechat: *mut t_weechat_plugin,
+    owned: bool,
+}
+
+impl Drop for Bar {
+    fn drop(&mut self) {
+        if !self.owned {
+            return;
+        }
+
+        let weechat = Weechat::from_ptr(self.weechat);
+        let bar_remove = weechat.get().bar_remove.unwrap();
+        unsafe { bar_remove(self.ptr) };
+    }
+}
+
+impl Bar {
+    /// Create a new bar.
+    ///
+    /// # Arguments
+    ///
+    /// * `settings` - Settings that describe the bar that should be created.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use weechat::hooks::{Bar, BarPosition, BarSettings, BarType};
+    /// let settings = BarSettings::new("sample_panel")
+    ///     .bar_type(BarType::Root)
+    ///     .position(BarPosition::Right)
+    ///     .size(20)
+    ///     .add_item("sample_item");
+    ///
+    /// let bar = Bar::new(settings).expect("Can't create the bar");
+    /// ```
+    pub fn new(settings: BarSettings) -> Result<Bar, ()> {
+        Weechat::check_thread();
+        let weechat = unsafe { Weechat::weechat() };
+
+        let bar_new = weechat.get().bar_new.unwrap();
+
+        let on_off = |value: bool| if value { "on" } else { "off" };
+
+        let name = LossyCString::new(settings.name);
+        let hidden = LossyCString::new(on_off(settings.hidden));
+        let priority = LossyCString::new(settings.priority.to_string());
+        let bar_type = LossyCString::new(settings.bar_type.as_str());
+        let condition = LossyCString::new(settings.condition);
+        let position = LossyCString::new(settings.position.as_str());
+        let filling_top_bottom = LossyCString::new(settings.filling_top_bottom);
+        let filling_left_right = LossyCString::new(settings.filling_left_right);
+        let size = LossyCString::new(settings.size.to_string());
+        let size_max = LossyCString::new(settings.size_max.to_string());
+        let color_fg = LossyCString::new(settings.color_fg);
+        let color_delim = LossyCString::new(settings.color_delim);
+        let color_bg = LossyCString::new(settings.color_bg);
+        let color_bg_inactive = LossyCString::new(settings.color_bg_inactive);
+        let separator = LossyCString::new(on_off(settings.separator));
+        let items = LossyCString::new(settings.items.join(","));
+
+        let bar_ptr = unsafe {
+            bar_new(
+                name.as_ptr(),
+                hidden.as_ptr(),
+                priority.as_ptr(),
+                bar_type.as_ptr(),
+                condition.as_ptr(),
+                position.as_ptr(),
+                filling_top_bottom.as_ptr(),
+                filling_left_right.as_ptr(),
+                size.as_ptr(),
+                size_max.as_ptr(),
+                color_fg.as_ptr(),
+                color_delim.as_ptr(),
+                color_bg.as_ptr(),
+                color_bg_inactive.as_ptr(),
+                separator.as_ptr(),
+                items.as_ptr(),
+            )
+        };
+
+        if bar_ptr.is_null() {
+            return Err(());
+        }
+
+        Ok(Bar { ptr: bar_ptr, weechat: weechat.ptr, owned: true })
+    }
+
+    /// Search for a bar by name.
+    ///
+    /// The returned bar isn't removed when the object is dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the bar.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    pub fn search(name: &str) -> Option<Bar> {
+        Weechat::check_thread();
+        let weechat = unsafe { Weechat::weechat() };
+
+        let bar_search = weechat.get().bar_search.unwrap();
+        let name = LossyCString::new(name);
+
+        let bar_ptr = unsafe { bar_search(name.as_ptr()) };
+
+        if bar_ptr.is_null() {
+            None
+        } else {
+            Some(Bar { ptr: bar_ptr, weechat: weechat.ptr, owned: false })
+        }
+    }
+
+    /// Set a property of the bar.
+    ///
+    /// # Arguments
+    ///
+    /// * `property` - The name of the property, this can be any of the bar
+    ///   options, e.g. `hidden`, `position`, `size`, `items`.
+    ///
+    /// * `value` - The new value of the property.
+    ///
+    /// Returns an error if the property couldn't be set.
+    pub fn set(&self, property: &str, value: &str) -> Result<(), ()> {
+        let weechat = Weechat::from_ptr(self.weechat);
+        let bar_set = weechat.get().bar_set.unwrap();
+
+        let property = LossyCString::new(property);
+        let value = LossyCString::new(value);
+
+        let ret = unsafe { bar_set(self.ptr, property.as_ptr(), value.as_ptr()) };
+
+        if ret == 1 {
+            Ok(())
+        } else {
+            Err(())
+        }
+    }
+
+    /// Refresh the content of the bar on the screen.
+    ///
+    /// Only this bar is redrawn, other bars that contain the same items are
+    /// left untouched.
+    pub fn update(&self) {
+        Weechat::bar_update(&self.name());
+    }
+
+    /// Get the name of the bar.
+    pub fn name(&self) -> String {
+        let weechat = Weechat::from_ptr(self.weechat);
+        let hdata = unsafe { weechat.hdata_get("bar") };
+        unsafe { weechat.hdata_string(hdata, self.ptr as *mut c_void, "name") }.into_owned()
+    }
+}
@@ -1,16 +1,28 @@
-use std::{borrow::Cow, ffi::CStr, os::raw::c_void, ptr};
+use std::{
+    borrow::Cow,
+    ffi::{CStr, CString},
+    os::raw::c_void,
+    ptr,
+    sync::atomic::{AtomicUsize, Ordering},
+};
 
 use libc::{c_char, c_int};
-use weechat_sys::{t_gui_buffer, t_weechat_plugin, WEECHAT_RC_OK};
+use weechat_sys::{t_gui_buffer, t_hook, t_weechat_plugin, WEECHAT_RC_ERROR, WEECHAT_RC_OK};
 
-use super::Hook;
-use crate::{buffer::Buffer, Args, LossyCString, ReturnCode, Weechat};
+use super::{CompletionCallback, CompletionHook, Hook};
+use crate::{
+    buffer::{Buffer, CommandOptions},
+    infolist::InfolistVariable,
+    Args, LossyCString, Prefix, ReturnCode, SplitFlags, Weechat,
+};
 
 /// Hook for a weechat command, the command is removed when the object is
 /// dropped.
 pub struct Command {
     _hook: Hook,
+    _alias_hooks: Vec<Hook>,
     _hook_data: Box<CommandHookData>,
+    _completion_hooks: Vec<CompletionHook>,
 }
 
 /// Trait for the command callback
@@ -37,6 +49,28 @@ impl<T: FnMut(&Weechat, &Buffer, Args) + 'static> CommandCallback for T {
     }
 }
 
+/// A command whose arguments are parsed into a struct.
+///
+/// This is usually implemented using `#[derive(WeechatCommand)]`, the derive
+/// generates the arguments, the argument description and the completion of
+/// the command from the fields of the struct. The command is created with
+/// `Command::typed()`.
+pub trait WeechatCommand: Sized {
+    /// The settings that describe the command.
+    fn settings() -> CommandSettings;
+
+    /// Parse the arguments of the command.
+    ///
+    /// Returns a message describing the problem if the arguments are
+    /// invalid.
+    ///
+    /// # Arguments
+    ///
+    /// * `arguments` - The arguments that were passed to the command, without
+    ///   the command itself.
+    fn parse(arguments: Vec<String>) -> Result<Self, String>;
+}
+
 #[derive(Default)]
 /// Description for a new Weechat command that should be created.
 ///
@@ -53,6 +87,177 @@ pub struct CommandSettings {
     argument_description: String,
     /// Completion template for the command.
     completion: Vec<String>,
+    /// Typed subcommands, used to generate the help and completion strings.
+    subcommands: Vec<Subcommand>,
+    /// Minimal number of arguments the command needs.
+    min_args: usize,
+    /// Should the usage be printed if the command is missing arguments.
+    usage_on_error: bool,
+    /// Alternative names for the command.
+    aliases: Vec<String>,
+    /// Completion template for the first argument of the command.
+    first_arg_completion: Option<String>,
+    /// Completion callbacks that are registered together with the command.
+    dynamic_completions: Vec<Box<dyn CompletionCallback>>,
+    /// Should invocations be checked against the subcommands.
+    strict: bool,
+}
+
+/// An argument of a subcommand.
+#[derive(Debug, Clone)]
+pub struct SubcommandArgument {
+    name: String,
+    required: bool,
+    free_form: bool,
+    description: String,
+    completion: Option<String>,
+}
+
+impl SubcommandArgument {
+    /// Create a required argument, displayed as `<name>` in the help.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the argument.
+    pub fn required<N: Into<String>>(name: N) -> Self {
+        SubcommandArgument {
+            name: name.into(),
+            required: true,
+            free_form: false,
+            description: String::new(),
+            completion: None,
+        }
+    }
+
+    /// Create an optional argument, displayed as `[<name>]` in the help.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the argument.
+    pub fn optional<N: Into<String>>(name: N) -> Self {
+        SubcommandArgument { required: false, ..SubcommandArgument::required(name) }
+    }
+
+    /// Set the description of the argument.
+    ///
+    /// # Arguments
+    ///
+    /// * `description` - The description that is displayed in the help.
+    pub fn description<D: Into<String>>(mut self, description: D) -> Self {
+        self.description = description.into();
+        self
+    }
+
+    /// Set the completion of the argument.
+    ///
+    /// Arguments without a completion aren't completed, the completion of
+    /// the arguments that follow them still applies.
+    ///
+    /// # Arguments
+    ///
+    /// * `completion` - A Weechat completion template, e.g. `%(nicks)`.
+    pub fn completion<C: Into<String>>(mut self, completion: C) -> Self {
+        self.completion = Some(completion.into());
+        self
+    }
+
+    /// Let the argument take the rest of the command line.
+    ///
+    /// The argument is displayed as `<name>...` in the help. Any number of
+    /// words may follow the argument when the command is checked in strict
+    /// mode, this should only be set on the last argument of a subcommand.
+    pub fn free_form(mut self) -> Self {
+        self.free_form = true;
+        self
+    }
+
+    fn usage(&self) -> String {
+        let dots = if self.free_form { "..." } else { "" };
+
+        if self.required {
+            format!("<{}>{}", self.name, dots)
+        } else {
+            format!("[<{}>{}]", self.name, dots)
+        }
+    }
+}
+
+/// A subcommand of a command.
+///
+/// The subcommands of a command are used to generate the arguments, the
+/// argument description and the completion of the command, keeping the help
+/// of the command in sync with the subcommands it accepts.
+#[derive(Debug, Clone)]
+pub struct Subcommand {
+    name: String,
+    description: String,
+    arguments: Vec<SubcommandArgument>,
+}
+
+impl Subcommand {
+    /// Create a new subcommand.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the subcommand.
+    pub fn new<N: Into<String>>(name: N) -> Self {
+        Subcommand { name: name.into(), description: String::new(), arguments: Vec::new() }
+    }
+
+    /// Set the description of the subcommand.
+    ///
+    /// # Arguments
+    ///
+    /// * `description` - The description that is displayed in the help.
+    pub fn description<D: Into<String>>(mut self, description: D) -> Self {
+        self.description = description.into();
+        self
+    }
+
+    /// Add an argument to the subcommand.
+    ///
+    /// # Arguments
+    ///
+    /// * `argument` - The argument that should be added.
+    pub fn add_argument(mut self, argument: SubcommandArgument) -> Self {
+        self.arguments.push(argument);
+        self
+    }
+
+    fn usage(&self) -> String {
+        std::iter::once(self.name.clone())
+            .chain(self.arguments.iter().map(|a| a.usage()))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    // Check the arguments that follow the name of the subcommand.
+    fn check(&self, arguments: &[String]) -> Result<(), String> {
+        let required = self.arguments.iter().filter(|a| a.required).count();
+        let free_form = matches!(self.arguments.last(), Some(a) if a.free_form);
+
+        if arguments.len() < required {
+            Err(format!("Missing arguments for \"{}\"", self.name))
+        } else if !free_form && arguments.len() > self.arguments.len() {
+            Err(format!("Too many arguments for \"{}\"", self.name))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn completion(&self) -> String {
+        // Arguments without a completion get the "%-" template, which
+        // completes nothing, so the templates of the following arguments
+        // stay at the position of their argument.
+        let mut templates: Vec<&str> =
+            self.arguments.iter().map(|a| a.completion.as_deref().unwrap_or("%-")).collect();
+
+        while templates.last() == Some(&"%-") {
+            templates.pop();
+        }
+
+        std::iter::once(self.name.as_str()).chain(templates).collect::<Vec<_>>().join(" ")
+    }
 }
 
 impl CommandSettings {
@@ -101,6 +306,91 @@ impl CommandSettings {
         self
     }
 
+    /// Add a subcommand to the command.
+    ///
+    /// The arguments, argument description and completion of the subcommand
+    /// are generated and appended to the ones that were set manually.
+    ///
+    /// # Arguments
+    ///
+    /// * `subcommand` - The subcommand that should be added.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use weechat::hooks::{CommandSettings, Subcommand, SubcommandArgument};
+    /// let settings = CommandSettings::new("matrix")
+    ///     .description("Matrix chat protocol command.")
+    ///     .add_subcommand(
+    ///         Subcommand::new("connect")
+    ///             .description("Connect to a server")
+    ///             .add_argument(
+    ///                 SubcommandArgument::required("server")
+    ///                     .description("The name of the server")
+    ///                     .completion("%(matrix_servers)"),
+    ///             ),
+    ///     )
+    ///     .add_subcommand(
+    ///         Subcommand::new("disconnect")
+    ///             .description("Disconnect from one or all servers")
+    ///             .add_argument(SubcommandArgument::optional("server")),
+    ///     );
+    /// ```
+    pub fn add_subcommand(mut self, subcommand: Subcommand) -> Self {
+        self.subcommands.push(subcommand);
+        self
+    }
+
+    fn generated_strings(&self) -> (String, String, String) {
+        let arguments = self
+            .arguments
+            .iter()
+            .cloned()
+            .chain(self.subcommands.iter().map(|s| s.usage()))
+            .collect::<Vec<_>>()
+            .join("||");
+
+        let completion = self
+            .first_arg_completion
+            .iter()
+            .cloned()
+            .chain(self.completion.iter().cloned())
+            .chain(self.subcommands.iter().map(|s| s.completion()))
+            .collect::<Vec<_>>()
+            .join("||");
+
+        let mut descriptions: Vec<(String, &str)> = Vec::new();
+
+        for subcommand in &self.subcommands {
+            descriptions.push((subcommand.name.clone(), &subcommand.description));
+        }
+
+        for argument in self.subcommands.iter().flat_map(|s| &s.arguments) {
+            let name = argument.name.clone();
+
+            if !argument.description.is_empty() && !descriptions.iter().any(|(n, _)| *n == name) {
+                descriptions.push((name, &argument.description));
+            }
+        }
+
+        let width = descriptions.iter().map(|(n, _)| n.chars().count()).max().unwrap_or(0);
+
+        let generated = descriptions
+            .iter()
+            .map(|(name, description)| format!("{:>width$}: {}", name, description, width = width))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let argument_description =
+            match (self.argument_description.is_empty(), generated.is_empty()) {
+                (_, true) => self.argument_description.clone(),
+                (true, false) => generated,
+                (false, false) => format!("{}\n\n{}", self.argument_description, generated),
+            };
+
+        (arguments, argument_description, completion)
+    }
+
     /// Add a completion definition to the command.
     ///
     /// Multiple arguments can be added to a command. See the `Command`
@@ -113,19 +403,310 @@ impl CommandSettings {
         self.completion.push(completion.into());
         self
     }
+
+    /// Set the completion template of the first argument of the command.
+    ///
+    /// The template is added as a separate alternative in front of the
+    /// `||`-joined completion list, Weechat merges the first argument of all
+    /// alternatives, so the first argument completes to the union of this
+    /// template and the first words of the other completions. The remaining
+    /// arguments are completed by the alternative whose first word matches.
+    ///
+    /// # Arguments
+    ///
+    /// * `completion` - A Weechat completion template, e.g.
+    ///   `connect|disconnect|%(my_servers)`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use weechat::hooks::CommandSettings;
+    /// let settings = CommandSettings::new("myplugin")
+    ///     .description("Control my plugin.")
+    ///     .first_arg_completion("connect|disconnect|help")
+    ///     .add_completion("connect %(my_servers)");
+    /// ```
+    pub fn first_arg_completion<C: Into<String>>(mut self, completion: C) -> Self {
+        self.first_arg_completion = Some(completion.into());
+        self
+    }
+
+    /// Add a completion callback for the command.
+    ///
+    /// The callback is registered as a completion item when the command is
+    /// created and is added to the completion template of the command as a
+    /// separate alternative, completing the first argument. The completion
+    /// item gets a unique generated name and is removed together with the
+    /// command. The arguments that were already typed can be inspected with
+    /// `Completion::arguments()`.
+    ///
+    /// # Arguments
+    ///
+    /// * `callback` - A function or a struct that implements
+    ///   CompletionCallback, the callback method of the trait will be called
+    ///   when the argument is completed.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use std::borrow::Cow;
+    /// # use weechat::Weechat;
+    /// # use weechat::buffer::Buffer;
+    /// # use weechat::hooks::{Completion, CommandSettings};
+    /// let settings = CommandSettings::new("greet").dynamic_completion(
+    ///     |_: &Weechat, buffer: &Buffer, _: Cow<str>, completion: &Completion| {
+    ///         for nick in buffer.nicks() {
+    ///             completion.add(&nick.name());
+    ///         }
+    ///         Ok(())
+    ///     },
+    /// );
+    /// ```
+    pub fn dynamic_completion(mut self, callback: impl CompletionCallback + 'static) -> Self {
+        self.dynamic_completions.push(Box::new(callback));
+        self
+    }
+
+    /// Set the minimal number of arguments the command needs.
+    ///
+    /// If the command is run with fewer arguments the callback isn't called
+    /// and the command fails, Weechat will then print an error pointing the
+    /// user to the command help.
+    ///
+    /// # Arguments
+    ///
+    /// * `min_args` - The minimal number of arguments, not counting the
+    ///   command itself. Defaults to 0 which disables the check.
+    pub fn min_args(mut self, min_args: usize) -> Self {
+        self.min_args = min_args;
+        self
+    }
+
+    /// Print the usage of the command if it's run with too few arguments.
+    ///
+    /// The usage consists of the arguments and the arguments description of
+    /// the command and is printed on the buffer the command was run on. This
+    /// has no effect unless `CommandSettings::min_args()` is set.
+    ///
+    /// # Arguments
+    ///
+    /// * `usage_on_error` - Should the usage be printed.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use weechat::hooks::CommandSettings;
+    /// let settings = CommandSettings::new("greet")
+    ///     .description("Greet somebody.")
+    ///     .add_argument("<nick>")
+    ///     .arguments_description("nick: The nick that should be greeted")
+    ///     .min_args(1)
+    ///     .usage_on_error(true);
+    /// ```
+    pub fn usage_on_error(mut self, usage_on_error: bool) -> Self {
+        self.usage_on_error = usage_on_error;
+        self
+    }
+
+    /// Check invocations of the command against its subcommands before the
+    /// callback runs.
+    ///
+    /// In strict mode the first argument has to be the name of a subcommand
+    /// and the number of arguments has to fit the arguments of the
+    /// subcommand. Invalid invocations are rejected and the usage of the
+    /// command is printed on the buffer the command was run on. Running the
+    /// command without arguments is still allowed, use
+    /// `CommandSettings::min_args()` to reject that as well.
+    ///
+    /// Arguments that were added with `CommandSettings::add_argument()` are
+    /// not considered, commands without subcommands aren't checked. Use
+    /// `SubcommandArgument::free_form()` for arguments that take the rest of
+    /// the line.
+    ///
+    /// # Arguments
+    ///
+    /// * `strict` - Should invocations be checked.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use weechat::hooks::{CommandSettings, Subcommand, SubcommandArgument};
+    /// let settings = CommandSettings::new("note")
+    ///     .add_subcommand(
+    ///         Subcommand::new("add")
+    ///             .add_argument(SubcommandArgument::required("text").free_form()),
+    ///     )
+    ///     .add_subcommand(Subcommand::new("list"))
+    ///     .strict(true);
+    /// ```
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Add an alias for the command.
+    ///
+    /// The alias is registered as an additional command that runs the same
+    /// callback, it's removed together with the command.
+    ///
+    /// # Arguments
+    ///
+    /// * `alias` - The alternative name of the command, without the leading
+    ///   slash.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use weechat::hooks::CommandSettings;
+    /// let settings = CommandSettings::new("myplugin")
+    ///     .description("Control my plugin.")
+    ///     .alias("mp");
+    /// ```
+    pub fn alias<A: Into<String>>(mut self, alias: A) -> Self {
+        self.aliases.push(alias.into());
+        self
+    }
+
+    fn usage(&self, arguments: &str, argument_description: &str) -> String {
+        let mut usage = arguments
+            .split("||")
+            .map(|a| format!("/{} {}", self.name, a).trim_end().to_owned())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        if !argument_description.is_empty() {
+            usage.push_str("\n\n");
+            usage.push_str(argument_description);
+        }
+
+        usage
+    }
 }
 
+// The callback of a command, either taking the split arguments or the raw
+// argument string.
+enum CommandHookCallback {
+    Args(Box<dyn CommandCallback>),
+    Raw(RawCommandCallback),
+}
+
+type RawCommandCallback = Box<dyn FnMut(&Weechat, &Buffer, Cow<str>)>;
+
 struct CommandHookData {
-    callback: Box<dyn CommandCallback>,
+    callback: CommandHookCallback,
     weechat_ptr: *mut t_weechat_plugin,
+    min_args: usize,
+    usage: Option<String>,
+    grammar: Option<CommandGrammar>,
+}
+
+// The subcommands a command in strict mode accepts.
+struct CommandGrammar {
+    subcommands: Vec<Subcommand>,
+    usage: String,
+}
+
+impl CommandGrammar {
+    fn check(&self, arguments: &[String]) -> Result<(), String> {
+        let (name, arguments) = match arguments.split_first() {
+            Some(split) => split,
+            None => return Ok(()),
+        };
+
+        self.subcommands
+            .iter()
+            .find(|s| &s.name == name)
+            .ok_or_else(|| format!("Unknown subcommand \"{}\"", name))?
+            .check(arguments)
+    }
 }
 
 /// Hook for a weechat command, the hook is removed when the object is dropped.
 pub struct CommandRun {
-    _hook: Hook,
+    _hooks: Vec<Hook>,
     _hook_data: Box<CommandRunHookData>,
 }
 
+/// The context of a command that is about to be run, passed to a
+/// `CommandRunCallback`.
+pub struct CommandRunContext<'a> {
+    weechat: &'a Weechat,
+    buffer: Buffer<'a>,
+    command: Cow<'a, str>,
+}
+
+impl<'a> CommandRunContext<'a> {
+    /// Get the buffer that received the command.
+    pub fn buffer(&self) -> &Buffer<'a> {
+        &self.buffer
+    }
+
+    /// Get the full command that is about to be run, including its
+    /// arguments.
+    pub fn command(&self) -> &str {
+        &self.command
+    }
+
+    /// Get the arguments of the command.
+    ///
+    /// The command is split on spaces the same way Weechat splits the
+    /// arguments it passes to a `Command`, the first argument is the command
+    /// itself. Quotes have no special meaning.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use weechat::{Weechat, ReturnCode};
+    /// # use weechat::hooks::{CommandRun, CommandRunContext};
+    /// let join = CommandRun::new("/join", |_: &Weechat, context: &CommandRunContext| {
+    ///     if let Some(channel) = context.args().nth(1) {
+    ///         Weechat::print(&format!("Joining {}", channel));
+    ///     }
+    ///     ReturnCode::Ok
+    /// })
+    /// .expect("Can't hook the join command");
+    /// ```
+    pub fn args(&self) -> Args {
+        Args::owned(self.weechat.string_split(&self.command, " ", SplitFlags::standard(), 0))
+    }
+
+    /// Run a modified command instead of the original one.
+    ///
+    /// The command is run on the buffer that received the original command,
+    /// the `CommandRun` hooks of this plugin aren't called for it. Returns
+    /// `ReturnCode::OkEat` which should be returned from the callback so the
+    /// original command isn't run.
+    ///
+    /// # Arguments
+    ///
+    /// * `command` - The command that should be run instead.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use weechat::{Weechat, ReturnCode};
+    /// # use weechat::hooks::{CommandRun, CommandRunContext};
+    /// let join = CommandRun::new("/join", |_: &Weechat, context: &CommandRunContext| {
+    ///     match context.args().nth(1) {
+    ///         Some(channel) if !channel.starts_with('#') => {
+    ///             context.eat_and_run(&format!("/join #{}", channel))
+    ///         }
+    ///         _ => ReturnCode::Ok,
+    ///     }
+    /// })
+    /// .expect("Can't hook the join command");
+    /// ```
+    pub fn eat_and_run(&self, command: &str) -> ReturnCode {
+        let options = CommandOptions::new().no_command_run();
+
+        // Errors of the command are reported by Weechat on the buffer, the
+        // original command has to be eaten either way.
+        let _ = self.buffer.run_command_with_options(command, &options);
+
+        ReturnCode::OkEat
+    }
+}
+
 /// Trait for the command-run callback
 ///
 /// A blanket implementation for pure `FnMut` functions exists, if data needs to
@@ -140,16 +721,14 @@ pub trait CommandRunCallback {
     ///
     /// * `weechat` - A Weechat context.
     ///
-    /// * `buffer` - The buffer that received the command.
-    ///
-    /// * `command` - The full command that was executed, including its
-    ///   arguments.
-    fn callback(&mut self, weechat: &Weechat, buffer: &Buffer, command: Cow<str>) -> ReturnCode;
+    /// * `context` - The context of the command, containing the buffer that
+    ///   received the command and the command itself.
+    fn callback(&mut self, weechat: &Weechat, context: &CommandRunContext) -> ReturnCode;
 }
 
-impl<T: FnMut(&Weechat, &Buffer, Cow<str>) -> ReturnCode + 'static> CommandRunCallback for T {
-    fn callback(&mut self, weechat: &Weechat, buffer: &Buffer, command: Cow<str>) -> ReturnCode {
-        self(weechat, buffer, command)
+impl<T: FnMut(&Weechat, &CommandRunContext) -> ReturnCode + 'static> CommandRunCallback for T {
+    fn callback(&mut self, weechat: &Weechat, context: &CommandRunContext) -> ReturnCode {
+        self(weechat, context)
     }
 }
 
@@ -158,6 +737,32 @@ struct CommandRunHookData {
     weechat_ptr: *mut t_weechat_plugin,
 }
 
+// Used to generate unique names for the completions of commands.
+static DYNAMIC_COMPLETION_ID: AtomicUsize = AtomicUsize::new(0);
+
+static COMMAND_RUN_SUPPRESSED: AtomicUsize = AtomicUsize::new(0);
+
+/// Guard that stops the `CommandRun` hooks of this plugin from being called
+/// while it is alive.
+pub(crate) struct CommandRunGuard;
+
+impl CommandRunGuard {
+    pub(crate) fn new() -> Self {
+        COMMAND_RUN_SUPPRESSED.fetch_add(1, Ordering::SeqCst);
+        CommandRunGuard
+    }
+
+    fn is_active() -> bool {
+        COMMAND_RUN_SUPPRESSED.load(Ordering::SeqCst) > 0
+    }
+}
+
+impl Drop for CommandRunGuard {
+    fn drop(&mut self) {
+        COMMAND_RUN_SUPPRESSED.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
 impl CommandRun {
     /// Override an existing Weechat command.
     ///
@@ -173,32 +778,75 @@ impl CommandRun {
     ///
     /// # Example
     /// ```no_run
-    /// # use std::borrow::Cow;
     /// # use weechat::{Weechat, ReturnCode};
-    /// # use weechat::hooks::CommandRun;
-    /// # use weechat::buffer::Buffer;
+    /// # use weechat::hooks::{CommandRun, CommandRunContext};
     ///
     /// let buffer_command = CommandRun::new(
     ///     "2000|/buffer *",
-    ///     |_: &Weechat, _: &Buffer, _: Cow<str>| ReturnCode::OkEat,
+    ///     |_: &Weechat, _: &CommandRunContext| ReturnCode::OkEat,
     /// )
     /// .expect("Can't override buffer command");
     /// ```
     pub fn new(command: &str, callback: impl CommandRunCallback + 'static) -> Result<Self, ()> {
+        CommandRun::new_multi(&[command], callback)
+    }
+
+    /// Override multiple existing Weechat commands with a single callback.
+    ///
+    /// All the hooks are removed when the returned object is dropped. If one
+    /// of the commands can't be hooked, the already created hooks are removed
+    /// and an error is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `commands` - The commands to override (wildcard `*` is allowed).
+    ///
+    /// * `callback` - The function that will be called when one of the
+    ///   commands is run.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use weechat::{Weechat, ReturnCode};
+    /// # use weechat::hooks::{CommandRun, CommandRunContext};
+    ///
+    /// let message_commands = CommandRun::new_multi(
+    ///     &["/query *", "/msg *", "/amsg *"],
+    ///     |_: &Weechat, context: &CommandRunContext| {
+    ///         Weechat::print(&format!("Sending a message with {}", context.command()));
+    ///         ReturnCode::Ok
+    ///     },
+    /// )
+    /// .expect("Can't override the message commands");
+    /// ```
+    pub fn new_multi(
+        commands: &[&str],
+        callback: impl CommandRunCallback + 'static,
+    ) -> Result<Self, ()> {
         unsafe extern "C" fn c_hook_cb(
             pointer: *const c_void,
             _data: *mut c_void,
             buffer: *mut t_gui_buffer,
             command: *const std::os::raw::c_char,
         ) -> c_int {
+            if CommandRunGuard::is_active() {
+                return WEECHAT_RC_OK;
+            }
+
             let hook_data: &mut CommandRunHookData = { &mut *(pointer as *mut CommandRunHookData) };
             let cb = &mut hook_data.callback;
 
             let weechat = Weechat::from_ptr(hook_data.weechat_ptr);
-            let buffer = weechat.buffer_from_ptr(buffer);
-            let command = CStr::from_ptr(command).to_string_lossy();
+            let context = CommandRunContext {
+                weechat: &weechat,
+                buffer: weechat.buffer_from_ptr(buffer),
+                command: CStr::from_ptr(command).to_string_lossy(),
+            };
 
-            cb.callback(&weechat, &buffer, command) as isize as i32
+            cb.callback(&weechat, &context) as isize as i32
         }
 
         Weechat::check_thread();
@@ -208,28 +856,40 @@ impl CommandRun {
             Box::new(CommandRunHookData { callback: Box::new(callback), weechat_ptr: weechat.ptr });
 
         let data_ref = Box::leak(data);
+        let data_ptr = data_ref as *const _ as *const c_void;
+        let hook_data = unsafe { Box::from_raw(data_ref) };
+
         let hook_command_run = weechat.get().hook_command_run.unwrap();
 
-        let command = LossyCString::new(command);
+        if commands.is_empty() {
+            return Err(());
+        }
 
-        let hook_ptr = unsafe {
-            hook_command_run(
-                weechat.ptr,
-                command.as_ptr(),
-                Some(c_hook_cb),
-                data_ref as *const _ as *const c_void,
-                ptr::null_mut(),
-            )
-        };
-        let hook_data = unsafe { Box::from_raw(data_ref) };
+        // The hooks are declared after the data so they are removed before the
+        // data is freed if we return early.
+        let mut hooks = Vec::with_capacity(commands.len());
 
-        if hook_ptr.is_null() {
-            Err(())
-        } else {
-            let hook = Hook { ptr: hook_ptr, weechat_ptr: weechat.ptr };
+        for command in commands {
+            let command = LossyCString::new(command);
+
+            let hook_ptr = unsafe {
+                hook_command_run(
+                    weechat.ptr,
+                    command.as_ptr(),
+                    Some(c_hook_cb),
+                    data_ptr,
+                    ptr::null_mut(),
+                )
+            };
+
+            if hook_ptr.is_null() {
+                return Err(());
+            }
 
-            Ok(CommandRun { _hook: hook, _hook_data: hook_data })
+            hooks.push(Hook { ptr: hook_ptr, weechat_ptr: weechat.ptr });
         }
+
+        Ok(CommandRun { _hooks: hooks, _hook_data: hook_data })
     }
 }
 
@@ -281,6 +941,126 @@ impl Command {
     pub fn new(
         command_settings: CommandSettings,
         callback: impl CommandCallback + 'static,
+    ) -> Result<Command, ()> {
+        Command::new_helper(command_settings, CommandHookCallback::Args(Box::new(callback)))
+    }
+
+    /// Create a new Weechat command whose callback receives the arguments as
+    /// a single string.
+    ///
+    /// The arguments are passed on exactly as the user typed them, without
+    /// splitting them and without the command itself. If the command was run
+    /// without arguments the string is empty. The minimal number of
+    /// arguments and the subcommands of strict mode are still checked.
+    ///
+    /// # Arguments
+    ///
+    /// * `command_settings` - Settings for the new command.
+    ///
+    /// * `callback` - The callback that will be called if the command is run.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use std::borrow::Cow;
+    /// # use weechat::Weechat;
+    /// # use weechat::buffer::Buffer;
+    /// # use weechat::hooks::{Command, CommandSettings};
+    /// let command = Command::new_raw(
+    ///     CommandSettings::new("myeval").add_argument("<expression>"),
+    ///     |_: &Weechat, buffer: &Buffer, expression: Cow<str>| {
+    ///         // Running `/myeval 2 + 2` passes "2 + 2".
+    ///         buffer.print(&format!("Evaluating {}", expression));
+    ///     },
+    /// )
+    /// .expect("Can't create command");
+    /// ```
+    pub fn new_raw(
+        command_settings: CommandSettings,
+        callback: impl FnMut(&Weechat, &Buffer, Cow<str>) + 'static,
+    ) -> Result<Command, ()> {
+        Command::new_helper(command_settings, CommandHookCallback::Raw(Box::new(callback)))
+    }
+
+    /// Create a new Weechat command whose arguments are parsed into a
+    /// struct.
+    ///
+    /// If the arguments can't be parsed the callback isn't called, the error
+    /// and the usage of the command are printed on the buffer the command
+    /// was run on instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `callback` - The callback that will be called with the parsed
+    ///   arguments if the command is run.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use weechat::Weechat;
+    /// # use weechat::buffer::Buffer;
+    /// # use weechat::hooks::{Command, WeechatCommand};
+    /// /// Greet somebody.
+    /// #[derive(WeechatCommand)]
+    /// #[command(name = "greet")]
+    /// struct Greet {
+    ///     /// Shout the greeting.
+    ///     loud: bool,
+    ///     /// Repeat the greeting this many times.
+    ///     #[arg(named)]
+    ///     times: Option<usize>,
+    ///     /// The nick that should be greeted.
+    ///     #[arg(completion = "%(nicks)")]
+    ///     nick: String,
+    /// }
+    ///
+    /// // Running `/greet -loud -times 2 poljar` greets poljar twice.
+    /// let command = Command::typed(|_: &Weechat, buffer: &Buffer, greet: Greet| {
+    ///     let greeting = format!("Hello {}", greet.nick);
+    ///     let greeting = if greet.loud { greeting.to_uppercase() } else { greeting };
+    ///
+    ///     for _ in 0..greet.times.unwrap_or(1) {
+    ///         buffer.print(&greeting);
+    ///     }
+    /// })
+    /// .expect("Can't create command");
+    /// ```
+    pub fn typed<C: WeechatCommand>(
+        mut callback: impl FnMut(&Weechat, &Buffer, C) + 'static,
+    ) -> Result<Command, ()> {
+        let settings = C::settings();
+        let (args, args_description, _) = settings.generated_strings();
+        let usage = settings.usage(&args, &args_description);
+        let name = settings.name.clone();
+
+        Command::new(settings, move |weechat: &Weechat, buffer: &Buffer, arguments: Args| {
+            // The first argument is the command itself.
+            match C::parse(arguments.skip(1).collect()) {
+                Ok(command) => callback(weechat, buffer, command),
+                Err(error) => {
+                    buffer.print(&format!(
+                        "{}/{}: {}",
+                        Weechat::prefix(Prefix::Error),
+                        name,
+                        error
+                    ));
+                    buffer.print(&usage);
+                }
+            }
+        })
+    }
+
+    fn new_helper(
+        mut command_settings: CommandSettings,
+        callback: CommandHookCallback,
     ) -> Result<Command, ()> {
         unsafe extern "C" fn c_hook_cb(
             pointer: *const c_void,
@@ -288,15 +1068,56 @@ impl Command {
             buffer: *mut t_gui_buffer,
             argc: i32,
             argv: *mut *mut c_char,
-            _argv_eol: *mut *mut c_char,
+            argv_eol: *mut *mut c_char,
         ) -> c_int {
             let hook_data: &mut CommandHookData = { &mut *(pointer as *mut CommandHookData) };
             let weechat = Weechat::from_ptr(hook_data.weechat_ptr);
             let buffer = weechat.buffer_from_ptr(buffer);
             let cb = &mut hook_data.callback;
-            let args = Args::new(argc, argv);
+            let mut args = Args::new(argc, argv);
+
+            if let Some(grammar) = &hook_data.grammar {
+                let words: Vec<String> = args.collect();
+
+                // The first argument is the command itself.
+                if let Err(error) = grammar.check(words.get(1..).unwrap_or_default()) {
+                    let command = words.first().map(|c| c.as_str()).unwrap_or_default();
+                    buffer.print(&format!(
+                        "{}{}: {}",
+                        Weechat::prefix(Prefix::Error),
+                        command,
+                        error
+                    ));
+                    buffer.print(&grammar.usage);
+
+                    return WEECHAT_RC_ERROR;
+                }
+
+                args = Args::owned(words);
+            }
+
+            // The first argument is the command itself.
+            if args.len().saturating_sub(1) < hook_data.min_args {
+                if let Some(usage) = &hook_data.usage {
+                    buffer.print(usage);
+                }
 
-            cb.callback(&weechat, &buffer, args);
+                return WEECHAT_RC_ERROR;
+            }
+
+            match cb {
+                CommandHookCallback::Args(cb) => cb.callback(&weechat, &buffer, args),
+                CommandHookCallback::Raw(cb) => {
+                    // The first element contains the command itself.
+                    let arguments = if argc > 1 {
+                        CStr::from_ptr(*argv_eol.offset(1)).to_string_lossy()
+                    } else {
+                        Cow::from("")
+                    };
+
+                    cb(&weechat, &buffer, arguments)
+                }
+            }
 
             WEECHAT_RC_OK
         }
@@ -304,19 +1125,77 @@ impl Command {
         Weechat::check_thread();
         let weechat = unsafe { Weechat::weechat() };
 
+        if !weechat.command_hooks(&command_settings.name, false).is_empty() {
+            Weechat::log(&format!(
+                "Warning: the command /{} is already registered",
+                command_settings.name
+            ));
+        }
+
+        let mut completion_hooks = Vec::new();
+
+        for callback in command_settings.dynamic_completions.drain(..) {
+            let id = DYNAMIC_COMPLETION_ID.fetch_add(1, Ordering::Relaxed);
+            let item = format!("{}_{}_{}", weechat.own_plugin_name(), command_settings.name, id);
+            let description = format!("Completion for /{}", command_settings.name);
+
+            completion_hooks.push(CompletionHook::new_boxed(&item, &description, callback)?);
+            command_settings.completion.push(format!("%({})", item));
+        }
+
+        let (args, args_description, completion) = command_settings.generated_strings();
+
+        let min_args = command_settings.min_args;
+        let usage = if command_settings.usage_on_error {
+            Some(command_settings.usage(&args, &args_description))
+        } else {
+            None
+        };
+        let grammar = if command_settings.strict && !command_settings.subcommands.is_empty() {
+            Some(CommandGrammar {
+                subcommands: command_settings.subcommands.clone(),
+                usage: command_settings.usage(&args, &args_description),
+            })
+        } else {
+            None
+        };
+
+        for alias in &command_settings.aliases {
+            if !weechat.command_hooks(alias, false).is_empty() {
+                Weechat::print(&format!(
+                    "{}Can't register /{} as an alias for /{}, the command already exists",
+                    Weechat::prefix(Prefix::Error),
+                    alias,
+                    command_settings.name
+                ));
+                return Err(());
+            }
+        }
+
+        let alias_description = LossyCString::new(format!("Alias for /{}.", command_settings.name));
+        let aliases: Vec<CString> =
+            command_settings.aliases.iter().map(LossyCString::new).collect();
+
         let name = LossyCString::new(command_settings.name);
         let description = LossyCString::new(command_settings.description);
-        let args = LossyCString::new(command_settings.arguments.join("||"));
-        let args_description = LossyCString::new(command_settings.argument_description);
-        let completion = LossyCString::new(command_settings.completion.join("||"));
+        let args = LossyCString::new(args);
+        let args_description = LossyCString::new(args_description);
+        let completion = LossyCString::new(completion);
 
-        let data =
-            Box::new(CommandHookData { callback: Box::new(callback), weechat_ptr: weechat.ptr });
+        let data = Box::new(CommandHookData {
+            callback,
+            weechat_ptr: weechat.ptr,
+            min_args,
+            usage,
+            grammar,
+        });
 
         let data_ref = Box::leak(data);
 
+        let data_ptr = data_ref as *const _ as *const c_void;
+
         let hook_command = weechat.get().hook_command.unwrap();
-        let hook_ptr = unsafe {
+        let hook = |name: &CString, description: &CString| unsafe {
             hook_command(
                 weechat.ptr,
                 name.as_ptr(),
@@ -325,18 +1204,101 @@ impl Command {
                 args_description.as_ptr(),
                 completion.as_ptr(),
                 Some(c_hook_cb),
-                data_ref as *const _ as *const c_void,
+                data_ptr,
                 ptr::null_mut(),
             )
         };
-        let hook_data = unsafe { Box::from_raw(data_ref) };
 
-        let hook = Hook { ptr: hook_ptr, weechat_ptr: weechat.ptr };
+        let hook_ptr = hook(&name, &description);
+        let hook_data = unsafe { Box::from_raw(data_ref) };
 
         if hook_ptr.is_null() {
-            Err(())
-        } else {
-            Ok(Command { _hook: hook, _hook_data: hook_data })
+            return Err(());
+        }
+
+        let mut command = Command {
+            _hook: Hook { ptr: hook_ptr, weechat_ptr: weechat.ptr },
+            _alias_hooks: Vec::new(),
+            _hook_data: hook_data,
+            _completion_hooks: completion_hooks,
+        };
+
+        for alias in &aliases {
+            let alias_ptr = hook(alias, &alias_description);
+
+            // Dropping the command removes the hooks that were already
+            // registered.
+            if alias_ptr.is_null() {
+                return Err(());
+            }
+
+            command._alias_hooks.push(Hook { ptr: alias_ptr, weechat_ptr: weechat.ptr });
+        }
+
+        Ok(command)
+    }
+}
+
+impl Weechat {
+    pub(crate) fn own_plugin_name(&self) -> Cow<'_, str> {
+        let plugin_get_name = self.get().plugin_get_name.unwrap();
+        unsafe { CStr::from_ptr(plugin_get_name(self.ptr)).to_string_lossy() }
+    }
+
+    fn command_hooks(&self, name: &str, own_only: bool) -> Vec<*mut t_hook> {
+        let plugin_name = self.own_plugin_name();
+
+        let infolist = match self.get_infolist("hook", Some(&format!("command,{}", name))) {
+            Ok(i) => i,
+            Err(_) => return Vec::new(),
+        };
+
+        infolist
+            .filter(|item| match item.get("command") {
+                Some(InfolistVariable::String(command)) => command == name,
+                _ => false,
+            })
+            .filter(|item| {
+                !own_only
+                    || match item.get("plugin_name") {
+                        Some(InfolistVariable::String(p)) => p == plugin_name,
+                        _ => false,
+                    }
+            })
+            .map(|item| item.pointer("pointer") as *mut t_hook)
+            .filter(|ptr| !ptr.is_null())
+            .collect()
+    }
+
+    /// Remove a command by name.
+    ///
+    /// This is a safety net to remove commands that linger around, e.g. after
+    /// a crash during a plugin reload, commands should normally be removed by
+    /// dropping their `Command` hook. Only commands that were registered by
+    /// this plugin are removed.
+    ///
+    /// Returns the number of removed commands.
+    ///
+    /// **Warning**: The `Command` object of a removed command must not be
+    /// alive, dropping it afterwards would remove the hook a second time.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the command, without the leading slash.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    pub fn unhook_command(&self, name: &str) -> usize {
+        Weechat::check_thread();
+
+        let unhook = self.get().unhook.unwrap();
+        let hooks = self.command_hooks(name, true);
+
+        for hook in &hooks {
+            unsafe { unhook(*hook) };
         }
+
+        hooks.len()
     }
 }
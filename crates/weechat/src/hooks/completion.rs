@@ -1,9 +1,7 @@
-use std::{borrow::Cow, ffi::CStr, os::raw::c_void, ptr};
+use std::{borrow::Cow, error::Error, ffi::CStr, fmt, os::raw::c_void, ptr};
 
 use libc::{c_char, c_int};
-use weechat_sys::{
-    t_gui_buffer, t_gui_completion, t_weechat_plugin, WEECHAT_RC_ERROR, WEECHAT_RC_OK,
-};
+use weechat_sys::{t_gui_buffer, t_gui_completion, t_weechat_plugin, WEECHAT_RC_OK};
 
 use crate::{buffer::Buffer, hooks::Hook, LossyCString, Weechat};
 
@@ -30,17 +28,21 @@ pub trait CompletionCallback {
     ///
     /// * `completion` - The completion object that should be populated with
     /// completion words by the callback.
+    ///
+    /// Returning an error logs it to the Weechat log file, the words that
+    /// were added before the error happened are still offered.
     fn callback(
         &mut self,
         weechat: &Weechat,
         buffer: &Buffer,
         completion_name: Cow<str>,
         completion: &Completion,
-    ) -> Result<(), ()>;
+    ) -> Result<(), CompletionError>;
 }
 
-impl<T: FnMut(&Weechat, &Buffer, Cow<str>, &Completion) -> Result<(), ()> + 'static>
-    CompletionCallback for T
+impl<
+        T: FnMut(&Weechat, &Buffer, Cow<str>, &Completion) -> Result<(), CompletionError> + 'static,
+    > CompletionCallback for T
 {
     fn callback(
         &mut self,
@@ -48,11 +50,76 @@ impl<T: FnMut(&Weechat, &Buffer, Cow<str>, &Completion) -> Result<(), ()> + 'sta
         buffer: &Buffer,
         completion_name: Cow<str>,
         completion: &Completion,
-    ) -> Result<(), ()> {
+    ) -> Result<(), CompletionError> {
         self(weechat, buffer, completion_name, completion)
     }
 }
 
+/// Error a completion callback returns if it can't provide its words, e.g.
+/// because the data source is unavailable.
+///
+/// The error is written to the Weechat log file, the user is offered the
+/// words that were added before the error happened.
+///
+/// # Example
+///
+/// ```no_run
+/// # use std::borrow::Cow;
+/// # use weechat::Weechat;
+/// # use weechat::buffer::Buffer;
+/// # use weechat::hooks::{Completion, CompletionError, CompletionHook};
+/// # fn fetch_servers() -> Result<Vec<String>, std::io::Error> { Ok(vec![]) }
+/// let hook = CompletionHook::new(
+///     "myplugin_servers",
+///     "Completion for the servers of my plugin",
+///     |_: &Weechat, _: &Buffer, _: Cow<str>, completion: &Completion| {
+///         let servers = fetch_servers().map_err(|e| CompletionError::new(e.to_string()))?;
+///
+///         for server in servers {
+///             completion.add(&server);
+///         }
+///
+///         Ok(())
+///     },
+/// )
+/// .expect("Can't create the completion");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompletionError {
+    message: String,
+}
+
+impl CompletionError {
+    /// Create a new completion error.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - A message describing why the completion failed, it's
+    ///   written to the Weechat log file.
+    pub fn new<M: Into<String>>(message: M) -> Self {
+        CompletionError { message: message.into() }
+    }
+
+    /// Get the message describing why the completion failed.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl From<()> for CompletionError {
+    fn from(_: ()) -> Self {
+        CompletionError::new("the completion callback failed")
+    }
+}
+
+impl fmt::Display for CompletionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl Error for CompletionError {}
+
 /// The positions an entry can be added to a completion list.
 #[derive(Clone, Copy)]
 pub enum CompletionPosition {
@@ -74,6 +141,106 @@ impl CompletionPosition {
     }
 }
 
+/// Completions that Weechat itself provides.
+///
+/// These can be used in the completion template of a command instead of
+/// writing the `%(...)` template by hand.
+///
+/// # Example
+///
+/// ```no_run
+/// # use weechat::hooks::{CommandSettings, CoreCompletion};
+/// let settings = CommandSettings::new("greet")
+///     .add_argument("<nick>")
+///     .add_completion(CoreCompletion::Nicks);
+///
+/// // A core completion can be combined with a custom one.
+/// let settings = CommandSettings::new("query")
+///     .add_argument("<nick>")
+///     .add_completion(format!("{}|%(myplugin_contacts)", CoreCompletion::Nicks.to_template()));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CoreCompletion {
+    /// Nicks of the current buffer.
+    Nicks,
+    /// Names of the buffers.
+    BuffersNames,
+    /// Numbers of the buffers.
+    BuffersNumbers,
+    /// Names of the buffers, including the plugin name.
+    BuffersPluginsNames,
+    /// Local variables of the current buffer.
+    BufferLocalVariables,
+    /// Commands of Weechat and the plugins.
+    Commands,
+    /// Commands of Weechat itself.
+    WeechatCommands,
+    /// Commands of the plugins.
+    PluginsCommands,
+    /// Names of the loaded plugins.
+    PluginsNames,
+    /// Names of the configuration files.
+    ConfigFiles,
+    /// Names of the configuration options.
+    ConfigOptions,
+    /// Color names.
+    Colors,
+    /// File names, directories are completed with a trailing slash. Use
+    /// `Weechat::expand_path()` to expand the path the user picked.
+    Filename,
+    /// Names of the filters.
+    FiltersNames,
+    /// Names of the bars.
+    BarsNames,
+    /// Names of the layouts.
+    LayoutsNames,
+    /// Names of the proxies.
+    ProxiesNames,
+    /// Numbers of the windows.
+    WindowsNumbers,
+    /// Environment variables.
+    EnvVars,
+}
+
+impl CoreCompletion {
+    /// Get the name of the completion item, e.g. `nicks`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            CoreCompletion::Nicks => "nicks",
+            CoreCompletion::BuffersNames => "buffers_names",
+            CoreCompletion::BuffersNumbers => "buffers_numbers",
+            CoreCompletion::BuffersPluginsNames => "buffers_plugins_names",
+            CoreCompletion::BufferLocalVariables => "buffer_local_variables",
+            CoreCompletion::Commands => "commands",
+            CoreCompletion::WeechatCommands => "weechat_commands",
+            CoreCompletion::PluginsCommands => "plugins_commands",
+            CoreCompletion::PluginsNames => "plugins_names",
+            CoreCompletion::ConfigFiles => "config_files",
+            CoreCompletion::ConfigOptions => "config_options",
+            CoreCompletion::Colors => "colors",
+            CoreCompletion::Filename => "filename",
+            CoreCompletion::FiltersNames => "filters_names",
+            CoreCompletion::BarsNames => "bars_names",
+            CoreCompletion::LayoutsNames => "layouts_names",
+            CoreCompletion::ProxiesNames => "proxies_names",
+            CoreCompletion::WindowsNumbers => "windows_numbers",
+            CoreCompletion::EnvVars => "env_vars",
+        }
+    }
+
+    /// Get the completion template of the completion, e.g. `%(nicks)`.
+    pub fn to_template(&self) -> String {
+        format!("%({})", self.name())
+    }
+}
+
+impl From<CoreCompletion> for String {
+    fn from(completion: CoreCompletion) -> Self {
+        completion.to_template()
+    }
+}
+
 impl Completion {
     pub(crate) fn from_raw(
         weechat: *mut t_weechat_plugin,
@@ -83,6 +250,12 @@ impl Completion {
     }
 
     /// Add a word for completion, keeping the list sorted.
+    ///
+    /// Weechat completions only consist of words, a description can't be
+    /// attached to a word and isn't displayed while completing. Describe the
+    /// possible values in the help of the command instead, e.g. using
+    /// `CommandSettings::arguments_description()`, users can read it with
+    /// `/help`.
     pub fn add(&self, word: &str) {
         self.add_with_options(word, false, CompletionPosition::Sorted)
     }
@@ -102,7 +275,27 @@ impl Completion {
         self.get_string("args")
     }
 
-    fn get_string(&self, property_name: &str) -> Option<Cow<str>> {
+    /// Get a string property of the completion.
+    ///
+    /// Returns `None` if the property is unknown.
+    ///
+    /// # Arguments
+    ///
+    /// * `property_name` - The name of the property, one of `base_command`,
+    ///   `base_word`, `args` or a property that the running Weechat version
+    ///   supports.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use weechat::hooks::Completion;
+    /// # fn complete(completion: &Completion) {
+    /// if completion.get_string("base_command").as_deref() == Some("join") {
+    ///     completion.add("#rust");
+    /// }
+    /// # }
+    /// ```
+    pub fn get_string(&self, property_name: &str) -> Option<Cow<str>> {
         let weechat = Weechat::from_ptr(self.weechat_ptr);
 
         let get_string = weechat.get().hook_completion_get_string.unwrap();
@@ -142,6 +335,42 @@ impl Completion {
             hook_completion_list_add(self.ptr, word.as_ptr(), is_nick as i32, method.as_ptr());
         }
     }
+
+    /// Add many words to the completion, ordered by the given keys instead of
+    /// alphabetically.
+    ///
+    /// The words are sorted by their key, words with equal keys keep the
+    /// order of the iterator. The words are then added to the end of the
+    /// list, so when Weechat narrows the list down to the words matching the
+    /// typed prefix the matches are offered in this order.
+    ///
+    /// # Arguments
+    ///
+    /// * `items` - The words that should be added, with the key they should
+    ///   be sorted by.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use std::cmp::Reverse;
+    /// # use weechat::hooks::Completion;
+    /// # fn complete(completion: &Completion) {
+    /// let servers = vec![("libera", 1588000000), ("oftc", 1590000000)];
+    ///
+    /// // Complete the most recently used server first.
+    /// completion.add_all_sorted(
+    ///     servers.into_iter().map(|(name, last_used)| (name.to_owned(), Reverse(last_used))),
+    /// );
+    /// # }
+    /// ```
+    pub fn add_all_sorted<K: Ord>(&self, items: impl IntoIterator<Item = (String, K)>) {
+        let mut items: Vec<(String, K)> = items.into_iter().collect();
+        items.sort_by(|a, b| a.1.cmp(&b.1));
+
+        for (word, _) in items {
+            self.add_with_options(&word, false, CompletionPosition::End);
+        }
+    }
 }
 
 /// Hook for a completion item, the hook is removed when the object is dropped.
@@ -175,14 +404,16 @@ impl CompletionHook {
     /// # use std::collections::HashSet;
     /// # use weechat::Weechat;
     /// # use weechat::buffer::Buffer;
-    /// # use weechat::hooks::{CompletionCallback, CompletionHook, Completion, CompletionPosition};
+    /// # use weechat::hooks::{
+    /// #     Completion, CompletionCallback, CompletionError, CompletionHook, CompletionPosition,
+    /// # };
     ///
     /// pub struct MyMap {
     ///     server_names: HashSet<String>,
     /// }
     ///
     /// impl CompletionCallback for MyMap {
-    ///     fn callback(&mut self, _: &Weechat, _: &Buffer, _: Cow<str>, completion: &Completion) -> Result<(), ()> {
+    ///     fn callback(&mut self, _: &Weechat, _: &Buffer, _: Cow<str>, completion: &Completion) -> Result<(), CompletionError> {
     ///         for server_name in &self.server_names {
     ///             completion.add_with_options(server_name, false, CompletionPosition::Sorted);
     ///         }
@@ -202,6 +433,14 @@ impl CompletionHook {
         completion_item: &str,
         description: &str,
         callback: impl CompletionCallback + 'static,
+    ) -> Result<CompletionHook, ()> {
+        CompletionHook::new_boxed(completion_item, description, Box::new(callback))
+    }
+
+    pub(crate) fn new_boxed(
+        completion_item: &str,
+        description: &str,
+        callback: Box<dyn CompletionCallback>,
     ) -> Result<CompletionHook, ()> {
         unsafe extern "C" fn c_hook_cb(
             pointer: *const c_void,
@@ -220,22 +459,23 @@ impl CompletionHook {
             let ret = cb.callback(
                 &weechat,
                 &buffer,
-                completion_item,
+                completion_item.clone(),
                 &Completion::from_raw(hook_data.weechat_ptr, completion),
             );
 
-            if let Ok(()) = ret {
-                WEECHAT_RC_OK
-            } else {
-                WEECHAT_RC_ERROR
+            // Failing the hook would make Weechat abort the whole completion,
+            // the words that were added so far are offered instead.
+            if let Err(e) = ret {
+                Weechat::log(&format!("Completion {} failed: {}", completion_item, e));
             }
+
+            WEECHAT_RC_OK
         }
 
         Weechat::check_thread();
         let weechat = unsafe { Weechat::weechat() };
 
-        let data =
-            Box::new(CompletionHookData { callback: Box::new(callback), weechat_ptr: weechat.ptr });
+        let data = Box::new(CompletionHookData { callback, weechat_ptr: weechat.ptr });
 
         let data_ref = Box::leak(data);
         let hook_completion = weechat.get().hook_completion.unwrap();
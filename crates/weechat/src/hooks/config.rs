@@ -0,0 +1,311 @@
+use std::{
+    cell::RefCell,
+    collections::BTreeSet,
+    ffi::CStr,
+    os::raw::{c_char, c_int, c_void},
+    ptr,
+    rc::Rc,
+    time::Duration,
+};
+
+use weechat_sys::{t_weechat_plugin, WEECHAT_RC_OK};
+
+use super::{timer::OneShot, Hook};
+use crate::{LossyCString, Weechat};
+
+/// Hook for changes of config options, the hook is removed when the object is
+/// dropped.
+pub struct ConfigHook {
+    _hook: Hook,
+    _hook_data: Box<ConfigHookData>,
+}
+
+struct ConfigHookData {
+    callback: Box<dyn ConfigCallback>,
+    weechat_ptr: *mut t_weechat_plugin,
+}
+
+/// Trait for the config callback.
+///
+/// A blanket implementation for pure `FnMut` functions exists, if data needs to
+/// be passed to the callback implement this over your struct.
+pub trait ConfigCallback {
+    /// Callback that will be called when a config option changes.
+    ///
+    /// # Arguments
+    ///
+    /// * `weechat` - A Weechat context.
+    ///
+    /// * `option_name` - The full name of the option that changed.
+    ///
+    /// * `value` - The new value of the option, `None` if the option was
+    ///   removed.
+    fn callback(&mut self, weechat: &Weechat, option_name: &str, value: Option<&str>);
+}
+
+impl<T: FnMut(&Weechat, &str, Option<&str>) + 'static> ConfigCallback for T {
+    fn callback(&mut self, weechat: &Weechat, option_name: &str, value: Option<&str>) {
+        self(weechat, option_name, value)
+    }
+}
+
+type CoalescedCallback = Box<dyn FnMut(&Weechat, BTreeSet<String>)>;
+
+struct CoalescedState {
+    changed: BTreeSet<String>,
+    // Taken out while the callback runs.
+    callback: Option<CoalescedCallback>,
+    timer: OneShot,
+}
+
+impl CoalescedState {
+    fn fire(state: &Rc<RefCell<CoalescedState>>, weechat: &Weechat) {
+        let (changed, callback) = {
+            let mut state = state.borrow_mut();
+            (std::mem::take(&mut state.changed), state.callback.take())
+        };
+
+        let mut callback = match callback {
+            Some(c) => c,
+            None => return,
+        };
+
+        // The callback may change options itself, those changes are reported
+        // in the next batch.
+        callback(weechat, changed);
+
+        state.borrow_mut().callback = Some(callback);
+    }
+}
+
+/// Trait for types that can be decoded from the string value of a config
+/// option.
+pub trait FromConfig: Sized {
+    /// Decode the value of a config option.
+    ///
+    /// Returns `None` if the value can't be represented by this type.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value of the option as Weechat formats it.
+    fn from_config(value: &str) -> Option<Self>;
+}
+
+impl FromConfig for bool {
+    fn from_config(value: &str) -> Option<Self> {
+        match value {
+            "on" => Some(true),
+            "off" => Some(false),
+            _ => None,
+        }
+    }
+}
+
+impl FromConfig for i32 {
+    fn from_config(value: &str) -> Option<Self> {
+        value.parse().ok()
+    }
+}
+
+impl FromConfig for i64 {
+    fn from_config(value: &str) -> Option<Self> {
+        value.parse().ok()
+    }
+}
+
+impl FromConfig for String {
+    fn from_config(value: &str) -> Option<Self> {
+        Some(value.to_owned())
+    }
+}
+
+impl ConfigHook {
+    /// Hook changes of config options.
+    ///
+    /// # Arguments
+    ///
+    /// * `option_pattern` - The full name of the option that should be
+    ///   watched, wildcard `*` is allowed, e.g. `weechat.look.*`.
+    ///
+    /// * `callback` - A function or a struct that implements ConfigCallback,
+    ///   the callback method of the trait will be called when a matching option
+    ///   changes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use weechat::Weechat;
+    /// # use weechat::hooks::ConfigHook;
+    /// let hook = ConfigHook::new(
+    ///     "weechat.look.*",
+    ///     |_: &Weechat, option_name: &str, value: Option<&str>| {
+    ///         Weechat::print(&format!("{} is now {}", option_name, value.unwrap_or("unset")));
+    ///     },
+    /// )
+    /// .expect("Can't hook config changes");
+    /// ```
+    pub fn new(option_pattern: &str, callback: impl ConfigCallback + 'static) -> Result<Self, ()> {
+        unsafe extern "C" fn c_hook_cb(
+            pointer: *const c_void,
+            _data: *mut c_void,
+            option_name: *const c_char,
+            value: *const c_char,
+        ) -> c_int {
+            let hook_data: &mut ConfigHookData = { &mut *(pointer as *mut ConfigHookData) };
+            let weechat = Weechat::from_ptr(hook_data.weechat_ptr);
+
+            let option_name = CStr::from_ptr(option_name).to_string_lossy();
+            let value =
+                if value.is_null() { None } else { Some(CStr::from_ptr(value).to_string_lossy()) };
+
+            hook_data.callback.callback(&weechat, &option_name, value.as_deref());
+
+            WEECHAT_RC_OK
+        }
+
+        Weechat::check_thread();
+        let weechat = unsafe { Weechat::weechat() };
+
+        let data =
+            Box::new(ConfigHookData { callback: Box::new(callback), weechat_ptr: weechat.ptr });
+
+        let data_ref = Box::leak(data);
+        let hook_config = weechat.get().hook_config.unwrap();
+
+        let option_pattern = LossyCString::new(option_pattern);
+
+        let hook_ptr = unsafe {
+            hook_config(
+                weechat.ptr,
+                option_pattern.as_ptr(),
+                Some(c_hook_cb),
+                data_ref as *const _ as *const c_void,
+                ptr::null_mut(),
+            )
+        };
+
+        let hook_data = unsafe { Box::from_raw(data_ref) };
+        let hook = Hook { ptr: hook_ptr, weechat_ptr: weechat.ptr };
+
+        if hook_ptr.is_null() {
+            Err(())
+        } else {
+            Ok(ConfigHook { _hook: hook, _hook_data: hook_data })
+        }
+    }
+
+    /// Hook changes of config options and decode the new value.
+    ///
+    /// Boolean options are decoded from `on` and `off`, integer options from
+    /// their decimal value. The callback isn't called if the option was
+    /// removed or if the value can't be decoded into `T`, e.g. for integer
+    /// options that use named values.
+    ///
+    /// # Arguments
+    ///
+    /// * `option_pattern` - The full name of the option that should be
+    ///   watched, wildcard `*` is allowed.
+    ///
+    /// * `callback` - A function that will be called with the name and the
+    ///   decoded value of the option that changed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use weechat::Weechat;
+    /// # use weechat::hooks::ConfigHook;
+    /// let hook = ConfigHook::typed("weechat.look.mouse", |_: &Weechat, _: &str, enabled: bool| {
+    ///     Weechat::print(if enabled { "Mouse enabled" } else { "Mouse disabled" });
+    /// })
+    /// .expect("Can't hook config changes");
+    /// ```
+    pub fn typed<T: FromConfig + 'static>(
+        option_pattern: &str,
+        mut callback: impl FnMut(&Weechat, &str, T) + 'static,
+    ) -> Result<Self, ()> {
+        ConfigHook::new(
+            option_pattern,
+            move |weechat: &Weechat, option_name: &str, value: Option<&str>| {
+                if let Some(value) = value.and_then(T::from_config) {
+                    callback(weechat, option_name, value)
+                }
+            },
+        )
+    }
+
+    /// Hook changes of config options and report them in batches.
+    ///
+    /// Changing many options at once, e.g. with `/set myplugin.*`, calls a
+    /// normal config hook once per option. This hook collects the names of
+    /// the changed options instead and calls the callback once, `debounce`
+    /// after the first change. A single change is reported after `debounce`
+    /// as well, so the delay should be kept short.
+    ///
+    /// # Arguments
+    ///
+    /// * `option_pattern` - The full name of the option that should be
+    ///   watched, wildcard `*` is allowed.
+    ///
+    /// * `debounce` - How long changes are collected before the callback is
+    ///   called, durations shorter than a millisecond are treated as a
+    ///   millisecond.
+    ///
+    /// * `callback` - A function that will be called with the full names of
+    ///   the options that changed or were removed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use std::collections::BTreeSet;
+    /// # use std::time::Duration;
+    /// # use weechat::Weechat;
+    /// # use weechat::hooks::ConfigHook;
+    /// let hook = ConfigHook::coalesced(
+    ///     "myplugin.net.*",
+    ///     Duration::from_millis(50),
+    ///     |_: &Weechat, changed: BTreeSet<String>| {
+    ///         Weechat::print(&format!("Reconnecting, {} options changed", changed.len()));
+    ///     },
+    /// )
+    /// .expect("Can't hook config changes");
+    /// ```
+    pub fn coalesced(
+        option_pattern: &str,
+        debounce: Duration,
+        callback: impl FnMut(&Weechat, BTreeSet<String>) + 'static,
+    ) -> Result<Self, ()> {
+        let state = Rc::new(RefCell::new(CoalescedState {
+            changed: BTreeSet::new(),
+            callback: Some(Box::new(callback)),
+            timer: OneShot::new(),
+        }));
+
+        ConfigHook::new(option_pattern, move |_: &Weechat, option_name: &str, _: Option<&str>| {
+            let pending = {
+                let mut state = state.borrow_mut();
+                state.changed.insert(option_name.to_owned());
+                state.timer.is_pending()
+            };
+
+            if pending {
+                return;
+            }
+
+            if OneShot::arm(&state, |s| &mut s.timer, debounce, CoalescedState::fire).is_err() {
+                Weechat::log("Can't create the timer for a coalesced config hook");
+            }
+        })
+    }
+}
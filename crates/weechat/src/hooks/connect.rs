@@ -0,0 +1,332 @@
+use std::{
+    borrow::Cow,
+    ffi::CStr,
+    os::{
+        raw::{c_char, c_int, c_void},
+        unix::io::RawFd,
+    },
+    ptr,
+};
+
+use weechat_sys::{
+    t_weechat_plugin, WEECHAT_HOOK_CONNECT_ADDRESS_NOT_FOUND,
+    WEECHAT_HOOK_CONNECT_CONNECTION_REFUSED, WEECHAT_HOOK_CONNECT_GNUTLS_HANDSHAKE_ERROR,
+    WEECHAT_HOOK_CONNECT_GNUTLS_INIT_ERROR, WEECHAT_HOOK_CONNECT_IP_ADDRESS_NOT_FOUND,
+    WEECHAT_HOOK_CONNECT_LOCAL_HOSTNAME_ERROR, WEECHAT_HOOK_CONNECT_MEMORY_ERROR,
+    WEECHAT_HOOK_CONNECT_OK, WEECHAT_HOOK_CONNECT_PROXY_ERROR, WEECHAT_HOOK_CONNECT_SOCKET_ERROR,
+    WEECHAT_HOOK_CONNECT_TIMEOUT, WEECHAT_RC_OK,
+};
+
+use super::Hook;
+use crate::{LossyCString, Weechat};
+
+/// The result of a connection attempt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectStatus {
+    /// The connection was established.
+    Connected,
+    /// The address couldn't be resolved.
+    AddressNotFound,
+    /// No IP address was found for the address.
+    IpAddressNotFound,
+    /// The connection was refused.
+    ConnectionRefused,
+    /// The proxy returned an error.
+    ProxyError,
+    /// The local hostname couldn't be used.
+    LocalHostnameError,
+    /// The TLS initialization failed.
+    GnutlsInitError,
+    /// The TLS handshake failed.
+    GnutlsHandshakeError,
+    /// Weechat ran out of memory.
+    MemoryError,
+    /// The connection attempt timed out.
+    Timeout,
+    /// The socket couldn't be created.
+    SocketError,
+    /// A status that this version of the library doesn't know about.
+    Unknown(i32),
+}
+
+impl From<c_int> for ConnectStatus {
+    fn from(status: c_int) -> Self {
+        match status {
+            WEECHAT_HOOK_CONNECT_OK => ConnectStatus::Connected,
+            WEECHAT_HOOK_CONNECT_ADDRESS_NOT_FOUND => ConnectStatus::AddressNotFound,
+            WEECHAT_HOOK_CONNECT_IP_ADDRESS_NOT_FOUND => ConnectStatus::IpAddressNotFound,
+            WEECHAT_HOOK_CONNECT_CONNECTION_REFUSED => ConnectStatus::ConnectionRefused,
+            WEECHAT_HOOK_CONNECT_PROXY_ERROR => ConnectStatus::ProxyError,
+            WEECHAT_HOOK_CONNECT_LOCAL_HOSTNAME_ERROR => ConnectStatus::LocalHostnameError,
+            WEECHAT_HOOK_CONNECT_GNUTLS_INIT_ERROR => ConnectStatus::GnutlsInitError,
+            WEECHAT_HOOK_CONNECT_GNUTLS_HANDSHAKE_ERROR => ConnectStatus::GnutlsHandshakeError,
+            WEECHAT_HOOK_CONNECT_MEMORY_ERROR => ConnectStatus::MemoryError,
+            WEECHAT_HOOK_CONNECT_TIMEOUT => ConnectStatus::Timeout,
+            WEECHAT_HOOK_CONNECT_SOCKET_ERROR => ConnectStatus::SocketError,
+            s => ConnectStatus::Unknown(s),
+        }
+    }
+}
+
+/// Settings describing a connection that should be established.
+pub struct ConnectSettings {
+    address: String,
+    port: u16,
+    proxy: Option<String>,
+    ipv6: bool,
+    retry: i32,
+    local_hostname: Option<String>,
+}
+
+impl ConnectSettings {
+    /// Create new connection settings.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - The name or IP address of the host to connect to.
+    ///
+    /// * `port` - The port to connect to.
+    pub fn new<A: Into<String>>(address: A, port: u16) -> Self {
+        ConnectSettings {
+            address: address.into(),
+            port,
+            proxy: None,
+            ipv6: true,
+            retry: 0,
+            local_hostname: None,
+        }
+    }
+
+    /// Set the proxy that should be used for the connection.
+    ///
+    /// # Arguments
+    ///
+    /// * `proxy` - The name of a proxy that the user defined with the `/proxy`
+    ///   command.
+    pub fn proxy<P: Into<String>>(mut self, proxy: P) -> Self {
+        self.proxy = Some(proxy.into());
+        self
+    }
+
+    /// Set if IPv6 should be used, with a fallback to IPv4. Enabled by
+    /// default.
+    pub fn ipv6(mut self, ipv6: bool) -> Self {
+        self.ipv6 = ipv6;
+        self
+    }
+
+    /// Set the retry count, used to fall back to IPv4 hosts if the connection
+    /// over IPv6 fails.
+    pub fn retry(mut self, retry: i32) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Set the local hostname that should be used for the connection.
+    pub fn local_hostname<H: Into<String>>(mut self, hostname: H) -> Self {
+        self.local_hostname = Some(hostname.into());
+        self
+    }
+}
+
+/// Trait for the connect callback.
+///
+/// A blanket implementation for pure `FnMut` functions exists, if data needs to
+/// be passed to the callback implement this over your struct.
+pub trait ConnectCallback {
+    /// Callback that will be called once the connection attempt finished.
+    ///
+    /// # Arguments
+    ///
+    /// * `weechat` - A Weechat context.
+    ///
+    /// * `status` - The result of the connection attempt.
+    ///
+    /// * `socket` - The connected socket if the connection was established,
+    ///   the callback takes ownership of the socket and is responsible for
+    ///   closing it.
+    ///
+    /// * `ip_address` - The IP address that was used for the connection.
+    ///
+    /// * `error` - An additional error message, if any.
+    fn callback(
+        &mut self,
+        weechat: &Weechat,
+        status: ConnectStatus,
+        socket: Option<RawFd>,
+        ip_address: Option<Cow<str>>,
+        error: Option<Cow<str>>,
+    );
+}
+
+impl<
+        T: FnMut(&Weechat, ConnectStatus, Option<RawFd>, Option<Cow<str>>, Option<Cow<str>>) + 'static,
+    > ConnectCallback for T
+{
+    fn callback(
+        &mut self,
+        weechat: &Weechat,
+        status: ConnectStatus,
+        socket: Option<RawFd>,
+        ip_address: Option<Cow<str>>,
+        error: Option<Cow<str>>,
+    ) {
+        self(weechat, status, socket, ip_address, error)
+    }
+}
+
+struct ConnectHookData {
+    callback: Box<dyn ConnectCallback>,
+    weechat_ptr: *mut t_weechat_plugin,
+    finished: bool,
+}
+
+/// Hook for a non-blocking connection, the connection attempt is aborted if
+/// the object is dropped before it finished.
+pub struct ConnectHook {
+    _hook: Hook,
+    _hook_data: Box<ConnectHookData>,
+}
+
+impl Drop for ConnectHook {
+    fn drop(&mut self) {
+        // Weechat removes the hook on its own once the callback ran.
+        if self._hook_data.finished {
+            self._hook.ptr = ptr::null_mut();
+        }
+    }
+}
+
+impl ConnectHook {
+    /// Connect to a remote host without blocking Weechat.
+    ///
+    /// Weechat resolves the address and establishes the connection in a
+    /// child process, honoring the proxy settings of the user. TLS sessions
+    /// aren't supported, the connection is a plain TCP connection.
+    ///
+    /// # Arguments
+    ///
+    /// * `settings` - Settings describing the connection.
+    ///
+    /// * `callback` - A function or a struct that implements ConnectCallback,
+    ///   the callback method of the trait will be called once the connection
+    ///   attempt finished.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use std::borrow::Cow;
+    /// # use std::os::unix::io::RawFd;
+    /// # use weechat::Weechat;
+    /// # use weechat::hooks::{ConnectHook, ConnectSettings, ConnectStatus};
+    /// let settings = ConnectSettings::new("irc.libera.chat", 6667).proxy("tor");
+    ///
+    /// let hook = ConnectHook::new(
+    ///     settings,
+    ///     |_: &Weechat,
+    ///      status: ConnectStatus,
+    ///      socket: Option<RawFd>,
+    ///      _: Option<Cow<str>>,
+    ///      _: Option<Cow<str>>| {
+    ///         if let Some(socket) = socket {
+    ///             Weechat::print(&format!("Connected, socket {}", socket));
+    ///         } else {
+    ///             Weechat::print(&format!("Can't connect: {:?}", status));
+    ///         }
+    ///     },
+    /// )
+    /// .expect("Can't start the connection");
+    /// ```
+    pub fn new(
+        settings: ConnectSettings,
+        callback: impl ConnectCallback + 'static,
+    ) -> Result<ConnectHook, ()> {
+        unsafe extern "C" fn c_hook_cb(
+            pointer: *const c_void,
+            _data: *mut c_void,
+            status: c_int,
+            _gnutls_rc: c_int,
+            sock: c_int,
+            error: *const c_char,
+            ip_address: *const c_char,
+        ) -> c_int {
+            let hook_data: &mut ConnectHookData = { &mut *(pointer as *mut ConnectHookData) };
+            hook_data.finished = true;
+
+            let weechat = Weechat::from_ptr(hook_data.weechat_ptr);
+            let status = ConnectStatus::from(status);
+
+            let to_option = |string: *const c_char| {
+                if string.is_null() {
+                    None
+                } else {
+                    Some(CStr::from_ptr(string).to_string_lossy())
+                }
+            };
+
+            let socket = if status == ConnectStatus::Connected && sock >= 0 {
+                Some(sock as RawFd)
+            } else {
+                None
+            };
+
+            hook_data.callback.callback(
+                &weechat,
+                status,
+                socket,
+                to_option(ip_address),
+                to_option(error),
+            );
+
+            WEECHAT_RC_OK
+        }
+
+        Weechat::check_thread();
+        let weechat = unsafe { Weechat::weechat() };
+
+        let data = Box::new(ConnectHookData {
+            callback: Box::new(callback),
+            weechat_ptr: weechat.ptr,
+            finished: false,
+        });
+
+        let data_ref = Box::leak(data);
+        let hook_connect = weechat.get().hook_connect.unwrap();
+
+        let proxy = settings.proxy.map(LossyCString::new);
+        let address = LossyCString::new(settings.address);
+        let local_hostname = settings.local_hostname.map(LossyCString::new);
+
+        let hook_ptr = unsafe {
+            hook_connect(
+                weechat.ptr,
+                proxy.as_ref().map_or(ptr::null(), |p| p.as_ptr()),
+                address.as_ptr(),
+                settings.port as c_int,
+                settings.ipv6 as c_int,
+                settings.retry,
+                ptr::null_mut(),
+                ptr::null_mut(),
+                0,
+                ptr::null(),
+                local_hostname.as_ref().map_or(ptr::null(), |h| h.as_ptr()),
+                Some(c_hook_cb),
+                data_ref as *const _ as *const c_void,
+                ptr::null_mut(),
+            )
+        };
+
+        let hook_data = unsafe { Box::from_raw(data_ref) };
+        let hook = Hook { ptr: hook_ptr, weechat_ptr: weechat.ptr };
+
+        if hook_ptr.is_null() {
+            Err(())
+        } else {
+            Ok(ConnectHook { _hook: hook, _hook_data: hook_data })
+        }
+    }
+}
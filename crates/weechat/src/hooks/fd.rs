@@ -4,7 +4,7 @@ use std::{
 };
 
 use libc::c_int;
-use weechat_sys::{t_weechat_plugin, WEECHAT_RC_OK};
+use weechat_sys::{t_hook, t_weechat_plugin, WEECHAT_RC_OK};
 
 use super::Hook;
 use crate::Weechat;
@@ -62,6 +62,61 @@ struct FdHookData<F> {
     callback: Box<dyn FdHookCallback<FdObject = F>>,
     weechat_ptr: *mut t_weechat_plugin,
     fd_object: F,
+    fd: i32,
+    mode: (i32, i32),
+    one_shot: bool,
+    // The currently registered Weechat hook, null if the hook is paused or
+    // if a one-shot hook already fired.
+    hook_ptr: *mut t_hook,
+}
+
+impl<F> Drop for FdHook<F> {
+    fn drop(&mut self) {
+        // The hook might have been removed by pause() or after a one-shot
+        // call, only unhook the currently registered one.
+        self._hook.ptr = self._hook_data.hook_ptr;
+    }
+}
+
+unsafe extern "C" fn c_hook_cb<F>(pointer: *const c_void, _data: *mut c_void, _fd: i32) -> c_int {
+    let hook_data: &mut FdHookData<F> = { &mut *(pointer as *mut FdHookData<F>) };
+    let weechat = Weechat::from_ptr(hook_data.weechat_ptr);
+
+    if hook_data.one_shot && !hook_data.hook_ptr.is_null() {
+        // Weechat allows a hook to be removed while its callback runs.
+        weechat.get().unhook.unwrap()(hook_data.hook_ptr);
+        hook_data.hook_ptr = ptr::null_mut();
+    }
+
+    let cb = &mut hook_data.callback;
+    let mut fd_object = &mut hook_data.fd_object;
+
+    cb.callback(&weechat, &mut fd_object);
+
+    WEECHAT_RC_OK
+}
+
+impl<F> FdHookData<F> {
+    fn hook(&mut self) -> *mut t_hook {
+        let weechat = Weechat::from_ptr(self.weechat_ptr);
+        let hook_fd = weechat.get().hook_fd.unwrap();
+        let (read, write) = self.mode;
+
+        self.hook_ptr = unsafe {
+            hook_fd(
+                weechat.ptr,
+                self.fd,
+                read,
+                write,
+                0,
+                Some(c_hook_cb::<F>),
+                self as *const _ as *const c_void,
+                ptr::null_mut(),
+            )
+        };
+
+        self.hook_ptr
+    }
 }
 
 impl<F> FdHook<F> {
@@ -119,50 +174,69 @@ impl<F> FdHook<F> {
     where
         F: AsRawFd,
     {
-        unsafe extern "C" fn c_hook_cb<F>(
-            pointer: *const c_void,
-            _data: *mut c_void,
-            _fd: i32,
-        ) -> c_int {
-            let hook_data: &mut FdHookData<F> = { &mut *(pointer as *mut FdHookData<F>) };
-            let cb = &mut hook_data.callback;
-            let mut fd_object = &mut hook_data.fd_object;
-            let weechat = Weechat::from_ptr(hook_data.weechat_ptr);
-
-            cb.callback(&weechat, &mut fd_object);
-
-            WEECHAT_RC_OK
-        }
+        FdHook::new_helper(fd_object, mode, false, callback)
+    }
+
+    /// Hook an object that can be turned into a raw file descriptor, the hook
+    /// is removed after the first call of the callback.
+    ///
+    /// Weechat watches file descriptors level-triggered, a normal `FdHook`
+    /// keeps calling the callback as long as the file descriptor is readable
+    /// or writable. A one-shot hook is useful to get notified once, e.g. when
+    /// a connecting socket becomes writable. The hook can be armed again using
+    /// `FdHook::resume()`.
+    ///
+    /// # Arguments
+    ///
+    /// * `fd_object` - An object for which the file descriptor will be watched
+    ///   and the callback called when read or write operations can happen on
+    ///   it.
+    ///
+    /// * `mode` - Configure the hook to watch for writes, reads or both on the
+    ///   file descriptor.
+    ///
+    /// * `callback` - A function that will be called once a watched event on
+    ///   the file descriptor happens.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    pub fn once(
+        fd_object: F,
+        mode: FdHookMode,
+        callback: impl FdHookCallback<FdObject = F> + 'static,
+    ) -> Result<FdHook<F>, ()>
+    where
+        F: AsRawFd,
+    {
+        FdHook::new_helper(fd_object, mode, true, callback)
+    }
 
+    fn new_helper(
+        fd_object: F,
+        mode: FdHookMode,
+        one_shot: bool,
+        callback: impl FdHookCallback<FdObject = F> + 'static,
+    ) -> Result<FdHook<F>, ()>
+    where
+        F: AsRawFd,
+    {
         Weechat::check_thread();
         let weechat = unsafe { Weechat::weechat() };
 
         let fd = fd_object.as_raw_fd();
 
-        let data = Box::new(FdHookData {
+        let mut hook_data = Box::new(FdHookData {
             callback: Box::new(callback),
             weechat_ptr: weechat.ptr,
             fd_object,
+            fd,
+            mode: mode.as_tuple(),
+            one_shot,
+            hook_ptr: ptr::null_mut(),
         });
 
-        let data_ref = Box::leak(data);
-        let hook_fd = weechat.get().hook_fd.unwrap();
-        let (read, write) = mode.as_tuple();
-
-        let hook_ptr = unsafe {
-            hook_fd(
-                weechat.ptr,
-                fd,
-                read,
-                write,
-                0,
-                Some(c_hook_cb::<F>),
-                data_ref as *const _ as *const c_void,
-                ptr::null_mut(),
-            )
-        };
-
-        let hook_data = unsafe { Box::from_raw(data_ref) };
+        let hook_ptr = hook_data.hook();
         let hook = Hook { ptr: hook_ptr, weechat_ptr: weechat.ptr };
 
         if hook_ptr.is_null() {
@@ -171,4 +245,46 @@ impl<F> FdHook<F> {
             Ok(FdHook::<F> { _hook: hook, _hook_data: hook_data })
         }
     }
+
+    /// Stop watching the file descriptor without removing the hook.
+    ///
+    /// Does nothing if the hook is already paused.
+    pub fn pause(&mut self) {
+        let hook_ptr = self._hook_data.hook_ptr;
+
+        if !hook_ptr.is_null() {
+            let weechat = Weechat::from_ptr(self._hook_data.weechat_ptr);
+            unsafe { weechat.get().unhook.unwrap()(hook_ptr) };
+            self._hook_data.hook_ptr = ptr::null_mut();
+        }
+    }
+
+    /// Start watching the file descriptor again after the hook was paused.
+    ///
+    /// The file descriptor is watched with the same mode as before. A one-shot
+    /// hook that already fired is armed again.
+    ///
+    /// Returns an error if Weechat refused to watch the file descriptor.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    pub fn resume(&mut self) -> Result<(), ()> {
+        Weechat::check_thread();
+
+        if !self._hook_data.hook_ptr.is_null() {
+            return Ok(());
+        }
+
+        if self._hook_data.hook().is_null() {
+            Err(())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Is the file descriptor currently being watched.
+    pub fn is_active(&self) -> bool {
+        !self._hook_data.hook_ptr.is_null()
+    }
 }
@@ -0,0 +1,218 @@
+use std::{collections::HashMap, os::raw::c_void, ptr};
+
+use weechat_sys::{t_gui_buffer, t_hashtable, t_weechat_plugin};
+
+use super::Hook;
+use crate::{buffer::Buffer, LossyCString, Weechat};
+
+/// Hook for the focus of an area, the hook is removed when the object is
+/// dropped.
+///
+/// Focus hooks are called when the user clicks with the mouse or uses the
+/// cursor mode, they can add additional information that is passed to the
+/// command bound to the key.
+pub struct FocusHook {
+    _hook: Hook,
+    _hook_data: Box<FocusHookData>,
+}
+
+struct FocusHookData {
+    callback: Box<dyn FocusCallback>,
+    weechat_ptr: *mut t_weechat_plugin,
+}
+
+/// Information about the area that has the focus.
+///
+/// Fields that don't apply to the focused area, e.g. the chat fields if a bar
+/// has the focus, are returned as `None`.
+pub struct FocusInfo<'a> {
+    weechat: &'a Weechat,
+    info: HashMap<String, String>,
+}
+
+impl<'a> FocusInfo<'a> {
+    /// Get the raw value of a focus field.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The name of the field, e.g. `_chat_line_nick`. See the
+    ///   Weechat `hook_focus()` documentation for a list of fields.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.info.get(key).map(|v| v.as_str()).filter(|v| !v.is_empty())
+    }
+
+    fn get_pointer(&self, key: &str) -> Option<*mut c_void> {
+        let value = self.get(key)?;
+        let value = value.trim_start_matches("0x");
+
+        usize::from_str_radix(value, 16).ok().filter(|p| *p != 0).map(|p| p as *mut c_void)
+    }
+
+    /// Get the key or mouse event that triggered the focus, e.g. `button1`.
+    pub fn key(&self) -> Option<&str> {
+        self.get("_key")
+    }
+
+    /// Was the focus triggered by a mouse event.
+    pub fn is_mouse(&self) -> bool {
+        matches!(self.key(), Some(k) if k.starts_with("button") || k.starts_with("wheel"))
+    }
+
+    /// Get the buffer that has the focus.
+    pub fn buffer(&self) -> Option<Buffer<'a>> {
+        let buffer_ptr = self.get_pointer("_buffer")?;
+        Some(self.weechat.buffer_from_ptr(buffer_ptr as *mut t_gui_buffer))
+    }
+
+    /// Is the focus on the chat area of a window.
+    pub fn is_chat(&self) -> bool {
+        self.get("_chat") == Some("1")
+    }
+
+    /// Get the word under the focus in the chat area.
+    pub fn chat_word(&self) -> Option<&str> {
+        self.get("_chat_word")
+    }
+
+    /// Get the message of the chat line under the focus.
+    pub fn chat_line_message(&self) -> Option<&str> {
+        self.get("_chat_line_message")
+    }
+
+    /// Get the nick of the chat line under the focus.
+    pub fn chat_line_nick(&self) -> Option<&str> {
+        self.get("_chat_line_nick")
+    }
+
+    /// Get the tags of the chat line under the focus.
+    pub fn chat_line_tags(&self) -> Vec<&str> {
+        self.get("_chat_line_tags").map(|t| t.split(',').collect()).unwrap_or_default()
+    }
+
+    /// Get the name of the bar that has the focus.
+    pub fn bar(&self) -> Option<&str> {
+        self.get("_bar_name")
+    }
+
+    /// Get the name of the bar item that has the focus.
+    pub fn bar_item(&self) -> Option<&str> {
+        self.get("_bar_item_name")
+    }
+}
+
+/// Trait for the focus callback.
+///
+/// A blanket implementation for pure `FnMut` functions exists, if data needs to
+/// be passed to the callback implement this over your struct.
+pub trait FocusCallback {
+    /// Callback that will be called when the hooked area gets the focus.
+    ///
+    /// Returns additional fields that should be passed to the command bound
+    /// to the key, an empty map adds nothing.
+    ///
+    /// # Arguments
+    ///
+    /// * `weechat` - A Weechat context.
+    ///
+    /// * `info` - Information about the focused area.
+    fn callback(&mut self, weechat: &Weechat, info: &FocusInfo) -> HashMap<String, String>;
+}
+
+impl<T: FnMut(&Weechat, &FocusInfo) -> HashMap<String, String> + 'static> FocusCallback for T {
+    fn callback(&mut self, weechat: &Weechat, info: &FocusInfo) -> HashMap<String, String> {
+        self(weechat, info)
+    }
+}
+
+impl FocusHook {
+    /// Hook the focus of an area.
+    ///
+    /// # Arguments
+    ///
+    /// * `area` - The area that should be hooked, `chat` for the chat area or
+    ///   the name of a bar item.
+    ///
+    /// * `callback` - A function or a struct that implements FocusCallback,
+    ///   the callback method of the trait will be called when the area gets
+    ///   the focus.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use std::collections::HashMap;
+    /// # use weechat::Weechat;
+    /// # use weechat::hooks::{FocusHook, FocusInfo};
+    /// let focus = FocusHook::new("chat", |_: &Weechat, focus: &FocusInfo| {
+    ///     let mut extra = HashMap::new();
+    ///
+    ///     if let Some(word) = focus.chat_word() {
+    ///         if word.starts_with("https://") {
+    ///             extra.insert("url".to_owned(), word.to_owned());
+    ///         }
+    ///     }
+    ///
+    ///     extra
+    /// })
+    /// .expect("Can't hook the chat focus");
+    /// ```
+    pub fn new(area: &str, callback: impl FocusCallback + 'static) -> Result<Self, ()> {
+        unsafe extern "C" fn c_hook_cb(
+            pointer: *const c_void,
+            _data: *mut c_void,
+            info: *mut t_hashtable,
+        ) -> *mut t_hashtable {
+            let hook_data: &mut FocusHookData = { &mut *(pointer as *mut FocusHookData) };
+            let weechat = Weechat::from_ptr(hook_data.weechat_ptr);
+
+            let focus = FocusInfo { weechat: &weechat, info: weechat.hashtable_to_map(info) };
+            let extra = hook_data.callback.callback(&weechat, &focus);
+
+            // Weechat accepts the info hashtable with added keys as the return
+            // value.
+            let hashtable_set = weechat.get().hashtable_set.unwrap();
+
+            for (key, value) in extra {
+                let key = LossyCString::new(key);
+                let value = LossyCString::new(value);
+
+                hashtable_set(info, key.as_ptr() as *const c_void, value.as_ptr() as *const c_void);
+            }
+
+            info
+        }
+
+        Weechat::check_thread();
+        let weechat = unsafe { Weechat::weechat() };
+
+        let data =
+            Box::new(FocusHookData { callback: Box::new(callback), weechat_ptr: weechat.ptr });
+
+        let data_ref = Box::leak(data);
+        let hook_focus = weechat.get().hook_focus.unwrap();
+
+        let area = LossyCString::new(area);
+
+        let hook_ptr = unsafe {
+            hook_focus(
+                weechat.ptr,
+                area.as_ptr(),
+                Some(c_hook_cb),
+                data_ref as *const _ as *const c_void,
+                ptr::null_mut(),
+            )
+        };
+
+        let hook_data = unsafe { Box::from_raw(data_ref) };
+        let hook = Hook { ptr: hook_ptr, weechat_ptr: weechat.ptr };
+
+        if hook_ptr.is_null() {
+            Err(())
+        } else {
+            Ok(FocusHook { _hook: hook, _hook_data: hook_data })
+        }
+    }
+}
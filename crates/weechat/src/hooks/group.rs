@@ -0,0 +1,161 @@
+use std::{
+    any::Any,
+    cell::RefCell,
+    rc::{Rc, Weak},
+};
+
+/// A group of hooks that share a single state object.
+///
+/// The group owns the state and the hooks that were added to it, the hooks
+/// are removed when the group is dropped. Callbacks get access to the state
+/// through a `GroupState` handle which only holds a weak reference, so the
+/// hooks don't keep the state alive and no reference cycles are created.
+///
+/// # Example
+///
+/// ```no_run
+/// # use std::time::Duration;
+/// # use weechat::{Weechat, Args, ReturnCode};
+/// # use weechat::buffer::Buffer;
+/// # use weechat::hooks::{
+/// #     Command, CommandSettings, HookGroup, RemainingCalls, SignalData, SignalHook, TimerHook,
+/// # };
+/// #[derive(Default)]
+/// struct State {
+///     commands: u32,
+///     ticks: u32,
+///     buffer_switches: u32,
+/// }
+///
+/// let mut group = HookGroup::new(State::default());
+///
+/// let state = group.state();
+/// group.add(
+///     Command::new(
+///         CommandSettings::new("counter"),
+///         move |_: &Weechat, _: &Buffer, _: Args| {
+///             state.with(|s| s.commands += 1);
+///         },
+///     )
+///     .expect("Can't create command"),
+/// );
+///
+/// let state = group.state();
+/// group.add(
+///     TimerHook::new(Duration::from_secs(1), 0, 0, move |_: &Weechat, _: RemainingCalls| {
+///         state.with(|s| s.ticks += 1);
+///     })
+///     .expect("Can't create timer"),
+/// );
+///
+/// let state = group.state();
+/// group.add(
+///     SignalHook::new(
+///         "buffer_switch",
+///         move |_: &Weechat, _: &str, _: Option<SignalData>| {
+///             state.with(|s| s.buffer_switches += 1);
+///             ReturnCode::Ok
+///         },
+///     )
+///     .expect("Can't create signal hook"),
+/// );
+/// ```
+pub struct HookGroup<S> {
+    state: Rc<RefCell<S>>,
+    hooks: Vec<Box<dyn Any>>,
+}
+
+/// A handle to the state of a `HookGroup`.
+///
+/// The handle can be moved into the callbacks of the hooks that are part of
+/// the group.
+pub struct GroupState<S> {
+    state: Weak<RefCell<S>>,
+}
+
+impl<S> Clone for GroupState<S> {
+    fn clone(&self) -> Self {
+        GroupState { state: self.state.clone() }
+    }
+}
+
+impl<S> GroupState<S> {
+    /// Run a closure with mutable access to the shared state.
+    ///
+    /// Returns `None` if the group was dropped or if the state is already
+    /// borrowed, this happens if a callback triggers another callback of
+    /// the same group while it is accessing the state, e.g. by running a
+    /// command that a hook of the group listens to.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - The closure that will be called with the state.
+    pub fn with<R>(&self, f: impl FnOnce(&mut S) -> R) -> Option<R> {
+        let state = self.state.upgrade()?;
+        let mut state = state.try_borrow_mut().ok()?;
+
+        Some(f(&mut state))
+    }
+}
+
+impl<S: 'static> HookGroup<S> {
+    /// Create a new hook group.
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - The state that will be shared between the hooks of the
+    ///   group.
+    pub fn new(state: S) -> Self {
+        HookGroup { state: Rc::new(RefCell::new(state)), hooks: Vec::new() }
+    }
+
+    /// Get a new handle to the shared state.
+    ///
+    /// The handle should be moved into the callback of a hook that is added
+    /// to the group.
+    pub fn state(&self) -> GroupState<S> {
+        GroupState { state: Rc::downgrade(&self.state) }
+    }
+
+    /// Run a closure with mutable access to the shared state.
+    ///
+    /// Returns `None` if the state is already borrowed.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - The closure that will be called with the state.
+    pub fn with_state<R>(&self, f: impl FnOnce(&mut S) -> R) -> Option<R> {
+        let mut state = self.state.try_borrow_mut().ok()?;
+        Some(f(&mut state))
+    }
+
+    /// Add a hook to the group.
+    ///
+    /// The hook will be removed when the group is dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `hook` - The hook that should be added, this can be any hook type
+    ///   e.g. a `Command`, a `TimerHook` or a `SignalHook`.
+    pub fn add<H: 'static>(&mut self, hook: H) {
+        self.hooks.push(Box::new(hook));
+    }
+
+    /// Get the number of hooks in the group.
+    pub fn len(&self) -> usize {
+        self.hooks.len()
+    }
+
+    /// Does the group contain no hooks.
+    pub fn is_empty(&self) -> bool {
+        self.hooks.is_empty()
+    }
+}
+
+impl<S> Drop for HookGroup<S> {
+    fn drop(&mut self) {
+        // Remove the hooks before the state so no callback can observe a
+        // dropped state.
+        self.hooks.clear();
+    }
+}
@@ -0,0 +1,285 @@
+#[cfg(feature = "async")]
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::{
+    collections::HashMap,
+    ffi::CStr,
+    os::raw::{c_char, c_int, c_void},
+    ptr,
+};
+
+use weechat_sys::{t_hashtable, t_weechat_plugin};
+
+use super::Hook;
+use crate::{LossyCString, ReturnCode, Weechat};
+
+/// Hook for a hsignal, a signal that carries a hashtable, the hook is removed
+/// when the object is dropped.
+pub struct HsignalHook {
+    _hook: Hook,
+    _hook_data: Box<HsignalHookData>,
+}
+
+struct HsignalHookData {
+    callback: Box<dyn HsignalCallback>,
+    weechat_ptr: *mut t_weechat_plugin,
+}
+
+/// Trait for the hsignal callback.
+///
+/// A blanket implementation for pure `FnMut` functions exists, if data needs to
+/// be passed to the callback implement this over your struct.
+pub trait HsignalCallback {
+    /// Callback that will be called when a hsignal is sent.
+    ///
+    /// # Arguments
+    ///
+    /// * `weechat` - A Weechat context.
+    ///
+    /// * `signal_name` - The name of the hsignal that fired the callback.
+    ///
+    /// * `data` - The content of the hashtable that was sent with the hsignal,
+    ///   values that aren't strings are converted to strings.
+    fn callback(
+        &mut self,
+        weechat: &Weechat,
+        signal_name: &str,
+        data: HashMap<String, String>,
+    ) -> ReturnCode;
+}
+
+impl<T: FnMut(&Weechat, &str, HashMap<String, String>) -> ReturnCode + 'static> HsignalCallback
+    for T
+{
+    fn callback(
+        &mut self,
+        weechat: &Weechat,
+        signal_name: &str,
+        data: HashMap<String, String>,
+    ) -> ReturnCode {
+        self(weechat, signal_name, data)
+    }
+}
+
+impl HsignalHook {
+    /// Hook a hsignal.
+    ///
+    /// # Arguments
+    ///
+    /// * `signal_name` - The hsignal to hook (wildcard `*` is allowed).
+    ///
+    /// * `callback` - A function or a struct that implements HsignalCallback,
+    ///   the callback method of the trait will be called when the hsignal is
+    ///   sent.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use std::collections::HashMap;
+    /// # use weechat::{Weechat, ReturnCode};
+    /// # use weechat::hooks::HsignalHook;
+    /// let hook = HsignalHook::new(
+    ///     "irc_redirection_*",
+    ///     |_: &Weechat, signal_name: &str, data: HashMap<String, String>| {
+    ///         Weechat::print(&format!("{}: {:?}", signal_name, data.get("output")));
+    ///         ReturnCode::Ok
+    ///     },
+    /// )
+    /// .expect("Can't hook the hsignal");
+    /// ```
+    pub fn new(signal_name: &str, callback: impl HsignalCallback + 'static) -> Result<Self, ()> {
+        unsafe extern "C" fn c_hook_cb(
+            pointer: *const c_void,
+            _data: *mut c_void,
+            signal_name: *const c_char,
+            hashtable: *mut t_hashtable,
+        ) -> c_int {
+            let hook_data: &mut HsignalHookData = { &mut *(pointer as *mut HsignalHookData) };
+            let weechat = Weechat::from_ptr(hook_data.weechat_ptr);
+
+            let signal_name = CStr::from_ptr(signal_name).to_string_lossy();
+            let data = if hashtable.is_null() {
+                HashMap::new()
+            } else {
+                weechat.hashtable_to_map(hashtable)
+            };
+
+            hook_data.callback.callback(&weechat, &signal_name, data) as i32
+        }
+
+        Weechat::check_thread();
+        let weechat = unsafe { Weechat::weechat() };
+
+        let data =
+            Box::new(HsignalHookData { callback: Box::new(callback), weechat_ptr: weechat.ptr });
+
+        let data_ref = Box::leak(data);
+        let hook_hsignal = weechat.get().hook_hsignal.unwrap();
+
+        let signal_name = LossyCString::new(signal_name);
+
+        let hook_ptr = unsafe {
+            hook_hsignal(
+                weechat.ptr,
+                signal_name.as_ptr(),
+                Some(c_hook_cb),
+                data_ref as *const _ as *const c_void,
+                ptr::null_mut(),
+            )
+        };
+
+        let hook_data = unsafe { Box::from_raw(data_ref) };
+        let hook = Hook { ptr: hook_ptr, weechat_ptr: weechat.ptr };
+
+        if hook_ptr.is_null() {
+            Err(())
+        } else {
+            Ok(HsignalHook { _hook: hook, _hook_data: hook_data })
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+static IRC_REDIRECT_ID: AtomicUsize = AtomicUsize::new(0);
+
+impl Weechat {
+    /// Send a hsignal.
+    ///
+    /// Returns the return code of the last callback that was called, together
+    /// with the content of the hashtable after the callbacks ran, some
+    /// hsignals report errors by adding keys to the hashtable.
+    ///
+    /// # Arguments
+    ///
+    /// * `signal_name` - The name of the hsignal that should be sent out.
+    ///
+    /// * `data` - The content of the hashtable that should be sent.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    pub fn hook_hsignal_send(
+        signal_name: &str,
+        data: HashMap<&str, &str>,
+    ) -> (ReturnCode, HashMap<String, String>) {
+        Weechat::check_thread();
+        let weechat = unsafe { Weechat::weechat() };
+
+        let hsignal_send = weechat.get().hook_hsignal_send.unwrap();
+        let hashtable_free = weechat.get().hashtable_free.unwrap();
+
+        let signal_name = LossyCString::new(signal_name);
+        let hashtable = weechat.hashmap_to_weechat(data);
+
+        let (ret, data) = unsafe {
+            let ret = hsignal_send(signal_name.as_ptr(), hashtable);
+            let data = weechat.hashtable_to_map(hashtable);
+            hashtable_free(hashtable);
+
+            (ret, data)
+        };
+
+        let ret = match ret {
+            weechat_sys::WEECHAT_RC_OK => ReturnCode::Ok,
+            weechat_sys::WEECHAT_RC_OK_EAT => ReturnCode::OkEat,
+            _ => ReturnCode::Error,
+        };
+
+        (ret, data)
+    }
+
+    /// Send a command to an IRC server and capture the reply of the server.
+    ///
+    /// This uses the redirection of the IRC plugin, the lines the server sends
+    /// as a reply are captured and not displayed. The reply is returned as
+    /// raw IRC messages separated by newlines.
+    ///
+    /// Only commands that the IRC plugin has a redirect pattern for can be
+    /// captured, e.g. `whois`, `whowas`, `who`, `names`, `list` or
+    /// `userhost`. The pattern is picked from the name of the command.
+    ///
+    /// Returns an error if the IRC plugin isn't loaded, the server is unknown,
+    /// the command has no redirect pattern or if the server didn't reply
+    /// before the timeout of the pattern ran out.
+    ///
+    /// # Arguments
+    ///
+    /// * `server` - The name of the IRC server the command should be sent
+    ///   to.
+    ///
+    /// * `command` - The command that should be sent, e.g. `/whois alice`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use weechat::Weechat;
+    /// # async fn whois() -> Result<(), ()> {
+    /// let whois = Weechat::irc_command_capture("libera", "/whois alice").await?;
+    ///
+    /// for line in whois.lines() {
+    ///     Weechat::print(line);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "async")]
+    #[cfg_attr(feature = "docs", doc(cfg(r#async)))]
+    pub async fn irc_command_capture(server: &str, command: &str) -> Result<String, ()> {
+        let pattern =
+            command.trim_start_matches('/').split_whitespace().next().ok_or(())?.to_lowercase();
+
+        let signal = {
+            let weechat = unsafe { Weechat::weechat() };
+
+            // The server buffer only exists if the IRC plugin is loaded and
+            // knows the server.
+            weechat.buffer_search("irc", &format!("server.{}", server)).ok_or(())?;
+
+            let id = IRC_REDIRECT_ID.fetch_add(1, Ordering::Relaxed);
+            format!("{}_capture_{}", weechat.own_plugin_name(), id)
+        };
+
+        let (sender, receiver) = futures::channel::oneshot::channel();
+        let mut sender = Some(sender);
+
+        let hook = HsignalHook::new(
+            &format!("irc_redirection_{}_{}", signal, pattern),
+            move |_: &Weechat, _: &str, mut data: HashMap<String, String>| {
+                if let Some(sender) = sender.take() {
+                    let error = data.remove("error").unwrap_or_default();
+                    let output = data.remove("output").unwrap_or_default();
+                    let _ = sender.send(if error.is_empty() { Ok(output) } else { Err(()) });
+                }
+                ReturnCode::Ok
+            },
+        )?;
+
+        let mut redirect = HashMap::new();
+        redirect.insert("server", server);
+        redirect.insert("pattern", pattern.as_str());
+        redirect.insert("signal", signal.as_str());
+
+        let (_, redirect) = Weechat::hook_hsignal_send("irc_redirect_command", redirect);
+
+        // The IRC plugin adds an error to the hashtable if the redirect can't
+        // be created.
+        if matches!(redirect.get("error"), Some(e) if !e.is_empty()) {
+            return Err(());
+        }
+
+        let command = format!("/{}", command.trim_start_matches('/'));
+        Weechat::hook_signal_send("irc_input_send", format!("{};;2;;{}", server, command));
+
+        let ret = receiver.await.map_err(|_| ())?;
+        drop(hook);
+
+        ret
+    }
+}
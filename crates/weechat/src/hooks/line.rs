@@ -0,0 +1,387 @@
+use std::{collections::HashMap, os::raw::c_void, ptr};
+
+use weechat_sys::{t_gui_buffer, t_hashtable, t_weechat_plugin};
+
+use super::Hook;
+use crate::{buffer::Buffer, LossyCString, Weechat};
+
+/// Hook for lines that are about to be added to a buffer, the hook is removed
+/// when the object is dropped.
+///
+/// Unlike a `PrintHook` the line hook is called before the line is displayed
+/// and can modify or drop the line.
+pub struct LineHook {
+    _hook: Hook,
+    _hook_data: Box<LineHookData>,
+}
+
+struct LineHookData {
+    callback: Box<dyn LineCallback>,
+    weechat_ptr: *mut t_weechat_plugin,
+}
+
+/// The type of buffers whose lines should be caught by a `LineHook`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineBufferType {
+    /// Buffers with formatted content, e.g. IRC channel buffers.
+    Formatted,
+    /// Buffers with free content.
+    Free,
+    /// All buffers.
+    All,
+}
+
+impl LineBufferType {
+    fn as_str(&self) -> &'static str {
+        match self {
+            LineBufferType::Formatted => "formatted",
+            LineBufferType::Free => "free",
+            LineBufferType::All => "*",
+        }
+    }
+}
+
+/// A line that is about to be added to a buffer.
+///
+/// See the Weechat `hook_line()` documentation for a list of the fields of a
+/// line.
+pub struct Line<'a> {
+    weechat: &'a Weechat,
+    fields: HashMap<String, String>,
+}
+
+impl<'a> Line<'a> {
+    /// Get the raw value of a field of the line.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The name of the field, e.g. `prefix` or `message`.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.fields.get(key).map(|v| v.as_str())
+    }
+
+    /// Get the buffer the line will be added to.
+    pub fn buffer(&self) -> Option<Buffer<'a>> {
+        let value = self.get("buffer")?.trim_start_matches("0x");
+        let ptr = usize::from_str_radix(value, 16).ok().filter(|p| *p != 0)?;
+
+        Some(self.weechat.buffer_from_ptr(ptr as *mut t_gui_buffer))
+    }
+
+    /// Get the full name of the buffer the line will be added to.
+    pub fn buffer_name(&self) -> &str {
+        self.get("buffer_name").unwrap_or_default()
+    }
+
+    /// Get the type of the buffer the line will be added to.
+    pub fn buffer_type(&self) -> LineBufferType {
+        match self.get("buffer_type") {
+            Some("free") => LineBufferType::Free,
+            _ => LineBufferType::Formatted,
+        }
+    }
+
+    /// Get the line number, only set for buffers with free content.
+    pub fn y(&self) -> Option<i32> {
+        self.get("y").and_then(|y| y.parse().ok()).filter(|y| *y >= 0)
+    }
+
+    /// Get the date of the line.
+    pub fn date(&self) -> i64 {
+        self.get("date").and_then(|d| d.parse().ok()).unwrap_or_default()
+    }
+
+    /// Get the tags of the line.
+    pub fn tags(&self) -> Vec<&str> {
+        self.get("tags")
+            .map(|t| t.split(',').filter(|t| !t.is_empty()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Get the prefix of the line.
+    pub fn prefix(&self) -> &str {
+        self.get("prefix").unwrap_or_default()
+    }
+
+    /// Get the message of the line.
+    pub fn message(&self) -> &str {
+        self.get("message").unwrap_or_default()
+    }
+}
+
+/// Changes that should be applied to a line caught by a `LineHook`.
+///
+/// The modifications are converted into the map that a `LineCallback`
+/// returns.
+#[derive(Debug, Clone, Default)]
+pub struct LineModifications {
+    changes: HashMap<String, String>,
+}
+
+impl LineModifications {
+    /// Create an empty set of modifications, leaving the line as it is.
+    pub fn new() -> Self {
+        LineModifications::default()
+    }
+
+    /// Change a raw field of the line.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The name of the field, e.g. `prefix` or `message`.
+    ///
+    /// * `value` - The new value of the field.
+    pub fn set<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.changes.insert(key.into(), value.into());
+        self
+    }
+
+    /// Add the line to another buffer.
+    ///
+    /// # Arguments
+    ///
+    /// * `buffer` - The buffer the line should be added to.
+    pub fn set_buffer(self, buffer: &Buffer) -> Self {
+        let ptr = format!("{:p}", buffer.ptr());
+        self.set("buffer", ptr)
+    }
+
+    /// Add the line to another buffer, searched by its full name.
+    ///
+    /// If no buffer with the given name exists the line stays in the buffer
+    /// it was meant for.
+    ///
+    /// # Arguments
+    ///
+    /// * `full_name` - The full name of the buffer the line should be added
+    ///   to, e.g. `irc.libera.#weechat`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use weechat::Weechat;
+    /// # use weechat::hooks::{Line, LineBufferType, LineHook, LineModifications};
+    /// let hook = LineHook::new(
+    ///     LineBufferType::Formatted,
+    ///     "irc.*",
+    ///     &["irc_notice"],
+    ///     |_: &Weechat, _: &Line| {
+    ///         LineModifications::new().set_buffer_by_name("myplugin.notices").into()
+    ///     },
+    /// )
+    /// .expect("Can't hook lines");
+    /// ```
+    pub fn set_buffer_by_name(self, full_name: &str) -> Self {
+        Weechat::check_thread();
+        let weechat = unsafe { Weechat::weechat() };
+
+        match weechat.buffer_search("==", full_name) {
+            Some(buffer) => self.set_buffer(&buffer),
+            None => self,
+        }
+    }
+}
+
+impl From<LineModifications> for HashMap<String, String> {
+    fn from(modifications: LineModifications) -> Self {
+        modifications.changes
+    }
+}
+
+/// Trait for the line callback.
+///
+/// A blanket implementation for pure `FnMut` functions exists, if data needs to
+/// be passed to the callback implement this over your struct.
+pub trait LineCallback {
+    /// Callback that will be called when a line is about to be added to a
+    /// buffer.
+    ///
+    /// Returns the fields of the line that should be changed, e.g. `message`
+    /// to rewrite the message. An empty map leaves the line as it is, setting
+    /// `buffer` to an empty string drops the line.
+    ///
+    /// # Arguments
+    ///
+    /// * `weechat` - A Weechat context.
+    ///
+    /// * `line` - The line that is about to be added.
+    fn callback(&mut self, weechat: &Weechat, line: &Line) -> HashMap<String, String>;
+}
+
+impl<T: FnMut(&Weechat, &Line) -> HashMap<String, String> + 'static> LineCallback for T {
+    fn callback(&mut self, weechat: &Weechat, line: &Line) -> HashMap<String, String> {
+        self(weechat, line)
+    }
+}
+
+impl LineHook {
+    /// Hook lines that are about to be added to a buffer.
+    ///
+    /// The filters are applied by Weechat, the callback is only called for
+    /// matching lines.
+    ///
+    /// # Arguments
+    ///
+    /// * `buffer_type` - The type of buffers whose lines should be caught.
+    ///
+    /// * `buffer_name` - A comma separated list of buffer masks, e.g.
+    ///   `irc.libera.*`, an empty string catches lines of all buffers.
+    ///
+    /// * `tags` - Only catch lines that have these tags, each element may
+    ///   contain multiple tags separated by `+` that must all be present. An
+    ///   empty slice catches all lines.
+    ///
+    /// * `callback` - A function or a struct that implements LineCallback,
+    ///   the callback method of the trait will be called when a matching line
+    ///   is about to be added.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use std::collections::HashMap;
+    /// # use weechat::Weechat;
+    /// # use weechat::hooks::{Line, LineBufferType, LineHook};
+    /// let hook = LineHook::new(
+    ///     LineBufferType::Formatted,
+    ///     "irc.*",
+    ///     &["irc_privmsg"],
+    ///     |_: &Weechat, line: &Line| {
+    ///         let mut changes = HashMap::new();
+    ///
+    ///         if line.message().contains(":)") {
+    ///             changes.insert("message".to_owned(), line.message().replace(":)", "🙂"));
+    ///         }
+    ///
+    ///         changes
+    ///     },
+    /// )
+    /// .expect("Can't hook lines");
+    /// ```
+    pub fn new(
+        buffer_type: LineBufferType,
+        buffer_name: &str,
+        tags: &[&str],
+        callback: impl LineCallback + 'static,
+    ) -> Result<Self, ()> {
+        unsafe extern "C" fn c_hook_cb(
+            pointer: *const c_void,
+            _data: *mut c_void,
+            line: *mut t_hashtable,
+        ) -> *mut t_hashtable {
+            let hook_data: &mut LineHookData = { &mut *(pointer as *mut LineHookData) };
+            let weechat = Weechat::from_ptr(hook_data.weechat_ptr);
+
+            let line = Line { weechat: &weechat, fields: weechat.hashtable_to_map(line) };
+            let changes = hook_data.callback.callback(&weechat, &line);
+
+            if changes.is_empty() {
+                return ptr::null_mut();
+            }
+
+            // Weechat frees the returned hashtable once the changes are
+            // applied.
+            weechat
+                .hashmap_to_weechat(changes.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect())
+        }
+
+        Weechat::check_thread();
+        let weechat = unsafe { Weechat::weechat() };
+
+        let data =
+            Box::new(LineHookData { callback: Box::new(callback), weechat_ptr: weechat.ptr });
+
+        let data_ref = Box::leak(data);
+        let hook_line = weechat.get().hook_line.unwrap();
+
+        let buffer_type = LossyCString::new(buffer_type.as_str());
+        let buffer_name = LossyCString::new(buffer_name);
+        let tags = LossyCString::new(tags.join(","));
+
+        let hook_ptr = unsafe {
+            hook_line(
+                weechat.ptr,
+                buffer_type.as_ptr(),
+                buffer_name.as_ptr(),
+                tags.as_ptr(),
+                Some(c_hook_cb),
+                data_ref as *const _ as *const c_void,
+                ptr::null_mut(),
+            )
+        };
+
+        let hook_data = unsafe { Box::from_raw(data_ref) };
+        let hook = Hook { ptr: hook_ptr, weechat_ptr: weechat.ptr };
+
+        if hook_ptr.is_null() {
+            Err(())
+        } else {
+            Ok(LineHook { _hook: hook, _hook_data: hook_data })
+        }
+    }
+
+    /// Hide lines that match a predicate.
+    ///
+    /// Matching lines are dropped before they are added to the buffer, they
+    /// aren't displayed, don't trigger any `PrintHook` and aren't written to
+    /// the log by the logger plugin either.
+    ///
+    /// If the lines should still end up in the log, use `LineHook::new()` to
+    /// add a tag to them instead and hide the tag with a Weechat filter, e.g.
+    /// `/filter add spam * myplugin_spam *`. Filtered lines are logged unless
+    /// they also carry the `no_log` tag.
+    ///
+    /// # Arguments
+    ///
+    /// * `buffer_type` - The type of buffers whose lines should be caught.
+    ///
+    /// * `buffer_name` - A comma separated list of buffer masks, an empty
+    ///   string catches lines of all buffers.
+    ///
+    /// * `tags` - Only catch lines that have these tags, an empty slice
+    ///   catches all lines.
+    ///
+    /// * `predicate` - A function that returns true if the line should be
+    ///   hidden.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use weechat::Weechat;
+    /// # use weechat::hooks::{Line, LineBufferType, LineHook};
+    /// let hook = LineHook::filter(
+    ///     LineBufferType::Formatted,
+    ///     "irc.*",
+    ///     &["irc_privmsg"],
+    ///     |_: &Weechat, line: &Line| line.message().contains("free crypto"),
+    /// )
+    /// .expect("Can't hook lines");
+    /// ```
+    pub fn filter(
+        buffer_type: LineBufferType,
+        buffer_name: &str,
+        tags: &[&str],
+        mut predicate: impl FnMut(&Weechat, &Line) -> bool + 'static,
+    ) -> Result<Self, ()> {
+        LineHook::new(buffer_type, buffer_name, tags, move |weechat: &Weechat, line: &Line| {
+            let mut changes = HashMap::new();
+
+            if predicate(weechat, line) {
+                changes.insert("buffer".to_owned(), "".to_owned());
+            }
+
+            changes
+        })
+    }
+}
@@ -8,19 +8,50 @@ mod signal;
 mod bar;
 mod commands;
 mod completion;
+mod config;
+mod connect;
 mod fd;
+mod focus;
+mod group;
+mod hsignal;
+mod line;
 #[cfg(feature = "unsound")]
 mod modifier;
+mod print;
+mod process;
 mod timer;
 
-pub use bar::{BarItem, BarItemCallback};
-pub use commands::{Command, CommandCallback, CommandRun, CommandRunCallback, CommandSettings};
-pub use completion::{Completion, CompletionCallback, CompletionHook, CompletionPosition};
+pub use bar::{
+    Animator, Bar, BarItem, BarItemCallback, BarPosition, BarSettings, BarType, DynBarItemCallback,
+};
+pub(crate) use commands::CommandRunGuard;
+pub use commands::{
+    Command, CommandCallback, CommandRun, CommandRunCallback, CommandRunContext, CommandSettings,
+    Subcommand, SubcommandArgument, WeechatCommand,
+};
+pub use completion::{
+    Completion, CompletionCallback, CompletionError, CompletionHook, CompletionPosition,
+    CoreCompletion,
+};
+pub use config::{ConfigCallback, ConfigHook, FromConfig};
+pub use connect::{ConnectCallback, ConnectHook, ConnectSettings, ConnectStatus};
 pub use fd::{FdHook, FdHookCallback, FdHookMode};
+pub use focus::{FocusCallback, FocusHook, FocusInfo};
+pub use group::{GroupState, HookGroup};
+pub use hsignal::{HsignalCallback, HsignalHook};
+pub use line::{Line, LineBufferType, LineCallback, LineHook, LineModifications};
 #[cfg(feature = "unsound")]
 pub use modifier::{ModifierCallback, ModifierData, ModifierHook};
-pub use signal::{SignalCallback, SignalData, SignalHook};
-pub use timer::{RemainingCalls, TimerCallback, TimerHook};
+pub use print::{PrintCallback, PrintHook, PrintedLine};
+pub use process::{ProcessCallback, ProcessHook, ProcessStatus};
+#[cfg(feature = "message_bus")]
+pub use signal::MessageBus;
+pub use signal::{LocalvarHook, SignalCallback, SignalData, SignalHook, TypedSignal};
+pub use timer::{
+    Backoff, BackoffCallback, BackoffSettings, RemainingCalls, Scheduler, TaskId, TimerCallback,
+    TimerHook, TimerSequence, YieldingTask,
+};
+pub use weechat_macro::WeechatCommand;
 use weechat_sys::{t_hook, t_weechat_plugin};
 
 use crate::Weechat;
@@ -34,6 +65,10 @@ pub(crate) struct Hook {
 
 impl Drop for Hook {
     fn drop(&mut self) {
+        if self.ptr.is_null() {
+            return;
+        }
+
         let weechat = Weechat::from_ptr(self.weechat_ptr);
         let unhook = weechat.get().unhook.unwrap();
         unsafe { unhook(self.ptr) };
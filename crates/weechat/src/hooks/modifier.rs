@@ -225,4 +225,120 @@ impl ModifierHook {
             Ok(Self { _hook: hook, _hook_data: hook_data })
         }
     }
+
+    /// Hook a modifier with the given priority.
+    ///
+    /// If multiple hooks exist for a modifier, hooks with a higher priority
+    /// are called first and each hook receives the string that the previous
+    /// one returned. The default priority of a hook is 1000.
+    ///
+    /// Messages of the IRC plugin are passed through the `irc_color_decode`
+    /// modifier before they are printed, a `weechat_print` modifier always
+    /// sees Weechat color codes instead of IRC ones regardless of its
+    /// priority. Use `Weechat::exec_modifier_list()` to run a custom chain of
+    /// modifiers in a fixed order.
+    ///
+    /// Like the rest of `ModifierHook` this is only available if the
+    /// `unsound` feature of the crate is enabled,
+    /// `Weechat::exec_modifier_list()` is always available.
+    ///
+    /// # Arguments
+    ///
+    /// * `priority` - The priority of the hook.
+    ///
+    /// * `modifier_name` - The modifier to hook.
+    ///
+    /// * `callback` - A function or a struct that implements ModifierCallback,
+    ///   the callback method of the trait will be called when the modifier is
+    ///   fired.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use std::borrow::Cow;
+    /// # use weechat::Weechat;
+    /// # use weechat::hooks::{ModifierData, ModifierHook};
+    /// // Run after the hooks with the default priority.
+    /// let hook = ModifierHook::with_priority(
+    ///     500,
+    ///     "weechat_print",
+    ///     |_: &Weechat, _: &str, _: Option<ModifierData>, string: Cow<str>| {
+    ///         Some(string.replace("teh", "the"))
+    ///     },
+    /// );
+    /// ```
+    #[cfg_attr(feature = "docs", doc(cfg(unsound)))]
+    pub fn with_priority(
+        priority: i32,
+        modifier_name: &str,
+        callback: impl ModifierCallback + 'static,
+    ) -> Result<Self, ()> {
+        ModifierHook::new(&format!("{}|{}", priority, modifier_name), callback)
+    }
+
+    /// Hook the display of the input line to add text after the input, e.g.
+    /// to show a suggestion for the rest of the input.
+    ///
+    /// This hooks the `input_text_display_with_cursor` modifier. The input is
+    /// split at the cursor, the cursor marker and the input itself are kept
+    /// as they are and the returned text is displayed after the input. The
+    /// text is only displayed, it doesn't become part of the input.
+    ///
+    /// Like the rest of `ModifierHook` this is only available if the
+    /// `unsound` feature of the crate is enabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `callback` - A function that will be called with the buffer of the
+    ///   input, the input text before the cursor and the input text after the
+    ///   cursor. It should return the text that should be displayed after the
+    ///   input or `None` to leave the input unchanged.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use weechat::Weechat;
+    /// # use weechat::buffer::Buffer;
+    /// # use weechat::hooks::ModifierHook;
+    /// let hook = ModifierHook::on_input_display(
+    ///     |_: &Weechat, _: &Buffer, before: &str, after: &str| {
+    ///         if after.is_empty() && "/buffer".starts_with(before) && before.starts_with('/') {
+    ///             Some(format!("{}{}", Weechat::color("darkgray"), &"/buffer"[before.len()..]))
+    ///         } else {
+    ///             None
+    ///         }
+    ///     },
+    /// );
+    /// ```
+    #[cfg_attr(feature = "docs", doc(cfg(unsound)))]
+    pub fn on_input_display(
+        mut callback: impl FnMut(&Weechat, &Buffer, &str, &str) -> Option<String> + 'static,
+    ) -> Result<Self, ()> {
+        ModifierHook::new(
+            "input_text_display_with_cursor",
+            move |weechat: &Weechat, _: &str, data: Option<ModifierData>, string: Cow<str>| {
+                let buffer = match data? {
+                    ModifierData::Buffer(buffer) => buffer,
+                    ModifierData::String(_) => return None,
+                };
+
+                // Weechat marks the cursor position with the color code that
+                // moves the cursor, it needs to stay in the displayed string.
+                let cursor = Weechat::color("bar_move");
+                let (before, after) = string.split_once(cursor)?;
+
+                let text = callback(weechat, &buffer, before, after)?;
+
+                Some(format!("{}{}{}{}", before, cursor, after, text))
+            },
+        )
+    }
 }
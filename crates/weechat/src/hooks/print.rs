@@ -0,0 +1,376 @@
+#[cfg(feature = "async")]
+use std::time::Duration;
+use std::{
+    borrow::Cow,
+    cell::RefCell,
+    ffi::CStr,
+    os::raw::{c_char, c_int, c_void},
+    ptr,
+    rc::Rc,
+};
+
+use once_cell::unsync::OnceCell;
+use weechat_sys::{t_gui_buffer, t_weechat_plugin, time_t};
+
+use super::Hook;
+#[cfg(feature = "async")]
+use crate::{
+    buffer::BufferHandle,
+    hooks::{RemainingCalls, TimerHook},
+};
+use crate::{
+    buffer::{Buffer, Tags},
+    LossyCString, ReturnCode, Weechat,
+};
+
+/// Hook for printed lines, the hook is removed when the object is dropped.
+pub struct PrintHook {
+    _hook: Hook,
+    _hook_data: Box<PrintHookData>,
+}
+
+struct PrintHookData {
+    callback: Box<dyn PrintCallback>,
+    weechat_ptr: *mut t_weechat_plugin,
+    strip_colors: bool,
+}
+
+/// A line that was printed on a buffer.
+pub struct PrintedLine<'a> {
+    date: i64,
+    tags: Tags<'a>,
+    displayed: bool,
+    highlight: bool,
+    prefix: Cow<'a, str>,
+    message: Cow<'a, str>,
+    colors_stripped: bool,
+    stripped: OnceCell<String>,
+}
+
+impl<'a> PrintedLine<'a> {
+    /// Get the date of the line.
+    pub fn date(&self) -> i64 {
+        self.date
+    }
+
+    /// Get the tags of the line.
+    pub fn tags(&self) -> &Tags<'a> {
+        &self.tags
+    }
+
+    /// Is the line displayed, lines can be hidden by filters.
+    pub fn displayed(&self) -> bool {
+        self.displayed
+    }
+
+    /// Is the line highlighted.
+    pub fn highlighted(&self) -> bool {
+        self.highlight
+    }
+
+    /// Get the prefix of the line.
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// Get the message of the line.
+    ///
+    /// The message contains the raw Weechat color codes unless the hook was
+    /// created with `strip_colors` set, use `PrintedLine::stripped()` to get
+    /// the message without them.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Get the message of the line with the Weechat color codes removed.
+    ///
+    /// The stripped message is computed on the first call and cached for
+    /// subsequent calls. If the hook was created with `strip_colors` set the
+    /// message already is stripped by Weechat and is returned as is.
+    pub fn stripped(&self) -> &str {
+        if self.colors_stripped {
+            return &self.message;
+        }
+
+        self.stripped.get_or_init(|| Weechat::remove_color(&self.message))
+    }
+}
+
+/// Trait for the print callback.
+///
+/// A blanket implementation for pure `FnMut` functions exists, if data needs to
+/// be passed to the callback implement this over your struct.
+pub trait PrintCallback {
+    /// Callback that will be called when a line is printed.
+    ///
+    /// # Arguments
+    ///
+    /// * `weechat` - A Weechat context.
+    ///
+    /// * `buffer` - The buffer the line was printed on.
+    ///
+    /// * `line` - The line that was printed.
+    ///
+    /// The line already is displayed when the callback is called, returning
+    /// `ReturnCode::OkEat` won't hide it. Use `LineHook::filter()` to hide
+    /// lines before they are displayed.
+    fn callback(&mut self, weechat: &Weechat, buffer: &Buffer, line: PrintedLine) -> ReturnCode;
+}
+
+impl<T: FnMut(&Weechat, &Buffer, PrintedLine) -> ReturnCode + 'static> PrintCallback for T {
+    fn callback(&mut self, weechat: &Weechat, buffer: &Buffer, line: PrintedLine) -> ReturnCode {
+        self(weechat, buffer, line)
+    }
+}
+
+impl PrintHook {
+    /// Hook the printing of lines.
+    ///
+    /// # Arguments
+    ///
+    /// * `buffer` - The buffer whose lines should be caught, if `None` lines
+    ///   printed on any buffer are caught.
+    ///
+    /// * `tags` - Only catch lines that have these tags, each element may
+    ///   contain multiple tags separated by `+` that must all be present. An
+    ///   empty slice catches all lines.
+    ///
+    /// * `message` - Only catch lines containing this string, an empty string
+    ///   catches all lines.
+    ///
+    /// * `strip_colors` - Should Weechat remove the color codes from the
+    ///   message before the callback is called. This is cheaper than
+    ///   stripping the colors in the callback, but the raw message isn't
+    ///   available anymore.
+    ///
+    /// * `callback` - A function or a struct that implements PrintCallback,
+    ///   the callback method of the trait will be called when a line is
+    ///   printed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use weechat::{Weechat, ReturnCode};
+    /// # use weechat::buffer::Buffer;
+    /// # use weechat::hooks::{PrintHook, PrintedLine};
+    /// let print_hook = PrintHook::new(
+    ///     None,
+    ///     &["irc_privmsg"],
+    ///     "",
+    ///     true,
+    ///     |_: &Weechat, buffer: &Buffer, line: PrintedLine| {
+    ///         Weechat::print(&format!("{}: {}", buffer.name(), line.message()));
+    ///         ReturnCode::Ok
+    ///     },
+    /// );
+    /// ```
+    pub fn new(
+        buffer: Option<&Buffer>,
+        tags: &[&str],
+        message: &str,
+        strip_colors: bool,
+        callback: impl PrintCallback + 'static,
+    ) -> Result<Self, ()> {
+        unsafe extern "C" fn c_hook_cb(
+            pointer: *const c_void,
+            _data: *mut c_void,
+            buffer: *mut t_gui_buffer,
+            date: time_t,
+            tags_count: c_int,
+            tags: *mut *const c_char,
+            displayed: c_int,
+            highlight: c_int,
+            prefix: *const c_char,
+            message: *const c_char,
+        ) -> c_int {
+            let hook_data: &mut PrintHookData = { &mut *(pointer as *mut PrintHookData) };
+            let cb = &mut hook_data.callback;
+
+            let weechat = Weechat::from_ptr(hook_data.weechat_ptr);
+            let buffer = weechat.buffer_from_ptr(buffer);
+
+            let tags = (0..tags_count as isize)
+                .map(|i| CStr::from_ptr(*tags.offset(i)).to_string_lossy())
+                .collect::<Vec<_>>()
+                .into();
+
+            let to_cow = |string: *const c_char| {
+                if string.is_null() {
+                    Cow::from("")
+                } else {
+                    CStr::from_ptr(string).to_string_lossy()
+                }
+            };
+
+            let line = PrintedLine {
+                date,
+                tags,
+                displayed: displayed != 0,
+                highlight: highlight != 0,
+                prefix: to_cow(prefix),
+                message: to_cow(message),
+                colors_stripped: hook_data.strip_colors,
+                stripped: OnceCell::new(),
+            };
+
+            cb.callback(&weechat, &buffer, line) as i32
+        }
+
+        Weechat::check_thread();
+        let weechat = unsafe { Weechat::weechat() };
+
+        let data = Box::new(PrintHookData {
+            callback: Box::new(callback),
+            weechat_ptr: weechat.ptr,
+            strip_colors,
+        });
+
+        let data_ref = Box::leak(data);
+        let hook_print = weechat.get().hook_print.unwrap();
+
+        let buffer_ptr = buffer.map(|b| b.ptr()).unwrap_or(ptr::null_mut());
+        let tags = LossyCString::new(tags.join(","));
+        let message = LossyCString::new(message);
+
+        let hook_ptr = unsafe {
+            hook_print(
+                weechat.ptr,
+                buffer_ptr,
+                tags.as_ptr(),
+                message.as_ptr(),
+                strip_colors as c_int,
+                Some(c_hook_cb),
+                data_ref as *const _ as *const c_void,
+                ptr::null_mut(),
+            )
+        };
+
+        let hook_data = unsafe { Box::from_raw(data_ref) };
+        let hook = Hook { ptr: hook_ptr, weechat_ptr: weechat.ptr };
+
+        if hook_ptr.is_null() {
+            Err(())
+        } else {
+            Ok(PrintHook { _hook: hook, _hook_data: hook_data })
+        }
+    }
+}
+
+impl Weechat {
+    fn capture_hook(buffer: &Buffer, lines: Rc<RefCell<Vec<String>>>) -> Result<PrintHook, ()> {
+        PrintHook::new(
+            Some(buffer),
+            &[],
+            "",
+            false,
+            move |_: &Weechat, _: &Buffer, line: PrintedLine| {
+                lines.borrow_mut().push(line.message().to_owned());
+                ReturnCode::Ok
+            },
+        )
+    }
+
+    /// Run a command and capture the lines it prints on the given buffer.
+    ///
+    /// Returns the printed messages joined with newlines, the messages keep
+    /// their color codes, use `Weechat::remove_color()` to get plain text.
+    ///
+    /// Returns an error if the print hook that captures the lines can't be
+    /// created or if Weechat reports that the command failed, e.g. because
+    /// the command doesn't exist. The output of a failed command usually
+    /// only consists of an error message, so it isn't returned.
+    ///
+    /// Only lines that are printed while the command is running are captured.
+    /// Commands that print their output asynchronously, for example IRC
+    /// commands that wait for a server reply, will not be captured, use
+    /// `Weechat::command_capture_async()` for those.
+    ///
+    /// # Arguments
+    ///
+    /// * `buffer` - The buffer the command should run on.
+    ///
+    /// * `command` - The command that should run.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use weechat::Weechat;
+    /// # let weechat = unsafe { Weechat::weechat() };
+    /// let buffer = weechat.core_buffer();
+    /// let help = weechat.command_capture(&buffer, "/help buffer")
+    ///     .expect("Can't run the help command");
+    /// ```
+    pub fn command_capture(&self, buffer: &Buffer, command: &str) -> Result<String, ()> {
+        let lines = Rc::new(RefCell::new(Vec::new()));
+
+        let hook = Weechat::capture_hook(buffer, lines.clone())?;
+        let ret = buffer.run_command(command);
+        drop(hook);
+
+        ret.map(|_| lines.borrow().join("\n"))
+    }
+
+    /// Run a command and capture the lines it prints on the given buffer
+    /// during the given duration.
+    ///
+    /// This is the async version of `Weechat::command_capture()`, it keeps
+    /// capturing lines until `duration` has passed, so output that is printed
+    /// asynchronously is captured as well.
+    ///
+    /// Returns an error if the buffer was closed, if the hooks can't be
+    /// created or if the command failed.
+    ///
+    /// # Arguments
+    ///
+    /// * `buffer` - The buffer the command should run on.
+    ///
+    /// * `command` - The command that should run.
+    ///
+    /// * `duration` - For how long lines should be captured after the command
+    ///   was run.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    #[cfg(feature = "async")]
+    #[cfg_attr(feature = "docs", doc(cfg(r#async)))]
+    pub async fn command_capture_async(
+        buffer: BufferHandle,
+        command: &str,
+        duration: Duration,
+    ) -> Result<String, ()> {
+        let lines = Rc::new(RefCell::new(Vec::new()));
+
+        let hook = {
+            let buffer = buffer.upgrade()?;
+            let hook = Weechat::capture_hook(&buffer, lines.clone())?;
+            buffer.run_command(command)?;
+            hook
+        };
+
+        let (sender, receiver) = futures::channel::oneshot::channel();
+        let mut sender = Some(sender);
+
+        let timer = TimerHook::new(duration, 0, 1, move |_: &Weechat, _: RemainingCalls| {
+            if let Some(sender) = sender.take() {
+                let _ = sender.send(());
+            }
+        })?;
+
+        let _ = receiver.await;
+
+        drop(timer);
+        drop(hook);
+
+        let lines = lines.borrow().join("\n");
+        Ok(lines)
+    }
+}
@@ -0,0 +1,549 @@
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    ffi::{CStr, CString},
+    os::raw::{c_char, c_int, c_void},
+    ptr,
+    time::{Duration, Instant},
+};
+
+use weechat_sys::{t_weechat_plugin, WEECHAT_RC_OK};
+
+use super::Hook;
+use crate::{LossyCString, Weechat};
+
+// The return codes of a process callback that aren't exit codes, these are
+// preprocessor defines that aren't part of the generated bindings.
+const WEECHAT_HOOK_PROCESS_RUNNING: c_int = -1;
+const WEECHAT_HOOK_PROCESS_ERROR: c_int = -2;
+
+/// The state of a process when its callback is called.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessStatus {
+    /// The process is still running, more output will follow.
+    Running,
+    /// The process finished with the given exit code.
+    Finished(i32),
+    /// The process couldn't be started or failed.
+    Error,
+    /// The process was killed because it ran longer than its timeout.
+    Timeout,
+}
+
+impl From<c_int> for ProcessStatus {
+    fn from(return_code: c_int) -> Self {
+        match return_code {
+            WEECHAT_HOOK_PROCESS_RUNNING => ProcessStatus::Running,
+            WEECHAT_HOOK_PROCESS_ERROR => ProcessStatus::Error,
+            c if c >= 0 => ProcessStatus::Finished(c),
+            _ => ProcessStatus::Error,
+        }
+    }
+}
+
+/// Trait for the process callback.
+///
+/// A blanket implementation for pure `FnMut` functions exists, if data needs to
+/// be passed to the callback implement this over your struct.
+pub trait ProcessCallback {
+    /// Callback that will be called when the process produced output or
+    /// finished.
+    ///
+    /// # Arguments
+    ///
+    /// * `weechat` - A Weechat context.
+    ///
+    /// * `status` - The state of the process.
+    ///
+    /// * `stdout` - The output of the process since the last call, if any.
+    ///
+    /// * `stderr` - The error output of the process since the last call, if
+    ///   any.
+    fn callback(
+        &mut self,
+        weechat: &Weechat,
+        status: ProcessStatus,
+        stdout: Option<Cow<str>>,
+        stderr: Option<Cow<str>>,
+    );
+}
+
+impl<T: FnMut(&Weechat, ProcessStatus, Option<Cow<str>>, Option<Cow<str>>) + 'static>
+    ProcessCallback for T
+{
+    fn callback(
+        &mut self,
+        weechat: &Weechat,
+        status: ProcessStatus,
+        stdout: Option<Cow<str>>,
+        stderr: Option<Cow<str>>,
+    ) {
+        self(weechat, status, stdout, stderr)
+    }
+}
+
+struct ProcessHookData {
+    callback: Box<dyn ProcessCallback>,
+    weechat_ptr: *mut t_weechat_plugin,
+    finished: bool,
+    started: Instant,
+    timeout: Option<Duration>,
+}
+
+/// Hook for a child process, the process is killed if the object is dropped
+/// before it finished.
+pub struct ProcessHook {
+    _hook: Hook,
+    _hook_data: Box<ProcessHookData>,
+}
+
+impl Drop for ProcessHook {
+    fn drop(&mut self) {
+        // Weechat removes the hook on its own once the process finished.
+        if self._hook_data.finished {
+            self._hook.ptr = ptr::null_mut();
+        }
+    }
+}
+
+impl ProcessHook {
+    /// Run a command in a child process without blocking Weechat.
+    ///
+    /// # Arguments
+    ///
+    /// * `command` - The command that should be run, the command is run using
+    ///   `sh -c`, a command starting with `url:` downloads the URL instead.
+    ///
+    /// * `timeout` - The time after which the process is killed, `None` lets
+    ///   the process run until it finishes. The callback is called with the
+    ///   `ProcessStatus::Timeout` status if the process is killed.
+    ///
+    /// * `callback` - A function or a struct that implements ProcessCallback,
+    ///   the callback method of the trait will be called when the process
+    ///   produced output and when it finished.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use std::borrow::Cow;
+    /// # use weechat::Weechat;
+    /// # use weechat::hooks::{ProcessHook, ProcessStatus};
+    /// let hook = ProcessHook::new(
+    ///     "uname -a",
+    ///     None,
+    ///     |_: &Weechat, status: ProcessStatus, stdout: Option<Cow<str>>, _: Option<Cow<str>>| {
+    ///         if let Some(stdout) = stdout {
+    ///             Weechat::print(&stdout);
+    ///         }
+    ///
+    ///         if let ProcessStatus::Finished(code) = status {
+    ///             Weechat::print(&format!("Finished with code {}", code));
+    ///         }
+    ///     },
+    /// )
+    /// .expect("Can't start the process");
+    /// ```
+    pub fn new(
+        command: &str,
+        timeout: Option<Duration>,
+        callback: impl ProcessCallback + 'static,
+    ) -> Result<ProcessHook, ()> {
+        ProcessHook::new_helper(command, None, timeout, Box::new(callback))
+    }
+
+    /// Run a command in a child process with additional options.
+    ///
+    /// # Arguments
+    ///
+    /// * `command` - The command that should be run.
+    ///
+    /// * `options` - Options for the process, e.g. `env_GIT_DIR` to set an
+    ///   environment variable, `stdin` set to `1` to create a pipe for the
+    ///   standard input of the process, which only takes text (see
+    ///   `ProcessHook::write_stdin()`), or `buffer_flush` to control how
+    ///   often the callback is called. See the Weechat documentation of
+    ///   `hook_process_hashtable()` for a full list of options.
+    ///
+    /// * `timeout` - The time after which the process is killed, `None` lets
+    ///   the process run until it finishes.
+    ///
+    /// * `callback` - A function or a struct that implements ProcessCallback,
+    ///   the callback method of the trait will be called when the process
+    ///   produced output and when it finished.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use std::borrow::Cow;
+    /// # use std::collections::HashMap;
+    /// # use weechat::Weechat;
+    /// # use weechat::hooks::{ProcessHook, ProcessStatus};
+    /// let mut options = HashMap::new();
+    /// options.insert("env_GIT_DIR", "/home/user/notes/.git");
+    /// options.insert("stdin", "1");
+    ///
+    /// let hook = ProcessHook::with_options(
+    ///     "git hash-object --stdin",
+    ///     options,
+    ///     None,
+    ///     |_: &Weechat, _: ProcessStatus, stdout: Option<Cow<str>>, _: Option<Cow<str>>| {
+    ///         if let Some(stdout) = stdout {
+    ///             Weechat::print(&stdout);
+    ///         }
+    ///     },
+    /// )
+    /// .expect("Can't start the process");
+    ///
+    /// hook.write_stdin("Some content").expect("Can't write to the process");
+    /// hook.close_stdin();
+    /// ```
+    pub fn with_options(
+        command: &str,
+        options: HashMap<&str, &str>,
+        timeout: Option<Duration>,
+        callback: impl ProcessCallback + 'static,
+    ) -> Result<ProcessHook, ()> {
+        ProcessHook::new_helper(command, Some(options), timeout, Box::new(callback))
+    }
+
+    /// Run a command that prints `key=value` lines and parse its output.
+    ///
+    /// The standard output of the process is collected until the process
+    /// finished, the callback is then called once with the parsed values. The
+    /// key and the value are split at the first `=` and surrounding whitespace
+    /// is removed, if a key appears multiple times the last value wins. Empty
+    /// lines are skipped, the standard error of the process is ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `command` - The command that should be run.
+    ///
+    /// * `timeout` - The time after which the process is killed, `None` lets
+    ///   the process run until it finishes.
+    ///
+    /// * `collect_malformed` - Should lines without a `=` be passed to the
+    ///   callback, if not they are ignored.
+    ///
+    /// * `callback` - A function that will be called with the state of the
+    ///   process, the parsed values and the malformed lines once the process
+    ///   finished.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use std::collections::HashMap;
+    /// # use weechat::Weechat;
+    /// # use weechat::hooks::{ProcessHook, ProcessStatus};
+    /// let hook = ProcessHook::key_value(
+    ///     "~/bin/battery-status",
+    ///     None,
+    ///     false,
+    ///     |_: &Weechat, _: ProcessStatus, values: HashMap<String, String>, _: Vec<String>| {
+    ///         if let Some(level) = values.get("level") {
+    ///             Weechat::print(&format!("Battery at {}%", level));
+    ///         }
+    ///     },
+    /// )
+    /// .expect("Can't start the process");
+    /// ```
+    pub fn key_value(
+        command: &str,
+        timeout: Option<Duration>,
+        collect_malformed: bool,
+        mut callback: impl FnMut(&Weechat, ProcessStatus, HashMap<String, String>, Vec<String>)
+            + 'static,
+    ) -> Result<ProcessHook, ()> {
+        let mut output = String::new();
+
+        ProcessHook::new(
+            command,
+            timeout,
+            move |weechat: &Weechat,
+                  status: ProcessStatus,
+                  stdout: Option<Cow<str>>,
+                  _: Option<Cow<str>>| {
+                if let Some(stdout) = stdout {
+                    output.push_str(&stdout);
+                }
+
+                if status == ProcessStatus::Running {
+                    return;
+                }
+
+                let mut values = HashMap::new();
+                let mut malformed = Vec::new();
+
+                for line in output.lines().map(str::trim).filter(|l| !l.is_empty()) {
+                    match line.split_once('=') {
+                        Some((key, value)) => {
+                            values.insert(key.trim().to_owned(), value.trim().to_owned());
+                        }
+                        None if collect_malformed => malformed.push(line.to_owned()),
+                        None => (),
+                    }
+                }
+
+                output.clear();
+                callback(weechat, status, values, malformed);
+            },
+        )
+    }
+
+    fn new_helper(
+        command: &str,
+        options: Option<HashMap<&str, &str>>,
+        timeout: Option<Duration>,
+        callback: Box<dyn ProcessCallback>,
+    ) -> Result<ProcessHook, ()> {
+        unsafe extern "C" fn c_hook_cb(
+            pointer: *const c_void,
+            _data: *mut c_void,
+            _command: *const c_char,
+            return_code: c_int,
+            out: *const c_char,
+            err: *const c_char,
+        ) -> c_int {
+            let hook_data: &mut ProcessHookData = { &mut *(pointer as *mut ProcessHookData) };
+            let mut status = ProcessStatus::from(return_code);
+
+            // Weechat reports a timeout as a generic error, find out if the
+            // timeout ran out.
+            if status == ProcessStatus::Error
+                && matches!(hook_data.timeout, Some(t) if hook_data.started.elapsed() >= t)
+            {
+                status = ProcessStatus::Timeout;
+            }
+
+            if status != ProcessStatus::Running {
+                hook_data.finished = true;
+            }
+
+            let weechat = Weechat::from_ptr(hook_data.weechat_ptr);
+
+            let to_option = |string: *const c_char| {
+                if string.is_null() {
+                    None
+                } else {
+                    Some(CStr::from_ptr(string).to_string_lossy())
+                }
+            };
+
+            hook_data.callback.callback(&weechat, status, to_option(out), to_option(err));
+
+            WEECHAT_RC_OK
+        }
+
+        Weechat::check_thread();
+        let weechat = unsafe { Weechat::weechat() };
+
+        let data = Box::new(ProcessHookData {
+            callback,
+            weechat_ptr: weechat.ptr,
+            finished: false,
+            started: Instant::now(),
+            timeout,
+        });
+
+        let data_ref = Box::leak(data);
+
+        let command = LossyCString::new(command);
+        let timeout = timeout.map_or(0, |t| t.as_millis().min(c_int::MAX as u128) as c_int);
+
+        let hook_ptr = if let Some(options) = options {
+            let hook_process_hashtable = weechat.get().hook_process_hashtable.unwrap();
+            let options = weechat.hashmap_to_weechat(options);
+
+            let hook_ptr = unsafe {
+                hook_process_hashtable(
+                    weechat.ptr,
+                    command.as_ptr(),
+                    options,
+                    timeout,
+                    Some(c_hook_cb),
+                    data_ref as *const _ as *const c_void,
+                    ptr::null_mut(),
+                )
+            };
+
+            unsafe { weechat.get().hashtable_free.unwrap()(options) };
+
+            hook_ptr
+        } else {
+            let hook_process = weechat.get().hook_process.unwrap();
+
+            unsafe {
+                hook_process(
+                    weechat.ptr,
+                    command.as_ptr(),
+                    timeout,
+                    Some(c_hook_cb),
+                    data_ref as *const _ as *const c_void,
+                    ptr::null_mut(),
+                )
+            }
+        };
+
+        let hook_data = unsafe { Box::from_raw(data_ref) };
+        let hook = Hook { ptr: hook_ptr, weechat_ptr: weechat.ptr };
+
+        if hook_ptr.is_null() {
+            Err(())
+        } else {
+            Ok(ProcessHook { _hook: hook, _hook_data: hook_data })
+        }
+    }
+
+    /// Has the process finished.
+    pub fn is_finished(&self) -> bool {
+        self._hook_data.finished
+    }
+
+    /// Kill the process.
+    ///
+    /// The hook is removed which makes Weechat kill the process, the callback
+    /// isn't called anymore. Does nothing if the process already finished.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use std::borrow::Cow;
+    /// # use std::time::Duration;
+    /// # use weechat::Weechat;
+    /// # use weechat::hooks::{ProcessHook, ProcessStatus};
+    /// let mut hook = ProcessHook::new(
+    ///     "curl -s https://example.org/slow",
+    ///     Some(Duration::from_secs(10)),
+    ///     |_: &Weechat, status: ProcessStatus, _: Option<Cow<str>>, _: Option<Cow<str>>| {
+    ///         if status == ProcessStatus::Timeout {
+    ///             Weechat::print("The download timed out");
+    ///         }
+    ///     },
+    /// )
+    /// .expect("Can't start the process");
+    ///
+    /// // The user cancelled the download.
+    /// hook.kill();
+    /// ```
+    pub fn kill(&mut self) {
+        if !self.is_finished() {
+            let weechat = Weechat::from_ptr(self._hook_data.weechat_ptr);
+            unsafe { weechat.get().unhook.unwrap()(self._hook.ptr) };
+
+            self._hook.ptr = ptr::null_mut();
+            self._hook_data.finished = true;
+        }
+    }
+
+    /// Write text to the standard input of the process.
+    ///
+    /// The process needs to be created using `with_options()` with the
+    /// `stdin` option set to `1`.
+    ///
+    /// Returns an error if the process already finished or if the text
+    /// contains null bytes, see below.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The text that should be written.
+    ///
+    /// # Binary data
+    ///
+    /// Weechat takes the input of a process as a C string, so only text can
+    /// be written. Text containing null bytes is rejected as a whole instead
+    /// of being truncated at the first null byte. Binary data needs to be
+    /// written to a process that is spawned using `std::process::Command`
+    /// instead. Writing the data
+    /// from a separate thread keeps large writes from blocking Weechat, an
+    /// `FdHook` picks up the output on the main thread:
+    ///
+    /// ```no_run
+    /// # use std::io::{Read, Write};
+    /// # use std::process::{ChildStdout, Command, Stdio};
+    /// # use std::thread;
+    /// # use weechat::Weechat;
+    /// # use weechat::hooks::{FdHook, FdHookCallback, FdHookMode};
+    /// struct OutputReader;
+    ///
+    /// impl FdHookCallback for OutputReader {
+    ///     type FdObject = ChildStdout;
+    ///
+    ///     fn callback(&mut self, _: &Weechat, stdout: &mut ChildStdout) {
+    ///         let mut buffer = [0; 4096];
+    ///
+    ///         match stdout.read(&mut buffer) {
+    ///             // The process closed its output, the hook should be dropped.
+    ///             Ok(0) | Err(_) => (),
+    ///             Ok(n) => Weechat::print(&String::from_utf8_lossy(&buffer[..n])),
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let payload = vec![0u8, 159, 146, 150];
+    ///
+    /// let mut child = Command::new("sha256sum")
+    ///     .stdin(Stdio::piped())
+    ///     .stdout(Stdio::piped())
+    ///     .spawn()
+    ///     .expect("Can't start the process");
+    ///
+    /// let mut stdin = child.stdin.take().expect("The process has no stdin pipe");
+    /// let stdout = child.stdout.take().expect("The process has no stdout pipe");
+    ///
+    /// // The pipe is closed once the thread finished, ending the input.
+    /// thread::spawn(move || stdin.write_all(&payload));
+    ///
+    /// let hook = FdHook::new(stdout, FdHookMode::Read, OutputReader)
+    ///     .expect("Can't hook the output of the process");
+    /// ```
+    pub fn write_stdin(&self, text: &str) -> Result<(), ()> {
+        if self.is_finished() {
+            return Err(());
+        }
+
+        let text = CString::new(text).map_err(|_| ())?;
+        self.hook_set("stdin", text.as_ptr());
+
+        Ok(())
+    }
+
+    /// Close the standard input of the process, signaling the end of the
+    /// input to the process.
+    pub fn close_stdin(&self) {
+        if !self.is_finished() {
+            self.hook_set("stdin_close", b"1\0".as_ptr() as *const c_char);
+        }
+    }
+
+    /// Send a signal to the process.
+    ///
+    /// # Arguments
+    ///
+    /// * `signal` - The name or the number of the signal, e.g. `term` or `9`.
+    pub fn signal(&self, signal: &str) {
+        if !self.is_finished() {
+            let signal = LossyCString::new(signal);
+            self.hook_set("signal", signal.as_ptr());
+        }
+    }
+
+    fn hook_set(&self, property: &str, value: *const c_char) {
+        let weechat = Weechat::from_ptr(self._hook_data.weechat_ptr);
+        let hook_set = weechat.get().hook_set.unwrap();
+
+        let property = LossyCString::new(property);
+
+        unsafe { hook_set(self._hook.ptr, property.as_ptr(), value) };
+    }
+}
@@ -1,6 +1,7 @@
 use std::{
     borrow::Cow,
-    cell::Cell,
+    cell::{Cell, RefCell},
+    collections::HashMap,
     ffi::CStr,
     os::raw::{c_char, c_int, c_void},
     ptr,
@@ -155,6 +156,76 @@ impl<'a> SignalData<'a> {
     }
 }
 
+/// A decoded signal with a typed payload.
+///
+/// Known signals are decoded into a variant carrying the payload with its
+/// concrete type, unknown signals or signals carrying unexpected data fall
+/// back to the generic `SignalData`.
+#[non_exhaustive]
+pub enum TypedSignal<'a> {
+    /// The current buffer was switched, carries the new current buffer.
+    BufferSwitch(Buffer<'a>),
+    /// A buffer was opened.
+    BufferOpened(Buffer<'a>),
+    /// A buffer is being closed.
+    BufferClosing(Buffer<'a>),
+    /// The title of a buffer changed.
+    BufferTitleChanged(Buffer<'a>),
+    /// A buffer was renamed.
+    BufferRenamed(Buffer<'a>),
+    /// The content of the input bar of a buffer changed.
+    InputTextChanged(Buffer<'a>),
+    /// The IRC plugin opened a channel buffer.
+    IrcChannelOpened(Buffer<'a>),
+    /// The IRC plugin opened a private buffer.
+    IrcPvOpened(Buffer<'a>),
+    /// The IRC plugin is connecting to a server, carries the server name.
+    IrcServerConnecting(Cow<'a, str>),
+    /// The IRC plugin connected to a server, carries the server name.
+    IrcServerConnected(Cow<'a, str>),
+    /// The IRC plugin disconnected from a server, carries the server name.
+    IrcServerDisconnected(Cow<'a, str>),
+    /// The system date changed, carries the new date.
+    DayChanged(Cow<'a, str>),
+    /// A signal that has no typed representation.
+    Other(Option<SignalData<'a>>),
+}
+
+impl<'a> TypedSignal<'a> {
+    /// Decode the data of a signal.
+    ///
+    /// # Arguments
+    ///
+    /// * `signal_name` - The name of the signal that was fired.
+    ///
+    /// * `data` - The data that was passed on by the signal.
+    pub fn new(signal_name: &str, data: Option<SignalData<'a>>) -> TypedSignal<'a> {
+        match (signal_name, data) {
+            ("buffer_switch", Some(SignalData::Buffer(b))) => TypedSignal::BufferSwitch(b),
+            ("buffer_opened", Some(SignalData::Buffer(b))) => TypedSignal::BufferOpened(b),
+            ("buffer_closing", Some(SignalData::Buffer(b))) => TypedSignal::BufferClosing(b),
+            ("buffer_title_changed", Some(SignalData::Buffer(b))) => {
+                TypedSignal::BufferTitleChanged(b)
+            }
+            ("buffer_renamed", Some(SignalData::Buffer(b))) => TypedSignal::BufferRenamed(b),
+            ("input_text_changed", Some(SignalData::Buffer(b))) => TypedSignal::InputTextChanged(b),
+            ("irc_channel_opened", Some(SignalData::Buffer(b))) => TypedSignal::IrcChannelOpened(b),
+            ("irc_pv_opened", Some(SignalData::Buffer(b))) => TypedSignal::IrcPvOpened(b),
+            ("irc_server_connecting", Some(SignalData::String(s))) => {
+                TypedSignal::IrcServerConnecting(s)
+            }
+            ("irc_server_connected", Some(SignalData::String(s))) => {
+                TypedSignal::IrcServerConnected(s)
+            }
+            ("irc_server_disconnected", Some(SignalData::String(s))) => {
+                TypedSignal::IrcServerDisconnected(s)
+            }
+            ("day_changed", Some(SignalData::String(s))) => TypedSignal::DayChanged(s),
+            (_, data) => TypedSignal::Other(data),
+        }
+    }
+}
+
 /// Trait for the signal callback.
 ///
 /// A blanket implementation for pure `FnMut` functions exists, if data needs to
@@ -276,6 +347,295 @@ impl SignalHook {
             Ok(SignalHook { _hook: hook, _hook_data: hook_data })
         }
     }
+
+    /// Hook a signal and decode its data into a `TypedSignal`.
+    ///
+    /// # Arguments
+    ///
+    /// * `signal_name` - The signal to hook (wildcard `*` is allowed).
+    ///
+    /// * `callback` - A function that will be called with the signal name and
+    ///   the decoded signal when the signal is fired.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use weechat::{Weechat, ReturnCode};
+    /// # use weechat::hooks::{SignalHook, TypedSignal};
+    /// let signal_hook = SignalHook::new_typed(
+    ///     "buffer_switch",
+    ///     |_: &Weechat, _: &str, signal: TypedSignal| {
+    ///         if let TypedSignal::BufferSwitch(buffer) = signal {
+    ///             buffer.print("Switched buffer");
+    ///         }
+    ///
+    ///         ReturnCode::Ok
+    ///     },
+    /// );
+    /// ```
+    pub fn new_typed(
+        signal_name: &str,
+        mut callback: impl FnMut(&Weechat, &str, TypedSignal) -> ReturnCode + 'static,
+    ) -> Result<Self, ()> {
+        SignalHook::new(
+            signal_name,
+            move |weechat: &Weechat, signal_name: &str, data: Option<SignalData>| {
+                callback(weechat, signal_name, TypedSignal::new(signal_name, data))
+            },
+        )
+    }
+
+    /// Hook the `input_text_changed` signal, sent when the content of the
+    /// input bar changes.
+    ///
+    /// The signal is sent for every key press, expensive work should be
+    /// delayed until the user stops typing, e.g. by replacing a pending one
+    /// shot `TimerHook` every time the callback is called.
+    ///
+    /// # Arguments
+    ///
+    /// * `callback` - A function that will be called with the buffer whose
+    ///   input changed and the new content of the input.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use weechat::Weechat;
+    /// # use weechat::buffer::Buffer;
+    /// # use weechat::hooks::SignalHook;
+    /// let input_hook = SignalHook::on_input_changed(|_: &Weechat, _: &Buffer, input: &str| {
+    ///     if input.starts_with("/color ") {
+    ///         Weechat::bar_item_update("color_preview");
+    ///     }
+    /// });
+    /// ```
+    pub fn on_input_changed(
+        mut callback: impl FnMut(&Weechat, &Buffer, &str) + 'static,
+    ) -> Result<Self, ()> {
+        SignalHook::new(
+            "input_text_changed",
+            move |weechat: &Weechat, _: &str, data: Option<SignalData>| {
+                if let Some(SignalData::Buffer(buffer)) = data {
+                    callback(weechat, &buffer, &buffer.input());
+                }
+
+                ReturnCode::Ok
+            },
+        )
+    }
+
+    /// Hook the `quit` signal, sent when Weechat is about to quit.
+    ///
+    /// The callback runs while Weechat is shutting down, so it should finish
+    /// quickly, e.g. by writing a small state file.
+    ///
+    /// # Arguments
+    ///
+    /// * `callback` - A function that will be called when Weechat quits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use weechat::Weechat;
+    /// # use weechat::hooks::SignalHook;
+    /// let quit_hook = SignalHook::on_quit(|_: &Weechat| {
+    ///     std::fs::write("/tmp/my_plugin_state", "state").ok();
+    /// });
+    /// ```
+    pub fn on_quit(mut callback: impl FnMut(&Weechat) + 'static) -> Result<Self, ()> {
+        SignalHook::new("quit", move |weechat: &Weechat, _: &str, _: Option<SignalData>| {
+            callback(weechat);
+            ReturnCode::Ok
+        })
+    }
+
+    /// Hook the `day_changed` signal, sent once when the system date changes
+    /// at local midnight.
+    ///
+    /// # Arguments
+    ///
+    /// * `callback` - A function that will be called with the new date,
+    ///   formatted as `YYYY-MM-DD`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use weechat::Weechat;
+    /// # use weechat::hooks::SignalHook;
+    /// let day_hook = SignalHook::on_day_changed(|_: &Weechat, _: &str| {
+    ///     Weechat::bar_item_update("date");
+    /// });
+    /// ```
+    pub fn on_day_changed(mut callback: impl FnMut(&Weechat, &str) + 'static) -> Result<Self, ()> {
+        SignalHook::new(
+            "day_changed",
+            move |weechat: &Weechat, _: &str, data: Option<SignalData>| {
+                if let Some(SignalData::String(date)) = data {
+                    callback(weechat, &date);
+                }
+                ReturnCode::Ok
+            },
+        )
+    }
+
+    /// Hook the `upgrade` signal, sent when the user runs the `/upgrade`
+    /// command.
+    ///
+    /// The callback runs while Weechat is shutting down, so it should finish
+    /// quickly.
+    ///
+    /// # Arguments
+    ///
+    /// * `callback` - A function that will be called when Weechat upgrades,
+    ///   the boolean argument is true if the upgrade was started with
+    ///   `/upgrade -quit`, meaning that Weechat quits instead of restarting.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    pub fn on_upgrade(mut callback: impl FnMut(&Weechat, bool) + 'static) -> Result<Self, ()> {
+        SignalHook::new("upgrade", move |weechat: &Weechat, _: &str, data: Option<SignalData>| {
+            let quit = matches!(data, Some(SignalData::String(ref s)) if s == "quit");
+            callback(weechat, quit);
+            ReturnCode::Ok
+        })
+    }
+}
+
+// The local variables of every buffer, keyed by the buffer pointer.
+type LocalvarSnapshot = HashMap<usize, HashMap<String, String>>;
+
+/// Hook for changes of the local variables of buffers, the hook is removed
+/// when the object is dropped.
+///
+/// Weechat only sends the buffer with the `buffer_localvar_added`,
+/// `buffer_localvar_changed` and `buffer_localvar_removed` signals, the hook
+/// keeps a copy of the local variables of every buffer to find out which
+/// variable changed. The copy of a buffer is taken when the buffer is opened
+/// and removed once it's closed.
+pub struct LocalvarHook {
+    _localvar_hook: SignalHook,
+    _opened_hook: SignalHook,
+    _closed_hook: SignalHook,
+}
+
+impl LocalvarHook {
+    /// Hook changes of the local variables of buffers.
+    ///
+    /// # Arguments
+    ///
+    /// * `callback` - A function that will be called with the buffer, the
+    ///   name of the local variable that changed and its new value, the value
+    ///   is `None` if the variable was removed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use weechat::Weechat;
+    /// # use weechat::buffer::Buffer;
+    /// # use weechat::hooks::LocalvarHook;
+    /// let hook = LocalvarHook::new(|_: &Weechat, _: &Buffer, name: &str, _: Option<&str>| {
+    ///     if name == "away" {
+    ///         Weechat::bar_item_update("away_status");
+    ///     }
+    /// })
+    /// .expect("Can't hook local variable changes");
+    /// ```
+    pub fn new(
+        mut callback: impl FnMut(&Weechat, &Buffer, &str, Option<&str>) + 'static,
+    ) -> Result<Self, ()> {
+        Weechat::check_thread();
+        let weechat = unsafe { Weechat::weechat() };
+
+        let snapshot: LocalvarSnapshot = weechat
+            .buffers()
+            .map(|buffer| (buffer.ptr() as usize, buffer.localvars().collect()))
+            .collect();
+        let snapshot = Rc::new(RefCell::new(snapshot));
+
+        let localvars = snapshot.clone();
+        let localvar_hook = SignalHook::new(
+            "buffer_localvar_*",
+            move |weechat: &Weechat, _: &str, data: Option<SignalData>| {
+                let buffer = match data {
+                    Some(SignalData::Buffer(buffer)) => buffer,
+                    _ => return ReturnCode::Ok,
+                };
+
+                let current: HashMap<String, String> = buffer.localvars().collect();
+                let previous = localvars
+                    .borrow_mut()
+                    .insert(buffer.ptr() as usize, current.clone())
+                    .unwrap_or_default();
+
+                for (name, value) in &current {
+                    if previous.get(name) != Some(value) {
+                        callback(weechat, &buffer, name, Some(value));
+                    }
+                }
+
+                for name in previous.keys().filter(|name| !current.contains_key(*name)) {
+                    callback(weechat, &buffer, name, None);
+                }
+
+                ReturnCode::Ok
+            },
+        )?;
+
+        // Remember the local variables of new buffers, otherwise the first
+        // change would report all of their variables.
+        let localvars = snapshot.clone();
+        let opened_hook = SignalHook::new(
+            "buffer_opened",
+            move |_: &Weechat, _: &str, data: Option<SignalData>| {
+                if let Some(SignalData::Buffer(buffer)) = data {
+                    localvars
+                        .borrow_mut()
+                        .insert(buffer.ptr() as usize, buffer.localvars().collect());
+                }
+
+                ReturnCode::Ok
+            },
+        )?;
+
+        // Forget closed buffers, a new buffer might reuse the pointer.
+        let closed_hook = SignalHook::new(
+            "buffer_closed",
+            move |_: &Weechat, _: &str, data: Option<SignalData>| {
+                if let Some(SignalData::Buffer(buffer)) = data {
+                    snapshot.borrow_mut().remove(&(buffer.ptr() as usize));
+                }
+
+                ReturnCode::Ok
+            },
+        )?;
+
+        Ok(LocalvarHook {
+            _localvar_hook: localvar_hook,
+            _opened_hook: opened_hook,
+            _closed_hook: closed_hook,
+        })
+    }
 }
 
 impl Weechat {
@@ -345,3 +705,120 @@ impl Weechat {
         }
     }
 }
+
+/// A namespaced bus to exchange typed messages between plugins.
+///
+/// Messages are encoded as JSON and sent as Weechat signals. Signals are
+/// global, so every topic is prefixed with the namespace of the bus, plugins
+/// that want to talk to each other need to use the same namespace.
+///
+/// # Example
+///
+/// ```no_run
+/// # use serde::{Deserialize, Serialize};
+/// # use weechat::Weechat;
+/// # use weechat::hooks::MessageBus;
+/// #[derive(Serialize, Deserialize)]
+/// struct Status {
+///     connected: bool,
+/// }
+///
+/// let bus = MessageBus::new("myplugins").expect("Invalid namespace");
+///
+/// let hook = bus
+///     .on("status", |_: &Weechat, status: Status| {
+///         Weechat::print(if status.connected { "Connected" } else { "Disconnected" });
+///     })
+///     .expect("Can't hook the topic");
+///
+/// bus.emit("status", &Status { connected: true }).expect("Can't encode the message");
+/// ```
+#[cfg(feature = "message_bus")]
+#[cfg_attr(feature = "docs", doc(cfg(message_bus)))]
+#[derive(Debug, Clone)]
+pub struct MessageBus {
+    namespace: String,
+}
+
+#[cfg(feature = "message_bus")]
+impl MessageBus {
+    /// Create a message bus for the given namespace.
+    ///
+    /// Returns an error if the namespace is empty.
+    ///
+    /// # Arguments
+    ///
+    /// * `namespace` - A name that is unique to the cooperating plugins, e.g.
+    ///   `myplugins`.
+    pub fn new(namespace: &str) -> Result<Self, ()> {
+        if namespace.is_empty() {
+            Err(())
+        } else {
+            Ok(MessageBus { namespace: namespace.to_owned() })
+        }
+    }
+
+    /// Get the namespace of the bus.
+    pub fn namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    fn signal_name(&self, topic: &str) -> String {
+        format!("{}.{}", self.namespace, topic)
+    }
+
+    /// Receive typed messages that were sent to a topic of this bus.
+    ///
+    /// Messages that can't be decoded into `T` are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `topic` - The topic that the messages are sent to.
+    ///
+    /// * `callback` - A function that will be called with every decoded
+    ///   message.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    pub fn on<T: serde::de::DeserializeOwned>(
+        &self,
+        topic: &str,
+        mut callback: impl FnMut(&Weechat, T) + 'static,
+    ) -> Result<SignalHook, ()> {
+        SignalHook::new(
+            &self.signal_name(topic),
+            move |weechat: &Weechat, _: &str, data: Option<SignalData>| {
+                if let Some(SignalData::String(payload)) = data {
+                    if let Ok(message) = serde_json::from_str(&payload) {
+                        callback(weechat, message);
+                    }
+                }
+
+                ReturnCode::Ok
+            },
+        )
+    }
+
+    /// Send a typed message to a topic of this bus.
+    ///
+    /// Every hook that was created with `MessageBus::on()` for the same
+    /// namespace and topic receives the message.
+    ///
+    /// Returns an error if the message can't be encoded.
+    ///
+    /// # Arguments
+    ///
+    /// * `topic` - The topic the message should be sent to.
+    ///
+    /// * `message` - The message that should be sent.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    pub fn emit<T: serde::Serialize>(&self, topic: &str, message: &T) -> Result<ReturnCode, ()> {
+        let payload = serde_json::to_string(message).map_err(|_| ())?;
+
+        Ok(Weechat::hook_signal_send(&self.signal_name(topic), payload))
+    }
+}
@@ -1,15 +1,26 @@
-use std::{os::raw::c_void, ptr, time::Duration};
+use std::{
+    cell::RefCell,
+    mem::ManuallyDrop,
+    os::raw::c_void,
+    ptr,
+    rc::{Rc, Weak},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
 
 use libc::c_int;
-use weechat_sys::{t_weechat_plugin, WEECHAT_RC_OK};
+use weechat_sys::{t_hook, t_weechat_plugin, WEECHAT_RC_OK};
 
 use super::Hook;
 use crate::Weechat;
 
 /// A hook for a timer, the hook will be removed when the object is dropped.
+///
+/// The timer may be dropped from within its own callback.
 pub struct TimerHook {
     _hook: Hook,
-    _hook_data: Box<TimerHookData>,
+    // Freed when the timer is dropped, or once the callback returns if the
+    // timer is dropped from within its own callback.
+    _hook_data: ManuallyDrop<Box<TimerHookData>>,
 }
 
 /// Enum representing how many calls a timer still has.
@@ -53,6 +64,108 @@ impl<T: FnMut(&Weechat, RemainingCalls) + 'static> TimerCallback for T {
 struct TimerHookData {
     callback: Box<dyn TimerCallback>,
     weechat_ptr: *mut t_weechat_plugin,
+    finished: bool,
+    fires: u64,
+    remaining: i32,
+    interval: Duration,
+    align_second: i32,
+    // The currently registered Weechat hook, null if the timer is paused or
+    // finished.
+    hook_ptr: *mut t_hook,
+    // The time at which the registered hook is expected to fire next.
+    next_fire: Instant,
+    // Is the registered hook a one-shot hook that fires after the delay that
+    // was left when the timer was paused.
+    catch_up: bool,
+    paused_remaining: Option<Duration>,
+    running: bool,
+    dropped: bool,
+}
+
+impl Drop for TimerHook {
+    fn drop(&mut self) {
+        // Weechat removes timers on its own after their last call, and the
+        // hook might have been removed by pause(), only unhook the currently
+        // registered one.
+        self._hook.ptr = self._hook_data.hook_ptr;
+
+        if self._hook_data.running {
+            self._hook_data.dropped = true;
+        } else {
+            unsafe { ManuallyDrop::drop(&mut self._hook_data) };
+        }
+    }
+}
+
+unsafe extern "C" fn c_hook_cb(
+    pointer: *const c_void,
+    _data: *mut c_void,
+    remaining: i32,
+) -> c_int {
+    let hook_data_ptr = pointer as *mut TimerHookData;
+    let hook_data: &mut TimerHookData = { &mut *hook_data_ptr };
+
+    let remaining = if hook_data.catch_up {
+        // The one-shot hook is removed by Weechat after this call, continue
+        // with a normal timer for the calls that are left.
+        hook_data.catch_up = false;
+        hook_data.hook_ptr = ptr::null_mut();
+
+        let remaining = if hook_data.remaining > 0 { hook_data.remaining - 1 } else { -1 };
+
+        if remaining != 0 {
+            hook_data.hook(hook_data.interval, hook_data.align_second, remaining.max(0));
+        }
+
+        remaining
+    } else {
+        hook_data.next_fire = Instant::now() + hook_data.interval;
+        remaining
+    };
+
+    hook_data.finished = remaining == 0;
+    hook_data.fires += 1;
+    hook_data.remaining = remaining;
+
+    if hook_data.finished {
+        hook_data.hook_ptr = ptr::null_mut();
+    }
+
+    hook_data.running = true;
+
+    let cb = &mut hook_data.callback;
+    cb.callback(&Weechat::from_ptr(hook_data.weechat_ptr), RemainingCalls::from(remaining));
+
+    let hook_data: &mut TimerHookData = { &mut *hook_data_ptr };
+    hook_data.running = false;
+
+    if hook_data.dropped {
+        drop(Box::from_raw(hook_data_ptr));
+    }
+
+    WEECHAT_RC_OK
+}
+
+impl TimerHookData {
+    fn hook(&mut self, interval: Duration, align_second: i32, max_calls: i32) -> *mut t_hook {
+        let weechat = Weechat::from_ptr(self.weechat_ptr);
+        let hook_timer = weechat.get().hook_timer.unwrap();
+
+        self.next_fire = Instant::now() + interval;
+        self.hook_ptr = unsafe {
+            hook_timer(
+                weechat.ptr,
+                interval.as_millis() as i64,
+                align_second,
+                max_calls,
+                Some(c_hook_cb),
+                self as *const _ as *const c_void,
+                ptr::null_mut(),
+            )
+        };
+
+        self.hook_ptr
+    }
 }
 
 impl TimerHook {
@@ -95,48 +208,879 @@ impl TimerHook {
         max_calls: i32,
         callback: impl TimerCallback + 'static,
     ) -> Result<TimerHook, ()> {
-        unsafe extern "C" fn c_hook_cb(
-            pointer: *const c_void,
-            _data: *mut c_void,
-            remaining: i32,
-        ) -> c_int {
-            let hook_data: &mut TimerHookData = { &mut *(pointer as *mut TimerHookData) };
-            let cb = &mut hook_data.callback;
-
-            cb.callback(&Weechat::from_ptr(hook_data.weechat_ptr), RemainingCalls::from(remaining));
-
-            WEECHAT_RC_OK
-        }
-
         Weechat::check_thread();
         let weechat = unsafe { Weechat::weechat() };
 
-        let data =
-            Box::new(TimerHookData { callback: Box::new(callback), weechat_ptr: weechat.ptr });
+        let mut hook_data = Box::new(TimerHookData {
+            callback: Box::new(callback),
+            weechat_ptr: weechat.ptr,
+            finished: false,
+            fires: 0,
+            remaining: if max_calls > 0 { max_calls } else { -1 },
+            interval,
+            align_second,
+            hook_ptr: ptr::null_mut(),
+            next_fire: Instant::now(),
+            catch_up: false,
+            paused_remaining: None,
+            running: false,
+            dropped: false,
+        });
 
-        let data_ref = Box::leak(data);
-        let hook_timer = weechat.get().hook_timer.unwrap();
-
-        let hook_ptr = unsafe {
-            hook_timer(
-                weechat.ptr,
-                interval.as_millis() as i64,
-                align_second,
-                max_calls,
-                Some(c_hook_cb),
-                data_ref as *const _ as *const c_void,
-                ptr::null_mut(),
-            )
-        };
-        let hook_data = unsafe { Box::from_raw(data_ref) };
+        let hook_ptr = hook_data.hook(interval, align_second, max_calls);
 
         if hook_ptr.is_null() {
             Err(())
         } else {
             Ok(TimerHook {
                 _hook: Hook { ptr: hook_ptr, weechat_ptr: weechat.ptr },
-                _hook_data: hook_data,
+                _hook_data: ManuallyDrop::new(hook_data),
             })
         }
     }
+
+    /// Run a callback once after a delay, the callback decides if and when
+    /// it runs again.
+    ///
+    /// Returning `Some(delay)` from the callback schedules the next call after
+    /// the given delay, returning `None` ends the sequence. Every call is
+    /// driven by a new one-shot timer, the next timer is only created after
+    /// the callback returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `delay` - The delay before the first call.
+    ///
+    /// * `callback` - A function that will be called when the timer fires.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use weechat::Weechat;
+    /// # use weechat::hooks::TimerHook;
+    /// let mut step = 0;
+    ///
+    /// let sequence = TimerHook::once_then(Duration::from_secs(1), move |_: &Weechat| {
+    ///     step += 1;
+    ///     Weechat::print(&format!("Step {}", step));
+    ///
+    ///     if step < 3 {
+    ///         Some(Duration::from_millis(500))
+    ///     } else {
+    ///         None
+    ///     }
+    /// })
+    /// .expect("Can't create timer hook");
+    /// ```
+    pub fn once_then(
+        delay: Duration,
+        callback: impl FnMut(&Weechat) -> Option<Duration> + 'static,
+    ) -> Result<TimerSequence, ()> {
+        let state = Rc::new(RefCell::new(TimerSequenceState {
+            step: Some(Box::new(callback)),
+            cancelled: false,
+            timer: OneShot::new(),
+        }));
+
+        TimerSequence::schedule(&state, delay)?;
+
+        Ok(TimerSequence { state })
+    }
+
+    /// Get the number of times the timer has fired.
+    pub fn fires(&self) -> u64 {
+        self._hook_data.fires
+    }
+
+    /// Get the number of times the timer will still fire.
+    ///
+    /// Returns `None` if the timer fires forever.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use weechat::Weechat;
+    /// # use weechat::hooks::{TimerHook, RemainingCalls};
+    /// let timer = TimerHook::new(Duration::from_secs(1), 0, 10, |_: &Weechat, _: RemainingCalls| {})
+    ///     .expect("Can't create timer hook");
+    ///
+    /// if let Some(remaining) = timer.remaining() {
+    ///     let total = timer.fires() + remaining as u64;
+    ///     Weechat::print(&format!("{}/{} refreshes done", timer.fires(), total));
+    /// }
+    /// ```
+    pub fn remaining(&self) -> Option<u32> {
+        match RemainingCalls::from(self._hook_data.remaining) {
+            RemainingCalls::Infinite => None,
+            RemainingCalls::Finite(r) => Some(r as u32),
+        }
+    }
+
+    /// Stop the timer without removing the hook.
+    ///
+    /// The delay that was left until the next call is remembered and used
+    /// when the timer is resumed. Does nothing if the timer is already paused
+    /// or finished.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use weechat::Weechat;
+    /// # use weechat::hooks::{TimerHook, RemainingCalls};
+    /// let mut poller =
+    ///     TimerHook::new(Duration::from_secs(30), 0, 0, |_: &Weechat, _: RemainingCalls| {
+    ///         Weechat::print("Polling the backend");
+    ///     })
+    ///     .expect("Can't create timer hook");
+    ///
+    /// // The user went away, stop polling until they return.
+    /// poller.pause();
+    /// poller.resume().expect("Can't resume the poller");
+    /// ```
+    pub fn pause(&mut self) {
+        let hook_data = &mut self._hook_data;
+
+        if !hook_data.hook_ptr.is_null() {
+            let weechat = Weechat::from_ptr(hook_data.weechat_ptr);
+            unsafe { weechat.get().unhook.unwrap()(hook_data.hook_ptr) };
+
+            hook_data.hook_ptr = ptr::null_mut();
+            hook_data.catch_up = false;
+            hook_data.paused_remaining =
+                Some(hook_data.next_fire.saturating_duration_since(Instant::now()));
+        }
+    }
+
+    /// Start the timer again after it was paused.
+    ///
+    /// The next call happens after the delay that was left when the timer was
+    /// paused, the following calls use the original interval again. The
+    /// second alignment isn't applied to the first call after resuming.
+    ///
+    /// Returns an error if Weechat refused to create the timer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    pub fn resume(&mut self) -> Result<(), ()> {
+        Weechat::check_thread();
+
+        let hook_data = &mut self._hook_data;
+
+        let delay = match hook_data.paused_remaining {
+            Some(d) if hook_data.hook_ptr.is_null() && !hook_data.finished => d,
+            _ => return Ok(()),
+        };
+
+        // Weechat refuses timers with an interval of 0.
+        let delay = delay.max(Duration::from_millis(1));
+
+        if hook_data.hook(delay, 0, 1).is_null() {
+            Err(())
+        } else {
+            hook_data.catch_up = true;
+            hook_data.paused_remaining = None;
+            Ok(())
+        }
+    }
+
+    /// Is the timer currently running, i.e. neither paused nor finished.
+    pub fn is_active(&self) -> bool {
+        !self._hook_data.hook_ptr.is_null()
+    }
+}
+
+/// A one-shot timer that is stored in some shared state and calls back into
+/// the state when it fires.
+pub(crate) struct OneShot {
+    timer: Option<TimerHook>,
+}
+
+impl OneShot {
+    pub(crate) fn new() -> Self {
+        OneShot { timer: None }
+    }
+
+    /// Arm the timer, a pending timer is replaced.
+    ///
+    /// Once the timer fires it's removed from the state and `fire` is called.
+    /// The state isn't borrowed while `fire` runs, so it may arm the timer
+    /// again.
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - The state that stores the timer.
+    ///
+    /// * `timer` - Function returning the timer that is stored in the state.
+    ///
+    /// * `delay` - The delay after which the timer fires, delays shorter than
+    ///   a millisecond are treated as a millisecond.
+    ///
+    /// * `fire` - The function that will be called when the timer fires.
+    pub(crate) fn arm<S: 'static>(
+        state: &Rc<RefCell<S>>,
+        timer: fn(&mut S) -> &mut OneShot,
+        delay: Duration,
+        fire: fn(&Rc<RefCell<S>>, &Weechat),
+    ) -> Result<(), ()> {
+        let weak = Rc::downgrade(state);
+
+        // Weechat refuses timers with an interval of 0.
+        let delay = delay.max(Duration::from_millis(1));
+
+        let hook = TimerHook::new(delay, 0, 1, move |weechat: &Weechat, _: RemainingCalls| {
+            if let Some(state) = weak.upgrade() {
+                timer(&mut state.borrow_mut()).cancel();
+                fire(&state, weechat);
+            }
+        })?;
+
+        timer(&mut state.borrow_mut()).timer = Some(hook);
+
+        Ok(())
+    }
+
+    /// Remove the pending timer.
+    pub(crate) fn cancel(&mut self) {
+        self.timer = None;
+    }
+
+    /// Is the timer armed and didn't fire yet.
+    pub(crate) fn is_pending(&self) -> bool {
+        self.timer.is_some()
+    }
+}
+
+// Returns the delay until the next step, `None` ends the sequence.
+type SequenceStep = Box<dyn FnMut(&Weechat) -> Option<Duration>>;
+
+struct TimerSequenceState {
+    step: Option<SequenceStep>,
+    cancelled: bool,
+    timer: OneShot,
+}
+
+/// A chain of one-shot timers created by `TimerHook::once_then()`.
+///
+/// The pending timer is removed when the object is dropped.
+pub struct TimerSequence {
+    state: Rc<RefCell<TimerSequenceState>>,
+}
+
+impl TimerSequence {
+    fn schedule(state: &Rc<RefCell<TimerSequenceState>>, delay: Duration) -> Result<(), ()> {
+        OneShot::arm(state, |s| &mut s.timer, delay, TimerSequence::fire)
+    }
+
+    fn fire(state: &Rc<RefCell<TimerSequenceState>>, weechat: &Weechat) {
+        let step = state.borrow_mut().step.take();
+
+        let mut step = match step {
+            Some(s) => s,
+            None => return,
+        };
+
+        // The step may query or cancel the sequence.
+        let next = step(weechat);
+
+        if let Some(delay) = next.filter(|_| !state.borrow().cancelled) {
+            state.borrow_mut().step = Some(step);
+
+            if TimerSequence::schedule(state, delay).is_err() {
+                state.borrow_mut().step = None;
+            }
+        }
+    }
+
+    /// Is a step of the sequence still scheduled.
+    pub fn is_pending(&self) -> bool {
+        self.state.borrow().timer.is_pending()
+    }
+
+    /// Stop the sequence, the pending step won't run.
+    pub fn cancel(&self) {
+        let step = {
+            let mut state = self.state.borrow_mut();
+            state.cancelled = true;
+            state.timer.cancel();
+            state.step.take()
+        };
+
+        drop(step);
+    }
+}
+
+/// Trait for the backoff callback
+///
+/// A blanket implementation for pure `FnMut` functions exists, if data needs to
+/// be passed to the callback implement this over your struct.
+pub trait BackoffCallback {
+    /// Callback that will be called when a scheduled retry is due.
+    ///
+    /// # Arguments
+    ///
+    /// * `weechat` - A Weechat context.
+    ///
+    /// * `backoff` - The backoff scheduler that fired, use it to schedule
+    ///   another retry if this one failed or to reset it if it succeeded.
+    ///
+    /// * `attempt` - The number of the attempt, starting at 1.
+    fn callback(&mut self, weechat: &Weechat, backoff: &Backoff, attempt: u32);
+}
+
+impl<T: FnMut(&Weechat, &Backoff, u32) + 'static> BackoffCallback for T {
+    fn callback(&mut self, weechat: &Weechat, backoff: &Backoff, attempt: u32) {
+        self(weechat, backoff, attempt)
+    }
+}
+
+/// Settings describing the delays of a `Backoff` scheduler.
+#[derive(Debug, Clone)]
+pub struct BackoffSettings {
+    base: Duration,
+    max: Duration,
+    factor: f64,
+    jitter: f64,
+}
+
+impl BackoffSettings {
+    /// Create new backoff settings.
+    ///
+    /// The delay starts at `base` and doubles on every attempt until it
+    /// reaches `max`, no jitter is applied by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `base` - The delay before the first attempt.
+    ///
+    /// * `max` - The maximal delay between attempts.
+    pub fn new(base: Duration, max: Duration) -> Self {
+        BackoffSettings { base, max, factor: 2.0, jitter: 0.0 }
+    }
+
+    /// Set the factor the delay is multiplied with after every attempt.
+    ///
+    /// # Arguments
+    ///
+    /// * `factor` - The multiplication factor, values lower than 1 are
+    ///   treated as 1.
+    pub fn factor(mut self, factor: f64) -> Self {
+        self.factor = factor.max(1.0);
+        self
+    }
+
+    /// Set the amount of randomness that is applied to the delays.
+    ///
+    /// # Arguments
+    ///
+    /// * `jitter` - A fraction between 0 and 1, a jitter of 0.1 will randomly
+    ///   shorten or lengthen each delay by up to 10 percent.
+    pub fn jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+}
+
+struct BackoffState {
+    settings: BackoffSettings,
+    attempt: u32,
+    seed: u64,
+    timer: OneShot,
+    callback: Option<Box<dyn BackoffCallback>>,
+}
+
+impl BackoffState {
+    fn random(&mut self) -> f64 {
+        // Xorshift, good enough to spread out retries.
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 7;
+        self.seed ^= self.seed << 17;
+
+        (self.seed >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn next_delay(&mut self) -> Duration {
+        let settings = &self.settings;
+        let exponent = self.attempt.min(i32::MAX as u32) as i32;
+        let delay = (settings.base.as_secs_f64() * settings.factor.powi(exponent))
+            .min(settings.max.as_secs_f64());
+
+        let jitter = self.settings.jitter;
+        let delay = if jitter > 0.0 {
+            delay * (1.0 - jitter + 2.0 * jitter * self.random())
+        } else {
+            delay
+        };
+
+        Duration::from_secs_f64(delay)
+    }
+}
+
+/// A scheduler that retries an action with increasing delays.
+///
+/// Every call to `schedule()` arms a timer that fires the callback once, the
+/// delay grows with every attempt until the configured maximum is reached.
+/// Calling `reset()` cancels a pending retry and restarts from the base delay.
+///
+/// The pending timer is removed when the object is dropped.
+pub struct Backoff {
+    state: Rc<RefCell<BackoffState>>,
+}
+
+impl Backoff {
+    /// Create a new backoff scheduler.
+    ///
+    /// No retry is scheduled until `schedule()` is called.
+    ///
+    /// # Arguments
+    ///
+    /// * `settings` - The settings describing the delays between attempts.
+    ///
+    /// * `callback` - A function or a struct that implements BackoffCallback,
+    ///   the callback method of the trait will be called when a retry is due.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use weechat::Weechat;
+    /// # use weechat::hooks::{Backoff, BackoffSettings};
+    /// # fn connect() -> Result<(), ()> { Ok(()) }
+    /// let settings = BackoffSettings::new(Duration::from_secs(1), Duration::from_secs(300))
+    ///     .jitter(0.1);
+    ///
+    /// let backoff = Backoff::new(settings, |_: &Weechat, backoff: &Backoff, attempt: u32| {
+    ///     Weechat::print(&format!("Reconnecting, attempt {}", attempt));
+    ///
+    ///     if connect().is_ok() {
+    ///         backoff.reset();
+    ///     } else {
+    ///         backoff.schedule().expect("Can't schedule a reconnect");
+    ///     }
+    /// });
+    ///
+    /// backoff.schedule().expect("Can't schedule a reconnect");
+    /// ```
+    pub fn new(settings: BackoffSettings, callback: impl BackoffCallback + 'static) -> Self {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or_default()
+            | 1;
+
+        let state = BackoffState {
+            settings,
+            attempt: 0,
+            seed,
+            timer: OneShot::new(),
+            callback: Some(Box::new(callback)),
+        };
+
+        Backoff { state: Rc::new(RefCell::new(state)) }
+    }
+
+    /// Schedule the next attempt.
+    ///
+    /// A pending attempt is replaced by the new one. Returns the delay after
+    /// which the callback will be called.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    pub fn schedule(&self) -> Result<Duration, ()> {
+        let delay = {
+            let mut state = self.state.borrow_mut();
+            let delay = state.next_delay();
+            state.attempt = state.attempt.saturating_add(1);
+            delay
+        };
+
+        OneShot::arm(&self.state, |s| &mut s.timer, delay, Backoff::fire)?;
+
+        Ok(delay)
+    }
+
+    /// Cancel the pending attempt and restart from the base delay.
+    pub fn reset(&self) {
+        let mut state = self.state.borrow_mut();
+        state.attempt = 0;
+        state.timer.cancel();
+    }
+
+    /// Get the number of attempts that were scheduled since the last reset.
+    pub fn attempt(&self) -> u32 {
+        self.state.borrow().attempt
+    }
+
+    /// Is an attempt currently scheduled.
+    pub fn is_pending(&self) -> bool {
+        self.state.borrow().timer.is_pending()
+    }
+
+    fn fire(state: &Rc<RefCell<BackoffState>>, weechat: &Weechat) {
+        let (mut callback, attempt) = {
+            let mut state = state.borrow_mut();
+            (state.callback.take(), state.attempt)
+        };
+
+        let backoff = Backoff { state: state.clone() };
+
+        if let Some(cb) = callback.as_mut() {
+            cb.callback(weechat, &backoff, attempt);
+        }
+
+        backoff.state.borrow_mut().callback = callback;
+    }
+}
+
+/// The identifier of a task that was added to a `Scheduler`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TaskId(u64);
+
+type TaskCallback = Box<dyn FnMut(&Weechat)>;
+
+struct ScheduledTask {
+    id: TaskId,
+    interval: Duration,
+    // The scheduler time at which the task is due next.
+    due: Duration,
+    // Taken out while the task runs.
+    callback: Option<TaskCallback>,
+}
+
+struct SchedulerState {
+    tick: Duration,
+    // The time of the last tick, counted in ticks since the scheduler was
+    // created so late timer calls don't make the tasks drift.
+    now: Duration,
+    next_id: u64,
+    tasks: Vec<ScheduledTask>,
+}
+
+/// Runs many periodic tasks using a single timer.
+///
+/// Every task has its own interval, the scheduler checks on every tick which
+/// tasks are due and runs them. A task whose interval isn't a multiple of
+/// the tick runs on the first tick at or after the time it's due.
+///
+/// The timer is removed when the object is dropped.
+pub struct Scheduler {
+    state: Rc<RefCell<SchedulerState>>,
+    _timer: TimerHook,
+}
+
+impl Scheduler {
+    /// Create a new scheduler without any tasks.
+    ///
+    /// Returns an error if the tick is shorter than a millisecond or the
+    /// timer can't be created.
+    ///
+    /// # Arguments
+    ///
+    /// * `tick` - The resolution of the scheduler, the interval of the
+    ///   underlying timer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use weechat::Weechat;
+    /// # use weechat::hooks::Scheduler;
+    /// let scheduler = Scheduler::new(Duration::from_secs(1)).expect("Can't create scheduler");
+    ///
+    /// for minutes in 1..=30 {
+    ///     scheduler.add(Duration::from_secs(60 * minutes), move |_: &Weechat| {
+    ///         Weechat::print(&format!("Running the {} minute job", minutes));
+    ///     });
+    /// }
+    /// ```
+    pub fn new(tick: Duration) -> Result<Self, ()> {
+        if tick.as_millis() == 0 {
+            return Err(());
+        }
+
+        let state = Rc::new(RefCell::new(SchedulerState {
+            tick,
+            now: Duration::from_secs(0),
+            next_id: 0,
+            tasks: Vec::new(),
+        }));
+
+        let weak = Rc::downgrade(&state);
+        let timer = TimerHook::new(tick, 0, 0, move |weechat: &Weechat, _: RemainingCalls| {
+            Scheduler::tick(&weak, weechat)
+        })?;
+
+        Ok(Scheduler { state, _timer: timer })
+    }
+
+    /// Add a periodic task to the scheduler.
+    ///
+    /// The task first runs once the interval has passed. Returns the
+    /// identifier of the task that can be used to remove it again.
+    ///
+    /// # Arguments
+    ///
+    /// * `interval` - The delay between two runs of the task.
+    ///
+    /// * `callback` - The function that will be called when the task is due.
+    pub fn add(&self, interval: Duration, callback: impl FnMut(&Weechat) + 'static) -> TaskId {
+        let mut state = self.state.borrow_mut();
+
+        let id = TaskId(state.next_id);
+        state.next_id += 1;
+
+        let due = state.now + interval;
+        state.tasks.push(ScheduledTask { id, interval, due, callback: Some(Box::new(callback)) });
+
+        id
+    }
+
+    /// Remove a task from the scheduler.
+    ///
+    /// Returns false if no task with the given identifier exists. A task may
+    /// remove itself while it runs.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The identifier that was returned when the task was added.
+    pub fn remove(&self, id: TaskId) -> bool {
+        let task = {
+            let mut state = self.state.borrow_mut();
+            let position = state.tasks.iter().position(|t| t.id == id);
+            position.map(|p| state.tasks.remove(p))
+        };
+
+        task.is_some()
+    }
+
+    /// Get the number of tasks in the scheduler.
+    pub fn len(&self) -> usize {
+        self.state.borrow().tasks.len()
+    }
+
+    /// Does the scheduler have no tasks.
+    pub fn is_empty(&self) -> bool {
+        self.state.borrow().tasks.is_empty()
+    }
+
+    fn tick(state: &Weak<RefCell<SchedulerState>>, weechat: &Weechat) {
+        let state = match state.upgrade() {
+            Some(s) => s,
+            None => return,
+        };
+
+        let (now, due) = {
+            let mut state = state.borrow_mut();
+            let now = state.now + state.tick;
+            state.now = now;
+
+            let due: Vec<TaskId> =
+                state.tasks.iter().filter(|t| t.due <= now).map(|t| t.id).collect();
+
+            (now, due)
+        };
+
+        for id in due {
+            let callback = {
+                let mut state = state.borrow_mut();
+
+                state.tasks.iter_mut().find(|t| t.id == id).and_then(|task| {
+                    // Runs that were missed aren't made up for.
+                    task.due += task.interval;
+                    if task.due <= now {
+                        task.due = now + task.interval;
+                    }
+
+                    task.callback.take()
+                })
+            };
+
+            let mut callback = match callback {
+                Some(c) => c,
+                None => continue,
+            };
+
+            // The state isn't borrowed while the task runs, so the task may
+            // add or remove tasks.
+            callback(weechat);
+
+            if let Some(task) = state.borrow_mut().tasks.iter_mut().find(|t| t.id == id) {
+                task.callback = Some(callback);
+            }
+        }
+    }
+}
+
+// Processes a single batch, returns true if there are items left.
+type YieldingStep = Box<dyn FnMut(&Weechat) -> bool>;
+
+struct YieldingTaskState {
+    step: Option<YieldingStep>,
+    processed: usize,
+    done: bool,
+    timer: OneShot,
+}
+
+/// A handle to work that is processed in batches by
+/// `Weechat::process_with_yield()`.
+///
+/// The remaining work is cancelled when the object is dropped.
+pub struct YieldingTask {
+    state: Rc<RefCell<YieldingTaskState>>,
+}
+
+impl YieldingTask {
+    // Delay between two batches, short enough to not slow the work down while
+    // still letting Weechat handle input and redraw the screen.
+    const TICK: Duration = Duration::from_millis(1);
+
+    fn schedule(state: &Rc<RefCell<YieldingTaskState>>) -> Result<(), ()> {
+        OneShot::arm(state, |s| &mut s.timer, YieldingTask::TICK, YieldingTask::tick)
+    }
+
+    fn tick(state: &Rc<RefCell<YieldingTaskState>>, weechat: &Weechat) {
+        let step = state.borrow_mut().step.take();
+
+        let mut step = match step {
+            Some(s) => s,
+            None => return,
+        };
+
+        if step(weechat) {
+            state.borrow_mut().step = Some(step);
+
+            if YieldingTask::schedule(state).is_err() {
+                state.borrow_mut().step = None;
+            }
+        } else {
+            state.borrow_mut().done = true;
+        }
+    }
+
+    /// Get the number of items that were processed so far.
+    pub fn processed(&self) -> usize {
+        self.state.borrow().processed
+    }
+
+    /// Were all the items processed.
+    pub fn is_done(&self) -> bool {
+        self.state.borrow().done
+    }
+}
+
+impl Weechat {
+    /// Process items in batches, yielding control back to Weechat between
+    /// batches.
+    ///
+    /// Long running work on the main thread freezes Weechat, this processes
+    /// `per_tick` items every time a short timer fires so Weechat stays
+    /// responsive.
+    ///
+    /// Returns a handle to the work, dropping the handle cancels the items
+    /// that weren't processed yet.
+    ///
+    /// # Arguments
+    ///
+    /// * `items` - The items that should be processed.
+    ///
+    /// * `per_tick` - The number of items that should be processed in a
+    ///   single batch, a value of 0 is treated as 1.
+    ///
+    /// * `f` - The function that will be called for every item.
+    ///
+    /// * `progress` - The function that will be called after every batch with
+    ///   the number of processed items and the total number of items, if it's
+    ///   known.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use weechat::Weechat;
+    /// # let weechat = unsafe { Weechat::weechat() };
+    /// let lines: Vec<String> = (0..10_000).map(|i| format!("Line {}", i)).collect();
+    ///
+    /// let task = weechat
+    ///     .process_with_yield(
+    ///         lines,
+    ///         100,
+    ///         |_: &Weechat, line: String| Weechat::print(&line),
+    ///         |_: &Weechat, processed: usize, total: Option<usize>| {
+    ///             if Some(processed) == total {
+    ///                 Weechat::print("Done printing");
+    ///             }
+    ///         },
+    ///     )
+    ///     .expect("Can't start processing lines");
+    /// ```
+    pub fn process_with_yield<I>(
+        &self,
+        items: I,
+        per_tick: usize,
+        mut f: impl FnMut(&Weechat, I::Item) + 'static,
+        mut progress: impl FnMut(&Weechat, usize, Option<usize>) + 'static,
+    ) -> Result<YieldingTask, ()>
+    where
+        I: IntoIterator,
+        I::IntoIter: 'static,
+    {
+        Weechat::check_thread();
+
+        let mut items = items.into_iter().peekable();
+        let total = match items.size_hint() {
+            (lower, Some(upper)) if lower == upper => Some(lower),
+            _ => None,
+        };
+        let per_tick = per_tick.max(1);
+
+        let state = Rc::new(RefCell::new(YieldingTaskState {
+            step: None,
+            processed: 0,
+            done: false,
+            timer: OneShot::new(),
+        }));
+
+        let weak = Rc::downgrade(&state);
+        let step = move |weechat: &Weechat| {
+            let mut count = 0;
+
+            while count < per_tick {
+                match items.next() {
+                    Some(item) => f(weechat, item),
+                    None => break,
+                }
+                count += 1;
+            }
+
+            let processed = match weak.upgrade() {
+                Some(state) => {
+                    let mut state = state.borrow_mut();
+                    state.processed += count;
+                    state.processed
+                }
+                None => return false,
+            };
+
+            progress(weechat, processed, total);
+
+            items.peek().is_some()
+        };
+
+        state.borrow_mut().step = Some(Box::new(step));
+        YieldingTask::schedule(&state)?;
+
+        Ok(YieldingTask { state })
+    }
 }
@@ -25,7 +25,7 @@ use std::{
         hash_map::{IntoIter as IterHashmap, Keys},
         HashMap,
     },
-    ffi::CStr,
+    ffi::{c_void, CStr},
     fmt::Debug,
     marker::PhantomData,
     ptr,
@@ -112,6 +112,14 @@ impl<'a> InfolistItem<'a> {
         }
     }
 
+    pub(crate) fn pointer(&self, name: &str) -> *mut c_void {
+        let name = LossyCString::new(name);
+
+        let infolist_pointer = self.weechat.get().infolist_pointer.unwrap();
+
+        unsafe { infolist_pointer(self.ptr, name.as_ptr()) }
+    }
+
     fn buffer(&self, name: &str) -> Option<Buffer> {
         let name = LossyCString::new(name);
 
@@ -41,6 +41,8 @@
 
 use std::ffi::CString;
 
+mod data_map;
+mod dyn_string;
 #[cfg(feature = "async")]
 mod executor;
 mod hashtable;
@@ -60,12 +62,18 @@ pub mod buffer;
 pub mod config;
 pub mod hooks;
 pub mod infolist;
+#[cfg(feature = "testing")]
+#[cfg_attr(feature = "docs", doc(cfg(testing)))]
+pub mod testing;
 
 pub use libc;
 pub use weechat_macro::plugin;
 pub use weechat_sys;
 
-pub use crate::weechat::{Args, Prefix, Weechat};
+pub use crate::{
+    dyn_string::DynString,
+    weechat::{Args, Casemapping, Prefix, SplitFlags, Unsupported, Weechat},
+};
 
 /// Weechat plugin trait.
 ///
@@ -113,3 +121,22 @@ impl LossyCString {
         }
     }
 }
+
+/// Take ownership of a string that was allocated by Weechat.
+///
+/// The string is copied into a Rust `String` and the C string is freed,
+/// `None` is returned if the pointer is null.
+///
+/// # Safety
+///
+/// The pointer needs to be null or point to a string that was allocated by
+/// Weechat and that the caller is responsible to free.
+pub(crate) unsafe fn take_weechat_string(ptr: *mut std::os::raw::c_char) -> Option<String> {
+    if ptr.is_null() {
+        None
+    } else {
+        let string = std::ffi::CStr::from_ptr(ptr).to_string_lossy().into_owned();
+        libc::free(ptr as *mut libc::c_void);
+        Some(string)
+    }
+}
@@ -0,0 +1,661 @@
+//! Utilities to test plugin logic without a running Weechat instance.
+//!
+//! The `MockWeechat` struct installs a fake global Weechat context. Messages
+//! printed using `Weechat::print()`, `Buffer::print()` and the other print
+//! methods of a buffer are recorded instead of being sent to Weechat and can
+//! be inspected in tests.
+//!
+//! Infos can be provided using `MockWeechat::set_info()` and
+//! `MockWeechat::set_info_hashtable()`.
+//!
+//! Only printing, infos and hashtables with string keys and values are
+//! supported by the mock. Calling any other Weechat API aborts the test
+//! process with a message on stderr that names the unsupported API.
+//!
+//! ```
+//! # use weechat::{Args, Weechat};
+//! # use weechat::buffer::Buffer;
+//! # use weechat::testing::MockWeechat;
+//! let mock = MockWeechat::new();
+//! let buffer = mock.buffer("test");
+//!
+//! let mut callback = |_: &Weechat, buffer: &Buffer, mut args: Args| {
+//!     let _command = args.next();
+//!     buffer.print(&format!("Hello {}", args.next().unwrap_or_default()));
+//! };
+//!
+//! mock.run_command(&mut callback, &buffer, &["/mycmd", "foo"]);
+//!
+//! assert_eq!(mock.printed(&buffer), vec!["Hello foo"]);
+//! ```
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::{BTreeMap, HashMap},
+    ffi::{CStr, CString},
+    io::Write,
+    mem,
+    os::raw::{c_char, c_int, c_longlong, c_ulonglong, c_void},
+    ptr,
+    rc::Rc,
+    sync::{Mutex, MutexGuard},
+};
+
+use weechat_sys::{
+    t_config_file, t_gui_buffer, t_hashtable, t_hashtable_item, t_weechat_plugin, time_t,
+    WEECHAT_HASHTABLE_STRING,
+};
+
+use crate::{
+    buffer::{Buffer, InnerBuffer, InnerBuffers},
+    hooks::CommandCallback,
+    Args, Weechat,
+};
+
+// The Weechat context is stored in a global, only one mock can be alive at a
+// time.
+static MOCK_LOCK: Mutex<()> = Mutex::new(());
+static RECORDED: Mutex<Option<Vec<PrintedMessage>>> = Mutex::new(None);
+
+fn recorded() -> MutexGuard<'static, Option<Vec<PrintedMessage>>> {
+    RECORDED.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+type InfoHashtableCallback = dyn Fn(HashMap<String, String>) -> Option<HashMap<String, String>>;
+
+thread_local! {
+    static INFOS: RefCell<HashMap<String, String>> = RefCell::new(HashMap::new());
+    static INFO_HASHTABLES: RefCell<HashMap<String, Rc<InfoHashtableCallback>>> =
+        RefCell::new(HashMap::new());
+}
+
+/// A message that was printed while a `MockWeechat` was active.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrintedMessage {
+    /// The buffer pointer the message was printed on, null for the core
+    /// buffer.
+    buffer: usize,
+    /// The date of the message.
+    pub date: i64,
+    /// The tags of the message.
+    pub tags: Vec<String>,
+    /// The printed message.
+    pub message: String,
+}
+
+/// A fake Weechat context that records printed messages.
+///
+/// The mock is installed when it's created and removed when it's dropped.
+/// Creating a mock blocks until other mocks, e.g. from tests running in
+/// parallel, are dropped.
+pub struct MockWeechat {
+    weechat: Weechat,
+    _plugin: Box<t_weechat_plugin>,
+    // The boxes give every mocked buffer a unique and stable address.
+    #[allow(clippy::vec_box)]
+    buffers: RefCell<Vec<Box<u8>>>,
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl MockWeechat {
+    /// Create and install a new mocked Weechat context.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a real Weechat context is active.
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        let lock = MOCK_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        let mut plugin: Box<t_weechat_plugin> = Box::new(unsafe { mem::zeroed() });
+        mock_plugin(&mut plugin);
+
+        let ptr = &mut *plugin as *mut t_weechat_plugin;
+
+        unsafe { Weechat::install_mock(ptr) };
+        *recorded() = Some(Vec::new());
+
+        MockWeechat {
+            weechat: Weechat::from_ptr(ptr),
+            _plugin: plugin,
+            buffers: RefCell::new(Vec::new()),
+            _lock: lock,
+        }
+    }
+
+    /// Get the mocked Weechat context.
+    pub fn weechat(&self) -> &Weechat {
+        &self.weechat
+    }
+
+    /// Create a new mocked buffer.
+    ///
+    /// Messages can be printed on the buffer, other buffer methods are not
+    /// supported.
+    ///
+    /// # Arguments
+    ///
+    /// * `_name` - The name of the buffer, only used to make tests more
+    ///   readable.
+    pub fn buffer(&self, _name: &str) -> Buffer<'_> {
+        // Every buffer gets its own allocation so buffers have distinct
+        // pointers.
+        let mut buffer = Box::new(0u8);
+        let ptr = &mut *buffer as *mut u8 as *mut t_gui_buffer;
+        self.buffers.borrow_mut().push(buffer);
+
+        Buffer {
+            inner: InnerBuffers::BorrowedBuffer(InnerBuffer {
+                weechat: &self.weechat,
+                ptr,
+                closing: Rc::new(Cell::new(false)),
+            }),
+        }
+    }
+
+    /// Run a command callback with the given arguments.
+    ///
+    /// # Arguments
+    ///
+    /// * `callback` - The command callback that should be run.
+    ///
+    /// * `buffer` - The buffer the command is run on.
+    ///
+    /// * `arguments` - The arguments of the command, the first argument is
+    ///   the command itself, e.g. `&["/mycmd", "foo"]`.
+    pub fn run_command(
+        &self,
+        callback: &mut dyn CommandCallback,
+        buffer: &Buffer,
+        arguments: &[&str],
+    ) {
+        callback.callback(&self.weechat, buffer, Args::from_slice(arguments));
+    }
+
+    /// Get all the recorded messages, in the order they were printed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use weechat::testing::MockWeechat;
+    /// let mock = MockWeechat::new();
+    /// let buffer = mock.buffer("test");
+    ///
+    /// buffer.print_date_tags(0, &["notify_highlight", "no_log"], "Done: 100%");
+    ///
+    /// let messages = mock.messages();
+    /// assert_eq!(messages[0].tags, vec!["notify_highlight", "no_log"]);
+    /// assert_eq!(messages[0].message, "Done: 100%");
+    /// ```
+    pub fn messages(&self) -> Vec<PrintedMessage> {
+        recorded().clone().unwrap_or_default()
+    }
+
+    /// Get the messages that were printed on the given buffer.
+    ///
+    /// # Arguments
+    ///
+    /// * `buffer` - The buffer for which the messages should be returned.
+    pub fn printed(&self, buffer: &Buffer) -> Vec<String> {
+        self.printed_on(buffer.ptr() as usize)
+    }
+
+    /// Get the messages that were printed on the core buffer using
+    /// `Weechat::print()`.
+    pub fn printed_core(&self) -> Vec<String> {
+        self.printed_on(0)
+    }
+
+    fn printed_on(&self, buffer: usize) -> Vec<String> {
+        self.messages().into_iter().filter(|m| m.buffer == buffer).map(|m| m.message).collect()
+    }
+
+    /// Forget all the recorded messages.
+    pub fn clear(&self) {
+        *recorded() = Some(Vec::new());
+    }
+
+    /// Provide an info that can be fetched using `Weechat::info_get()`.
+    ///
+    /// The arguments that are passed when the info is fetched are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the info.
+    ///
+    /// * `value` - The value of the info.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use weechat::Weechat;
+    /// # use weechat::testing::MockWeechat;
+    /// let mock = MockWeechat::new();
+    /// mock.set_info("weechat_dir", "/home/user/.weechat");
+    ///
+    /// assert_eq!(Weechat::info_get("weechat_dir", ""), Some("/home/user/.weechat".to_owned()));
+    /// assert_eq!(Weechat::info_get("unknown_info", ""), None);
+    /// ```
+    pub fn set_info(&self, name: &str, value: &str) {
+        INFOS.with(|infos| infos.borrow_mut().insert(name.to_owned(), value.to_owned()));
+    }
+
+    /// Provide an info that can be fetched using
+    /// `Weechat::info_get_hashtable()`.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the info.
+    ///
+    /// * `callback` - Function that computes the info from the arguments
+    ///   hashtable, returning `None` behaves like an info that failed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use std::collections::HashMap;
+    /// # use weechat::Weechat;
+    /// # use weechat::testing::MockWeechat;
+    /// let mock = MockWeechat::new();
+    /// mock.set_info_hashtable("echo", |arguments| Some(arguments));
+    ///
+    /// let mut arguments = HashMap::new();
+    /// arguments.insert("message", "hello");
+    /// arguments.insert("empty", "");
+    ///
+    /// let info = Weechat::info_get_hashtable("echo", arguments).unwrap();
+    ///
+    /// assert_eq!(info.len(), 2);
+    /// assert_eq!(info["message"], "hello");
+    /// assert_eq!(info["empty"], "");
+    /// assert_eq!(Weechat::info_get_hashtable("unknown_info", HashMap::new()), None);
+    /// ```
+    pub fn set_info_hashtable(
+        &self,
+        name: &str,
+        callback: impl Fn(HashMap<String, String>) -> Option<HashMap<String, String>> + 'static,
+    ) {
+        INFO_HASHTABLES.with(|infos| infos.borrow_mut().insert(name.to_owned(), Rc::new(callback)));
+    }
+}
+
+impl Drop for MockWeechat {
+    fn drop(&mut self) {
+        *recorded() = None;
+        INFOS.with(|infos| infos.borrow_mut().clear());
+        let info_hashtables = INFO_HASHTABLES.with(|infos| infos.replace(HashMap::new()));
+        drop(info_hashtables);
+        unsafe { Weechat::uninstall_mock() };
+    }
+}
+
+extern "C" {
+    fn weechat_rs_mock_printf_date_tags(
+        buffer: *mut t_gui_buffer,
+        date: time_t,
+        tags: *const c_char,
+        message: *const c_char,
+        ...
+    );
+    fn weechat_rs_mock_printf_y(buffer: *mut t_gui_buffer, y: c_int, message: *const c_char, ...);
+    fn weechat_rs_mock_log_printf(message: *const c_char, ...);
+    fn weechat_rs_mock_config_write_line(
+        config_file: *mut t_config_file,
+        option_name: *const c_char,
+        value: *const c_char,
+        ...
+    ) -> c_int;
+}
+
+// Called by the mocked print functions once they formatted the message.
+#[doc(hidden)]
+#[no_mangle]
+pub unsafe extern "C" fn weechat_rs_mock_record_print(
+    buffer: *mut t_gui_buffer,
+    date: c_longlong,
+    tags: *const c_char,
+    message: *const c_char,
+) {
+    let tags = if tags.is_null() { "".into() } else { CStr::from_ptr(tags).to_string_lossy() };
+    let message = CStr::from_ptr(message).to_string_lossy();
+
+    if let Some(messages) = recorded().as_mut() {
+        messages.push(PrintedMessage {
+            buffer: buffer as usize,
+            date,
+            tags: tags.split(',').filter(|t| !t.is_empty()).map(|t| t.to_owned()).collect(),
+            message: message.into_owned(),
+        });
+    }
+}
+
+// Hashtables of the mocked context, only string keys and values are
+// supported.
+type MockHashtable = BTreeMap<CString, CString>;
+
+unsafe fn c_str(string: *const c_void) -> CString {
+    CStr::from_ptr(string as *const c_char).to_owned()
+}
+
+unsafe extern "C" fn mock_hashtable_new(
+    _size: c_int,
+    type_keys: *const c_char,
+    type_values: *const c_char,
+    _callback_hash_key: Option<
+        unsafe extern "C" fn(*mut t_hashtable, *const c_void) -> c_ulonglong,
+    >,
+    _callback_keycmp: Option<
+        unsafe extern "C" fn(*mut t_hashtable, *const c_void, *const c_void) -> c_int,
+    >,
+) -> *mut t_hashtable {
+    let string_type = CStr::from_bytes_with_nul_unchecked(WEECHAT_HASHTABLE_STRING);
+
+    if CStr::from_ptr(type_keys) != string_type || CStr::from_ptr(type_values) != string_type {
+        unsupported_api("hashtables that don't have string keys and values");
+    }
+
+    Box::into_raw(Box::new(MockHashtable::new())) as *mut t_hashtable
+}
+
+unsafe extern "C" fn mock_hashtable_set(
+    hashtable: *mut t_hashtable,
+    key: *const c_void,
+    value: *const c_void,
+) -> *mut t_hashtable_item {
+    let hashtable = &mut *(hashtable as *mut MockHashtable);
+    let value = if value.is_null() { CString::default() } else { c_str(value) };
+
+    hashtable.insert(c_str(key), value);
+
+    ptr::null_mut()
+}
+
+unsafe extern "C" fn mock_hashtable_map_string(
+    hashtable: *mut t_hashtable,
+    callback: Option<
+        unsafe extern "C" fn(*mut c_void, *mut t_hashtable, *const c_char, *const c_char),
+    >,
+    data: *mut c_void,
+) {
+    let callback = match callback {
+        Some(callback) => callback,
+        None => return,
+    };
+    let items = (*(hashtable as *mut MockHashtable)).clone();
+
+    for (key, value) in &items {
+        callback(data, hashtable, key.as_ptr(), value.as_ptr());
+    }
+}
+
+unsafe extern "C" fn mock_hashtable_free(hashtable: *mut t_hashtable) {
+    drop(Box::from_raw(hashtable as *mut MockHashtable));
+}
+
+unsafe extern "C" fn mock_info_get(
+    _plugin: *mut t_weechat_plugin,
+    info_name: *const c_char,
+    _arguments: *const c_char,
+) -> *mut c_char {
+    let name = CStr::from_ptr(info_name).to_string_lossy();
+
+    match INFOS.with(|infos| infos.borrow().get(name.as_ref()).cloned()) {
+        // The caller frees the info, it needs to be allocated like Weechat
+        // does it.
+        Some(value) => libc::strdup(CString::new(value).unwrap_or_default().as_ptr()),
+        None => ptr::null_mut(),
+    }
+}
+
+unsafe extern "C" fn mock_info_get_hashtable(
+    _plugin: *mut t_weechat_plugin,
+    info_name: *const c_char,
+    arguments: *mut t_hashtable,
+) -> *mut t_hashtable {
+    let name = CStr::from_ptr(info_name).to_string_lossy();
+    let callback = INFO_HASHTABLES.with(|infos| infos.borrow().get(name.as_ref()).cloned());
+
+    let callback = match callback {
+        Some(callback) => callback,
+        None => return ptr::null_mut(),
+    };
+
+    let arguments = if arguments.is_null() {
+        HashMap::new()
+    } else {
+        (*(arguments as *mut MockHashtable))
+            .iter()
+            .map(|(k, v)| (k.to_string_lossy().into_owned(), v.to_string_lossy().into_owned()))
+            .collect()
+    };
+
+    match callback(arguments) {
+        Some(info) => {
+            let hashtable: MockHashtable = info
+                .into_iter()
+                .map(|(k, v)| {
+                    (CString::new(k).unwrap_or_default(), CString::new(v).unwrap_or_default())
+                })
+                .collect();
+            Box::into_raw(Box::new(hashtable)) as *mut t_hashtable
+        }
+        None => ptr::null_mut(),
+    }
+}
+
+// Called by the stubs of the Weechat APIs that aren't mocked. Panics can't
+// unwind out of the stubs since they are called from Rust through `extern "C"`
+// function pointers, abort instead.
+fn unsupported_api(name: &str) -> ! {
+    let _ = writeln!(std::io::stderr(), "MockWeechat doesn't support {}", name);
+    std::process::abort();
+}
+
+// The name of a Weechat API, implemented by a marker type per API.
+trait ApiName {
+    const NAME: &'static str;
+}
+
+// Function pointer types of the plugin struct for which a stub with the same
+// signature can be created.
+trait Stub<N: ApiName> {
+    fn stub() -> Self;
+}
+
+macro_rules! impl_stub {
+    ($($argument:ident),*) => {
+        impl<N: ApiName, R, $($argument),*> Stub<N>
+            for Option<unsafe extern "C" fn($($argument),*) -> R>
+        {
+            fn stub() -> Self {
+                unsafe extern "C" fn stub<N: ApiName, R, $($argument),*>(
+                    $(_: $argument),*
+                ) -> R {
+                    unsupported_api(N::NAME)
+                }
+
+                Some(stub::<N, R, $($argument),*>)
+            }
+        }
+    };
+}
+
+// Implement the stub for every arity up to the largest one of the plugin
+// API.
+macro_rules! impl_stubs {
+    () => {
+        impl_stub!();
+    };
+    ($first:ident $(, $rest:ident)*) => {
+        impl_stub!($first $(, $rest)*);
+        impl_stubs!($($rest),*);
+    };
+}
+
+impl_stubs!(
+    A1, A2, A3, A4, A5, A6, A7, A8, A9, A10, A11, A12, A13, A14, A15, A16, A17, A18, A19, A20
+);
+
+// Replace the function pointers of the plugin that aren't mocked with stubs
+// of the same signature that abort, naming the API that isn't supported.
+macro_rules! unsupported {
+    ($plugin:ident, $($name:ident),* $(,)?) => {
+        $(
+            $plugin.$name = {
+                #[allow(non_camel_case_types)]
+                struct $name;
+
+                impl ApiName for $name {
+                    const NAME: &'static str = stringify!($name);
+                }
+
+                <_ as Stub<$name>>::stub()
+            };
+        )*
+    };
+}
+
+fn mock_plugin(plugin: &mut t_weechat_plugin) {
+    // The Weechat APIs this crate uses.
+    unsupported!(
+        plugin,
+        bar_item_new,
+        bar_item_remove,
+        bar_item_update,
+        bar_new,
+        bar_remove,
+        bar_search,
+        bar_set,
+        bar_update,
+        buffer_clear,
+        buffer_close,
+        buffer_get_integer,
+        buffer_get_pointer,
+        buffer_get_string,
+        buffer_merge,
+        buffer_new,
+        buffer_search,
+        buffer_search_main,
+        buffer_set,
+        buffer_set_pointer,
+        buffer_string_replace_local_var,
+        buffer_unmerge,
+        color,
+        command,
+        command_options,
+        config_boolean,
+        config_free,
+        config_get,
+        config_get_plugin,
+        config_integer,
+        config_new,
+        config_new_option,
+        config_new_section,
+        config_option_free,
+        config_option_get_string,
+        config_option_is_null,
+        config_option_reset,
+        config_option_set,
+        config_read,
+        config_search_option,
+        config_section_free,
+        config_section_free_options,
+        config_set_plugin,
+        config_string,
+        config_write,
+        config_write_option,
+        hashtable_free,
+        hashtable_map_string,
+        hashtable_new,
+        hashtable_set,
+        hdata_char,
+        hdata_get,
+        hdata_get_list,
+        hdata_get_var_array_size,
+        hdata_hashtable,
+        hdata_integer,
+        hdata_move,
+        hdata_pointer,
+        hdata_string,
+        hdata_time,
+        hdata_update,
+        hook_command,
+        hook_command_run,
+        hook_completion,
+        hook_completion_get_string,
+        hook_completion_list_add,
+        hook_config,
+        hook_connect,
+        hook_fd,
+        hook_focus,
+        hook_hsignal,
+        hook_hsignal_send,
+        hook_line,
+        hook_modifier,
+        hook_modifier_exec,
+        hook_print,
+        hook_process,
+        hook_process_hashtable,
+        hook_set,
+        hook_signal,
+        hook_signal_send,
+        hook_timer,
+        info_get,
+        info_get_hashtable,
+        infolist_fields,
+        infolist_free,
+        infolist_get,
+        infolist_integer,
+        infolist_next,
+        infolist_pointer,
+        infolist_string,
+        infolist_time,
+        nicklist_add_group,
+        nicklist_add_nick,
+        nicklist_get_next_item,
+        nicklist_group_get_integer,
+        nicklist_group_get_string,
+        nicklist_nick_get_integer,
+        nicklist_nick_get_string,
+        nicklist_remove_group,
+        nicklist_remove_nick,
+        nicklist_search_group,
+        nicklist_search_nick,
+        plugin_get_name,
+        prefix,
+        string_build_with_split_string,
+        string_dyn_alloc,
+        string_dyn_concat,
+        string_dyn_copy,
+        string_dyn_free,
+        string_eval_expression,
+        string_eval_path_home,
+        string_expand_home,
+        string_free_split,
+        string_has_highlight,
+        string_has_highlight_regex,
+        string_regcomp,
+        string_remove_color,
+        string_replace,
+        string_replace_regex,
+        string_split,
+        strlen_screen,
+        strndup,
+        unhook,
+        window_get_integer,
+        window_get_pointer,
+        window_search_with_buffer,
+        window_set_title,
+    );
+
+    plugin.printf_date_tags = Some(weechat_rs_mock_printf_date_tags);
+    plugin.printf_y = Some(weechat_rs_mock_printf_y);
+    plugin.log_printf = Some(weechat_rs_mock_log_printf);
+    plugin.config_write_line = Some(weechat_rs_mock_config_write_line);
+    plugin.hashtable_new = Some(mock_hashtable_new);
+    plugin.hashtable_set = Some(mock_hashtable_set);
+    plugin.hashtable_map_string = Some(mock_hashtable_map_string);
+    plugin.hashtable_free = Some(mock_hashtable_free);
+    plugin.info_get = Some(mock_info_get);
+    plugin.info_get_hashtable = Some(mock_info_get_hashtable);
+}
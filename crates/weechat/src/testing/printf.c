@@ -0,0 +1,93 @@
+/*
+ * Mocked Weechat print functions.
+ *
+ * The print functions of the Weechat plugin API are variadic, stable Rust
+ * can't define variadic functions so the mocks are implemented in C. The
+ * formatted message is passed to the Rust side which records it.
+ *
+ * The other variadic functions aren't supported by the mock, their stubs
+ * abort like the stubs of the other unsupported functions.
+ */
+
+#include <stdarg.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <time.h>
+
+extern void weechat_rs_mock_record_print(void *buffer, long long date,
+                                         const char *tags,
+                                         const char *message);
+
+static void
+record_print(void *buffer, long long date, const char *tags,
+             const char *format, va_list args)
+{
+    va_list args_copy;
+    char *message;
+    int length;
+
+    va_copy(args_copy, args);
+    length = vsnprintf(NULL, 0, format, args_copy);
+    va_end(args_copy);
+
+    if (length < 0)
+        return;
+
+    message = malloc(length + 1);
+    if (!message)
+        return;
+
+    vsnprintf(message, length + 1, format, args);
+    weechat_rs_mock_record_print(buffer, date, tags, message);
+    free(message);
+}
+
+void
+weechat_rs_mock_printf_date_tags(void *buffer, time_t date, const char *tags,
+                                 const char *format, ...)
+{
+    va_list args;
+
+    va_start(args, format);
+    record_print(buffer, date, tags, format, args);
+    va_end(args);
+}
+
+void
+weechat_rs_mock_printf_y(void *buffer, int y, const char *format, ...)
+{
+    va_list args;
+
+    (void) y;
+
+    va_start(args, format);
+    record_print(buffer, 0, NULL, format, args);
+    va_end(args);
+}
+
+static void
+unsupported(const char *name)
+{
+    fprintf(stderr, "MockWeechat doesn't support %s\n", name);
+    abort();
+}
+
+void
+weechat_rs_mock_log_printf(const char *format, ...)
+{
+    (void) format;
+
+    unsupported("log_printf");
+}
+
+int
+weechat_rs_mock_config_write_line(void *config_file, const char *option_name,
+                                  const char *value, ...)
+{
+    (void) config_file;
+    (void) option_name;
+    (void) value;
+
+    unsupported("config_write_line");
+    return 0;
+}
@@ -3,21 +3,32 @@
 #[cfg(feature = "async")]
 use std::future::Future;
 use std::{
+    any::TypeId,
+    borrow::Cow,
+    collections::HashMap,
     ffi::{CStr, CString},
+    mem,
+    ops::BitOr,
+    os::raw::c_void,
     panic::PanicInfo,
     path::PathBuf,
-    ptr, vec,
+    ptr,
+    rc::Rc,
+    vec,
 };
 
 #[cfg(feature = "async")]
 pub use async_task::Task;
 use backtrace::Backtrace;
 use libc::{c_char, c_int};
-use weechat_sys::t_weechat_plugin;
+use weechat_sys::{
+    t_weechat_plugin, WEECHAT_STRING_SPLIT_COLLAPSE_SEPS, WEECHAT_STRING_SPLIT_KEEP_EOL,
+    WEECHAT_STRING_SPLIT_STRIP_LEFT, WEECHAT_STRING_SPLIT_STRIP_RIGHT,
+};
 
 #[cfg(feature = "async")]
 use crate::executor::WeechatExecutor;
-use crate::LossyCString;
+use crate::{data_map::DataMap, take_weechat_string, LossyCString};
 
 /// An iterator over the arguments of a Weechat command, yielding a String value
 /// for each argument.
@@ -52,6 +63,108 @@ impl Prefix {
     }
 }
 
+/// Flags controlling how `Weechat::string_split()` splits a string.
+///
+/// Flags can be combined using the `|` operator.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SplitFlags(c_int);
+
+impl SplitFlags {
+    /// Don't strip or collapse anything.
+    pub const NONE: SplitFlags = SplitFlags(0);
+    /// Strip separators on the left of the string.
+    pub const STRIP_LEFT: SplitFlags = SplitFlags(WEECHAT_STRING_SPLIT_STRIP_LEFT);
+    /// Strip separators on the right of the string.
+    pub const STRIP_RIGHT: SplitFlags = SplitFlags(WEECHAT_STRING_SPLIT_STRIP_RIGHT);
+    /// Collapse multiple consecutive separators into a single one.
+    pub const COLLAPSE_SEPARATORS: SplitFlags = SplitFlags(WEECHAT_STRING_SPLIT_COLLAPSE_SEPS);
+    /// Keep the end of the line for each item.
+    pub const KEEP_EOL: SplitFlags = SplitFlags(WEECHAT_STRING_SPLIT_KEEP_EOL);
+
+    /// The flags that Weechat uses to split most lists, strip separators on
+    /// both sides and collapse consecutive separators.
+    pub fn standard() -> SplitFlags {
+        SplitFlags::STRIP_LEFT | SplitFlags::STRIP_RIGHT | SplitFlags::COLLAPSE_SEPARATORS
+    }
+}
+
+impl BitOr for SplitFlags {
+    type Output = SplitFlags;
+
+    fn bitor(self, rhs: SplitFlags) -> SplitFlags {
+        SplitFlags(self.0 | rhs.0)
+    }
+}
+
+/// The casemappings IRC servers use to decide which nicks are equal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Casemapping {
+    /// Only the letters `A` to `Z` are mapped to `a` to `z`.
+    Ascii,
+    /// Like `Ascii`, additionally `[]\~` are mapped to `{}|^`.
+    Rfc1459,
+    /// Like `Rfc1459`, but `~` and `^` are distinct characters.
+    StrictRfc1459,
+}
+
+impl Casemapping {
+    fn fold(self, c: char) -> char {
+        match (self, c) {
+            (_, 'A'..='Z') => c.to_ascii_lowercase(),
+            (Casemapping::Rfc1459, '~') => '^',
+            (Casemapping::Rfc1459, '[') | (Casemapping::StrictRfc1459, '[') => '{',
+            (Casemapping::Rfc1459, ']') | (Casemapping::StrictRfc1459, ']') => '}',
+            (Casemapping::Rfc1459, '\\') | (Casemapping::StrictRfc1459, '\\') => '|',
+            _ => c,
+        }
+    }
+}
+
+/// Error returned if the running Weechat version doesn't support a feature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Unsupported {
+    feature: &'static str,
+    required_version: (u8, u8, u8),
+}
+
+impl Unsupported {
+    // Returns an error if the running Weechat version is older than the
+    // given one.
+    pub(crate) fn check(
+        feature: &'static str,
+        required_version: (u8, u8, u8),
+    ) -> Result<(), Unsupported> {
+        let (major, minor, patch) = required_version;
+        let required = (major as u32) << 24 | (minor as u32) << 16 | (patch as u32) << 8;
+
+        if Weechat::version_number() >= required {
+            Ok(())
+        } else {
+            Err(Unsupported { feature, required_version })
+        }
+    }
+
+    /// Get the name of the unsupported feature.
+    pub fn feature(&self) -> &str {
+        self.feature
+    }
+
+    /// Get the Weechat version that is required for the feature as a
+    /// `(major, minor, patch)` tuple.
+    pub fn required_version(&self) -> (u8, u8, u8) {
+        self.required_version
+    }
+}
+
+impl std::fmt::Display for Unsupported {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (major, minor, patch) = self.required_version;
+        write!(f, "{} requires Weechat {}.{}.{} or newer", self.feature, major, minor, patch)
+    }
+}
+
+impl std::error::Error for Unsupported {}
+
 impl Args {
     /// Create an Args object from the underlying weechat C types.
     /// Expects the strings in argv to be valid utf8, if not invalid UTF-8
@@ -74,6 +187,39 @@ impl Args {
             .collect();
         Args { iter: args.into_iter() }
     }
+
+    /// Create an Args object from a list of owned strings.
+    ///
+    /// This is mostly useful to test argument parsing logic, the created
+    /// object behaves the same as the one Weechat passes to a callback.
+    ///
+    /// # Arguments
+    ///
+    /// * `args` - The arguments, the first argument is usually the command
+    ///   itself.
+    pub fn owned(args: Vec<String>) -> Args {
+        Args { iter: args.into_iter() }
+    }
+
+    /// Create an Args object from a slice of strings.
+    ///
+    /// # Arguments
+    ///
+    /// * `args` - The arguments, the first argument is usually the command
+    ///   itself.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use weechat::Args;
+    /// let mut args = Args::from_slice(&["/mycmd", "foo"]);
+    ///
+    /// assert_eq!(args.len(), 2);
+    /// assert_eq!(args.nth(1).as_deref(), Some("foo"));
+    /// ```
+    pub fn from_slice(args: &[&str]) -> Args {
+        Args::owned(args.iter().map(|a| a.to_string()).collect())
+    }
 }
 
 impl std::fmt::Debug for Args {
@@ -109,6 +255,10 @@ impl DoubleEndedIterator for Args {
 static mut WEECHAT: Option<Weechat> = None;
 static mut WEECHAT_THREAD_ID: Option<std::thread::ThreadId> = None;
 
+thread_local! {
+    static PLUGIN_DATA: DataMap<(usize, TypeId)> = DataMap::new();
+}
+
 /// Main Weechat struct that encapsulates common weechat API functions.
 /// It has a similar API as the weechat script API.
 pub struct Weechat {
@@ -215,8 +365,84 @@ impl Weechat {
     /// This should never be called by the user. This is called internally.
     #[doc(hidden)]
     pub unsafe fn free() {
+        // Weechat closes the buffers of a plugin only after the end function
+        // of the plugin returns, the close callbacks would then run without a
+        // global Weechat context. Close them while the context still exists.
+        if let Ok(weechat) = Weechat::try_weechat() {
+            let buffers: Vec<_> = weechat.buffers().filter(|b| b.is_own()).collect();
+
+            for buffer in buffers {
+                buffer.close();
+            }
+        }
+
+        crate::buffer::free_buffer_data();
+
+        PLUGIN_DATA.with(|data| data.clear());
+
         #[cfg(feature = "async")]
         WeechatExecutor::free();
+
+        // The panic hook lives in the plugin library which is about to be
+        // unloaded, restore the default one.
+        let _ = std::panic::take_hook();
+
+        WEECHAT = None;
+        WEECHAT_THREAD_ID = None;
+    }
+
+    /// Store plugin scoped data.
+    ///
+    /// One value per type can be stored, storing a value of the same type
+    /// again replaces the previous value. This allows libraries that build on
+    /// top of this crate to keep their own context that can be retrieved
+    /// inside of any callback. The data is dropped when the plugin is
+    /// unloaded.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The data that should be stored.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use std::cell::Cell;
+    /// # use weechat::Weechat;
+    /// # let weechat = unsafe { Weechat::weechat() };
+    /// struct FrameworkContext {
+    ///     handled_commands: Cell<u64>,
+    /// }
+    ///
+    /// weechat.set_plugin_data(FrameworkContext { handled_commands: Cell::new(0) });
+    ///
+    /// if let Some(context) = weechat.plugin_data::<FrameworkContext>() {
+    ///     context.handled_commands.set(context.handled_commands.get() + 1);
+    /// }
+    /// ```
+    pub fn set_plugin_data<T: 'static>(&self, data: T) {
+        Weechat::check_thread();
+
+        let key = (self.ptr as usize, TypeId::of::<T>());
+        PLUGIN_DATA.with(|d| d.insert(key, data));
+    }
+
+    /// Get plugin scoped data that was stored using
+    /// `Weechat::set_plugin_data()`.
+    ///
+    /// Returns `None` if no data of the given type was stored.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    pub fn plugin_data<T: 'static>(&self) -> Option<Rc<T>> {
+        Weechat::check_thread();
+
+        let key = (self.ptr as usize, TypeId::of::<T>());
+        PLUGIN_DATA.with(|d| d.get(&key))
     }
 
     pub(crate) fn from_ptr(ptr: *mut t_weechat_plugin) -> Weechat {
@@ -235,10 +461,52 @@ impl Weechat {
     ///
     /// Since this one will have a static lifetime, objects that are fetched
     /// from this object may have a longer lifetime than they should.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the plugin isn't initialized yet or if it was already
+    /// unloaded, use `Weechat::try_weechat()` to handle this case.
     pub unsafe fn weechat() -> &'static mut Weechat {
+        match Weechat::try_weechat() {
+            Ok(w) => w,
+            Err(_) => panic!(
+                "Weechat isn't initialized, the plugin either wasn't loaded \
+                 yet or was already unloaded"
+            ),
+        }
+    }
+
+    /// Install a mocked plugin as the global Weechat context.
+    #[cfg(feature = "testing")]
+    pub(crate) unsafe fn install_mock(ptr: *mut t_weechat_plugin) {
+        if Weechat::try_weechat().is_ok() {
+            panic!("Can't install a mocked Weechat context, a context is already active");
+        }
+
+        WEECHAT = Some(Weechat { ptr });
+        WEECHAT_THREAD_ID = Some(std::thread::current().id());
+    }
+
+    /// Remove the mocked global Weechat context.
+    #[cfg(feature = "testing")]
+    pub(crate) unsafe fn uninstall_mock() {
+        WEECHAT = None;
+        WEECHAT_THREAD_ID = None;
+    }
+
+    /// Get the Weechat plugin if the plugin is currently loaded.
+    ///
+    /// Returns an error if the plugin isn't initialized yet or if it was
+    /// already unloaded, e.g. when called from a `Drop` implementation that
+    /// runs after the plugin was unloaded.
+    ///
+    /// # Safety
+    ///
+    /// The same caveats as for `Weechat::weechat()` apply.
+    pub unsafe fn try_weechat() -> Result<&'static mut Weechat, ()> {
         match WEECHAT {
-            Some(ref mut w) => w,
-            None => panic!("Plugin wasn't initialized correctly"),
+            Some(ref mut w) => Ok(w),
+            None => Err(()),
         }
     }
 
@@ -287,8 +555,8 @@ impl Weechat {
     fn thread_id() -> std::thread::ThreadId {
         *unsafe {
             WEECHAT_THREAD_ID.as_ref().expect(
-                "Weechat main thread ID wasn't found, plugin \
-                 wasn't correctly initialized",
+                "Weechat main thread ID wasn't found, the plugin either \
+                 wasn't loaded yet or was already unloaded",
             )
         }
     }
@@ -296,8 +564,8 @@ impl Weechat {
     pub(crate) fn check_thread() {
         let weechat_thread_id = unsafe {
             WEECHAT_THREAD_ID.as_ref().expect(
-                "Weechat main thread ID wasn't found, plugin \
-                 wasn't correctly initialized",
+                "Weechat main thread ID wasn't found, the plugin either \
+                 wasn't loaded yet or was already unloaded",
             )
         };
 
@@ -311,6 +579,9 @@ impl Weechat {
 
     /// Return a string color code for display.
     ///
+    /// Besides color names, terminal color numbers (e.g. `214`) can be used
+    /// if the terminal supports enough colors.
+    ///
     /// # Arguments
     ///
     /// `color_name` - name of the color
@@ -347,6 +618,166 @@ impl Weechat {
         Weechat::color(&format!("{},{}", foreground_color, background_color)).to_string()
     }
 
+    /// Check if the given name is a valid Weechat color.
+    ///
+    /// Every name that is accepted by `Weechat::color()` is valid, this
+    /// includes color names and numbers with attributes (e.g. `*red` or
+    /// `_214`), color pairs (e.g. `*red,blue`), attributes (e.g. `bold`) and
+    /// the names of color options (e.g. `chat_nick`).
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the color that should be checked.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use weechat::Weechat;
+    /// # let weechat = unsafe { Weechat::weechat() };
+    /// assert!(weechat.color_is_valid("*red,blue"));
+    /// assert!(!weechat.color_is_valid("notacolor"));
+    /// ```
+    pub fn color_is_valid(&self, name: &str) -> bool {
+        // Weechat returns an empty string for unknown colors.
+        !name.is_empty() && !Weechat::color(name).is_empty()
+    }
+
+    /// Get the Weechat color name for a terminal color number.
+    ///
+    /// The first 16 colors have names in Weechat, other colors are referenced
+    /// by their number.
+    ///
+    /// # Arguments
+    ///
+    /// * `number` - The terminal color number.
+    pub fn color_name_for_number(&self, number: u32) -> Cow<'static, str> {
+        const NAMES: [&str; 16] = [
+            "black",
+            "red",
+            "green",
+            "brown",
+            "blue",
+            "magenta",
+            "cyan",
+            "gray",
+            "darkgray",
+            "lightred",
+            "lightgreen",
+            "yellow",
+            "lightblue",
+            "lightmagenta",
+            "lightcyan",
+            "white",
+        ];
+
+        match NAMES.get(number as usize) {
+            Some(name) => Cow::Borrowed(name),
+            None => Cow::Owned(number.to_string()),
+        }
+    }
+
+    /// Return a color code for display that is the closest match for the given
+    /// RGB color.
+    ///
+    /// Weechat can't display true colors, the color is mapped to the 256
+    /// color palette. If the terminal supports fewer colors the closest of
+    /// the basic colors is used instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `red` - The red component of the color.
+    ///
+    /// * `green` - The green component of the color.
+    ///
+    /// * `blue` - The blue component of the color.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use weechat::Weechat;
+    /// # let weechat = unsafe { Weechat::weechat() };
+    /// let orange = weechat.color_rgb(255, 128, 0);
+    /// Weechat::print(&format!("{}Orange", orange));
+    /// ```
+    pub fn color_rgb(&self, red: u8, green: u8, blue: u8) -> String {
+        let term_colors: u32 =
+            Weechat::info_get("term_colors", "").and_then(|c| c.parse().ok()).unwrap_or(16);
+
+        let number = if term_colors >= 256 {
+            Weechat::rgb_to_palette(red, green, blue)
+        } else {
+            Weechat::rgb_to_basic(red, green, blue, term_colors.clamp(8, 16))
+        };
+
+        Weechat::color(&self.color_name_for_number(number)).to_owned()
+    }
+
+    fn rgb_distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+        let d = |x: u8, y: u8| (x as i32 - y as i32).pow(2) as u32;
+        d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)
+    }
+
+    // Map a color to the 6x6x6 color cube or the grayscale ramp of the 256
+    // color palette, whichever is closer.
+    fn rgb_to_palette(red: u8, green: u8, blue: u8) -> u32 {
+        const LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+        let level = |v: u8| match v {
+            0..=47 => 0,
+            48..=114 => 1,
+            _ => (v as usize - 35) / 40,
+        };
+
+        let (r, g, b) = (level(red), level(green), level(blue));
+        let cube = (LEVELS[r], LEVELS[g], LEVELS[b]);
+
+        let average = (red as u32 + green as u32 + blue as u32) / 3;
+        let gray_index = (average.saturating_sub(3) / 10).min(23);
+        let gray_level = (8 + gray_index * 10) as u8;
+
+        if Weechat::rgb_distance((red, green, blue), (gray_level, gray_level, gray_level))
+            < Weechat::rgb_distance((red, green, blue), cube)
+        {
+            232 + gray_index
+        } else {
+            16 + 36 * r as u32 + 6 * g as u32 + b as u32
+        }
+    }
+
+    // Map a color to the closest of the basic terminal colors.
+    fn rgb_to_basic(red: u8, green: u8, blue: u8, colors: u32) -> u32 {
+        const BASIC: [(u8, u8, u8); 16] = [
+            (0, 0, 0),
+            (205, 0, 0),
+            (0, 205, 0),
+            (205, 205, 0),
+            (0, 0, 238),
+            (205, 0, 205),
+            (0, 205, 205),
+            (229, 229, 229),
+            (127, 127, 127),
+            (255, 0, 0),
+            (0, 255, 0),
+            (255, 255, 0),
+            (92, 92, 255),
+            (255, 0, 255),
+            (0, 255, 255),
+            (255, 255, 255),
+        ];
+
+        (0..colors)
+            .min_by_key(|&i| Weechat::rgb_distance((red, green, blue), BASIC[i as usize]))
+            .unwrap_or_default()
+    }
+
     /// Retrieve a prefix value
     ///
     /// # Arguments:
@@ -380,8 +811,35 @@ impl Weechat {
         }
     }
 
+    /// Get the version of the running Weechat as a number.
+    ///
+    /// The version is encoded as `0xMMmmpp00`, e.g. `0x04000200` for version
+    /// 4.0.2, so versions can be compared numerically.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    pub fn version_number() -> u32 {
+        Weechat::info_get("version_number", "").and_then(|v| v.parse().ok()).unwrap_or_default()
+    }
+
     /// Get some info from Weechat or a plugin.
     ///
+    /// The info is copied into an owned string, the string Weechat allocates
+    /// for the info is freed. `None` is returned if the info doesn't exist,
+    /// e.g. because the running Weechat version is too old.
+    ///
+    /// Some useful infos that Weechat provides:
+    ///
+    /// * `version` - The Weechat version as a string.
+    /// * `pid` - The process ID of Weechat, the same as
+    ///   `std::process::id()` since plugins run inside the Weechat process.
+    /// * `uptime` - The number of seconds since Weechat was started.
+    /// * `weechat_dir` - The Weechat home directory, on Weechat 3.2 and newer
+    ///   this is the data directory.
+    /// * `weechat_config_dir`, `weechat_data_dir`, `weechat_cache_dir` - The
+    ///   XDG directories that Weechat uses since version 3.2.
+    ///
     /// # Arguments
     ///
     /// * `name` - name the info
@@ -398,10 +856,55 @@ impl Weechat {
 
         unsafe {
             let info = info_get(weechat.ptr, info_name.as_ptr(), arguments.as_ptr());
+            take_weechat_string(info)
+        }
+    }
+
+    /// Get some info from Weechat or a plugin as a hashtable.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - name the info
+    ///
+    /// * `arguments` - arguments for the info
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use std::collections::HashMap;
+    /// # use weechat::Weechat;
+    /// let mut arguments = HashMap::new();
+    /// arguments.insert("message", ":nick!user@host PRIVMSG #weechat :hello!");
+    ///
+    /// let parsed = Weechat::info_get_hashtable("irc_message_parse", arguments);
+    /// ```
+    pub fn info_get_hashtable(
+        name: &str,
+        arguments: HashMap<&str, &str>,
+    ) -> Option<HashMap<String, String>> {
+        Weechat::check_thread();
+        let weechat = unsafe { Weechat::weechat() };
+
+        let info_get_hashtable = weechat.get().info_get_hashtable.unwrap();
+        let hashtable_free = weechat.get().hashtable_free.unwrap();
+
+        let info_name = LossyCString::new(name);
+        let arguments = weechat.hashmap_to_weechat(arguments);
+
+        unsafe {
+            let info = info_get_hashtable(weechat.ptr, info_name.as_ptr(), arguments);
+            hashtable_free(arguments);
+
             if info.is_null() {
                 None
             } else {
-                Some(CStr::from_ptr(info).to_string_lossy().to_string())
+                let map = weechat.hashtable_to_map(info);
+                hashtable_free(info);
+                Some(map)
             }
         }
     }
@@ -423,12 +926,10 @@ impl Weechat {
 
         let remove_color = weechat.get().string_remove_color.unwrap();
 
-        let string = unsafe {
+        unsafe {
             let ptr = remove_color(string.as_ptr(), ptr::null());
-            CString::from_raw(ptr)
-        };
-
-        string.to_string_lossy().to_string()
+            take_weechat_string(ptr).unwrap_or_default()
+        }
     }
 
     /// Evaluate a Weechat expression and return the result.
@@ -459,67 +960,630 @@ impl Weechat {
                 ptr::null_mut(),
             );
 
-            if result.is_null() {
-                Err(())
-            } else {
-                Ok(CStr::from_ptr(result).to_string_lossy().to_string())
-            }
+            take_weechat_string(result).ok_or(())
         }
     }
 
-    /// Get the Weechat homedir.
-    pub fn home_dir() -> PathBuf {
+    /// Evaluate a Weechat expression with additional variables.
+    ///
+    /// The variables can be referenced in the expression using `${name}`,
+    /// variables that aren't defined are replaced by an empty string.
+    ///
+    /// # Arguments
+    ///
+    /// * `expression` - The expression that should be evaluated.
+    ///
+    /// * `variables` - Variables that should be available in the expression.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use std::collections::HashMap;
+    /// # use weechat::Weechat;
+    /// let mut variables = HashMap::new();
+    /// variables.insert("nick", "alice");
+    ///
+    /// let greeting =
+    ///     Weechat::eval_string_expression_with_vars("Hello ${color:red}${nick}", variables);
+    /// ```
+    pub fn eval_string_expression_with_vars(
+        expression: &str,
+        variables: HashMap<&str, &str>,
+    ) -> Result<String, ()> {
         Weechat::check_thread();
         let weechat = unsafe { Weechat::weechat() };
 
-        let eval_path_home = weechat.get().string_eval_path_home.unwrap();
+        let string_eval_expression = weechat.get().string_eval_expression.unwrap();
+        let hashtable_free = weechat.get().hashtable_free.unwrap();
 
-        let path = LossyCString::new("%h");
+        let expr = LossyCString::new(expression);
+        let extra_vars = weechat.hashmap_to_weechat(variables);
 
-        let path = unsafe {
+        unsafe {
             let result =
-                eval_path_home(path.as_ptr(), ptr::null_mut(), ptr::null_mut(), ptr::null_mut());
+                string_eval_expression(expr.as_ptr(), ptr::null_mut(), extra_vars, ptr::null_mut());
 
-            if result.is_null() {
-                panic!("Returned null while evaluating the Weechat home dir");
-            } else {
-                CStr::from_ptr(result).to_string_lossy().to_string()
-            }
-        };
+            hashtable_free(extra_vars);
 
-        PathBuf::from(path)
+            take_weechat_string(result).ok_or(())
+        }
     }
 
-    /// Replace a leading `~` with the home directory.
+    /// Render a user defined format for a line.
     ///
-    /// If the string does not start with `~`, the same string is returned.
-    pub fn expand_home(string: &str) -> String {
-        Weechat::check_thread();
-
-        let weechat = unsafe { Weechat::weechat() };
-        let expand = weechat.get().string_expand_home.unwrap();
-        let string = LossyCString::new(string);
-
-        let string = unsafe {
-            let result = expand(string.as_ptr());
-
-            if result.is_null() {
-                panic!("Returned null while expanding the home dir");
-            } else {
-                CStr::from_ptr(result).to_string_lossy().to_string()
-            }
-        };
-
-        string
-    }
-
-    /// Execute a modifier.
+    /// The format is evaluated as a Weechat expression with the given
+    /// variables, so it may contain `${...}` variables as well as
+    /// `${color:...}` colors. The rendered line is then passed through the
+    /// given modifier, this allows other plugins and scripts to alter the
+    /// line by hooking the modifier, the format is passed as the modifier
+    /// data.
     ///
-    /// A modifier takes a string and modifies it in some way, Weechat has a
-    /// list of defined modifiers. For example to parse a string with some color
-    /// format (ansi, irc...) and to convert it to another format.
+    /// Variables that aren't defined are rendered as empty strings.
     ///
-    /// Returns the modified string or an empty error if the string couldn't be
+    /// # Arguments
+    ///
+    /// * `modifier` - The name of the modifier the rendered line is passed
+    ///   through, e.g. `myplugin_format_line`.
+    ///
+    /// * `format` - The format that should be rendered, usually the value of
+    ///   a config option.
+    ///
+    /// * `variables` - Variables that should be available in the format.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use std::collections::HashMap;
+    /// # use weechat::Weechat;
+    /// let mut variables = HashMap::new();
+    /// variables.insert("nick", "alice");
+    /// variables.insert("message", "Hello");
+    ///
+    /// let line = Weechat::render_format(
+    ///     "myplugin_format_line",
+    ///     "${color:green}${nick}${color:reset}: ${message}",
+    ///     variables,
+    /// );
+    /// ```
+    pub fn render_format(modifier: &str, format: &str, variables: HashMap<&str, &str>) -> String {
+        let line = Weechat::eval_string_expression_with_vars(format, variables).unwrap_or_default();
+
+        Weechat::execute_modifier(modifier, format, &line).unwrap_or(line)
+    }
+
+    /// Get the Weechat homedir.
+    pub fn home_dir() -> PathBuf {
+        let path = Weechat::eval_path_home("%h")
+            .expect("Returned null while evaluating the Weechat home dir");
+
+        PathBuf::from(path)
+    }
+
+    /// Get the directory where Weechat stores its data, e.g. logs and
+    /// scripts.
+    ///
+    /// Weechat versions older than 3.2 don't split their directories, the
+    /// Weechat home directory is returned on those.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    pub fn data_dir() -> PathBuf {
+        Weechat::dir_info("weechat_data_dir")
+    }
+
+    /// Get the directory where Weechat stores its cache, e.g. downloaded
+    /// script lists.
+    ///
+    /// Weechat versions older than 3.2 don't split their directories, the
+    /// Weechat home directory is returned on those.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use weechat::Weechat;
+    /// let socket_path = Weechat::cache_dir().join("myplugin.sock");
+    /// ```
+    pub fn cache_dir() -> PathBuf {
+        Weechat::dir_info("weechat_cache_dir")
+    }
+
+    // Get a directory info, falling back to the home directory on Weechat
+    // versions that don't know the info.
+    fn dir_info(name: &str) -> PathBuf {
+        Weechat::info_get(name, "")
+            .or_else(|| Weechat::info_get("weechat_dir", ""))
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .unwrap_or_else(Weechat::home_dir)
+    }
+
+    /// Expand a path the same way Weechat expands paths in its options.
+    ///
+    /// A leading `%h` is replaced with the Weechat home directory, a leading
+    /// `~` with the home directory of the user and `${...}` expressions are
+    /// evaluated. Relative paths are made absolute using the current
+    /// directory of Weechat.
+    ///
+    /// This pairs with `CoreCompletion::Filename` which completes paths as
+    /// the user typed them, directories are completed with a trailing slash.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path that should be expanded.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use weechat::Weechat;
+    /// let path = Weechat::expand_path("%h/scripts/hello.rs");
+    /// assert!(path.is_absolute());
+    /// ```
+    pub fn expand_path(path: &str) -> PathBuf {
+        let path = PathBuf::from(Weechat::eval_path_home(path).unwrap_or_else(|| path.to_owned()));
+
+        if path.is_relative() {
+            std::env::current_dir().map(|dir| dir.join(&path)).unwrap_or(path)
+        } else {
+            path
+        }
+    }
+
+    fn eval_path_home(path: &str) -> Option<String> {
+        Weechat::check_thread();
+        let weechat = unsafe { Weechat::weechat() };
+
+        let eval_path_home = weechat.get().string_eval_path_home.unwrap();
+
+        let path = LossyCString::new(path);
+
+        unsafe {
+            let result =
+                eval_path_home(path.as_ptr(), ptr::null_mut(), ptr::null_mut(), ptr::null_mut());
+
+            take_weechat_string(result)
+        }
+    }
+
+    /// Replace a leading `~` with the home directory.
+    ///
+    /// If the string does not start with `~`, the same string is returned.
+    pub fn expand_home(string: &str) -> String {
+        Weechat::check_thread();
+
+        let weechat = unsafe { Weechat::weechat() };
+        let expand = weechat.get().string_expand_home.unwrap();
+        let string = LossyCString::new(string);
+
+        unsafe {
+            let result = expand(string.as_ptr());
+
+            take_weechat_string(result).expect("Returned null while expanding the home dir")
+        }
+    }
+
+    /// Get the number of columns a string needs on the screen.
+    ///
+    /// Wide characters are counted as multiple columns and Weechat color codes
+    /// aren't counted.
+    ///
+    /// # Arguments
+    ///
+    /// * `string` - The string that should be measured.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    pub fn strlen_screen(&self, string: &str) -> usize {
+        Weechat::check_thread();
+
+        let strlen_screen = self.get().strlen_screen.unwrap();
+        let string = LossyCString::new(string);
+
+        let length = unsafe { strlen_screen(string.as_ptr()) };
+
+        length.max(0) as usize
+    }
+
+    /// Get the longest prefix of a string that fits into the given width on
+    /// the screen.
+    ///
+    /// The width is measured using `Weechat::strlen_screen()`, so wide
+    /// characters and color codes are taken into account. The string is only
+    /// cut at character boundaries, if the first character is already wider
+    /// than the given width an empty string is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `string` - The string that should be fit.
+    ///
+    /// * `width` - The number of columns the string may use.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use weechat::Weechat;
+    /// # let weechat = unsafe { Weechat::weechat() };
+    /// let nick = weechat.fit_to_width("a_very_long_nickname", 9);
+    /// assert_eq!(nick, "a_very_lo");
+    /// ```
+    pub fn fit_to_width<'a>(&self, string: &'a str, width: usize) -> &'a str {
+        if self.strlen_screen(string) <= width {
+            return string;
+        }
+
+        // Color codes don't take up any columns, so the width only grows
+        // with the prefix and the scan can stop at the first prefix that is
+        // too wide. Zero width characters after the last fitting character,
+        // e.g. a color code, are kept.
+        let mut end = 0;
+
+        for (index, c) in string.char_indices() {
+            let next = index + c.len_utf8();
+
+            if self.strlen_screen(&string[..next]) > width {
+                break;
+            }
+
+            end = next;
+        }
+
+        &string[..end]
+    }
+
+    /// Pad a string with spaces on the right until it fills the given width
+    /// on the screen.
+    ///
+    /// The width is measured using `Weechat::strlen_screen()`, so wide
+    /// characters and color codes are taken into account. Strings that are
+    /// wider than the given width are returned unchanged.
+    ///
+    /// # Arguments
+    ///
+    /// * `string` - The string that should be padded.
+    ///
+    /// * `width` - The number of columns the string should fill.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use weechat::Weechat;
+    /// # let weechat = unsafe { Weechat::weechat() };
+    /// # let buffer = weechat.current_buffer();
+    /// for (nick, host) in &[("alice", "example.org"), ("ボブ", "example.com")] {
+    ///     buffer.print(&format!("{} {}", weechat.pad_right(nick, 10), host));
+    /// }
+    /// ```
+    pub fn pad_right(&self, string: &str, width: usize) -> String {
+        let padding = width.saturating_sub(self.strlen_screen(string));
+        format!("{}{}", string, " ".repeat(padding))
+    }
+
+    /// Pad a string with spaces on the left until it fills the given width on
+    /// the screen.
+    ///
+    /// The width is measured using `Weechat::strlen_screen()`, so wide
+    /// characters and color codes are taken into account. Strings that are
+    /// wider than the given width are returned unchanged.
+    ///
+    /// # Arguments
+    ///
+    /// * `string` - The string that should be padded.
+    ///
+    /// * `width` - The number of columns the string should fill.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    pub fn pad_left(&self, string: &str, width: usize) -> String {
+        let padding = width.saturating_sub(self.strlen_screen(string));
+        format!("{}{}", " ".repeat(padding), string)
+    }
+
+    /// Split a string the same way Weechat splits it.
+    ///
+    /// # Arguments
+    ///
+    /// * `string` - The string that should be split.
+    ///
+    /// * `separators` - The characters that are used as separators.
+    ///
+    /// * `flags` - Flags controlling how the string is split.
+    ///
+    /// * `max_items` - The maximum number of items that should be created, 0
+    ///   means no limit.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use weechat::{SplitFlags, Weechat};
+    /// # let weechat = unsafe { Weechat::weechat() };
+    /// let servers = weechat.string_split("libera,,oftc,", ",", SplitFlags::standard(), 0);
+    /// assert_eq!(servers, vec!["libera", "oftc"]);
+    /// ```
+    pub fn string_split(
+        &self,
+        string: &str,
+        separators: &str,
+        flags: SplitFlags,
+        max_items: i32,
+    ) -> Vec<String> {
+        Weechat::check_thread();
+
+        let string_split = self.get().string_split.unwrap();
+        let string_free_split = self.get().string_free_split.unwrap();
+
+        let string = LossyCString::new(string);
+        let separators = LossyCString::new(separators);
+        let mut num_items: c_int = 0;
+
+        unsafe {
+            let items = string_split(
+                string.as_ptr(),
+                separators.as_ptr(),
+                ptr::null(),
+                flags.0,
+                max_items,
+                &mut num_items,
+            );
+
+            if items.is_null() {
+                return Vec::new();
+            }
+
+            let result = (0..num_items as isize)
+                .map(|i| CStr::from_ptr(*items.offset(i)).to_string_lossy().to_string())
+                .collect();
+
+            string_free_split(items);
+
+            result
+        }
+    }
+
+    /// Replace all the occurrences of a string with another string.
+    ///
+    /// # Arguments
+    ///
+    /// * `string` - The string in which the occurrences should be replaced.
+    ///
+    /// * `search` - The string that should be replaced, if it's empty the
+    ///   input is returned unchanged.
+    ///
+    /// * `replace` - The replacement string.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use weechat::Weechat;
+    /// # let weechat = unsafe { Weechat::weechat() };
+    /// let message = weechat.string_replace("Hello :)", ":)", "🙂");
+    /// ```
+    pub fn string_replace(&self, string: &str, search: &str, replace: &str) -> String {
+        Weechat::check_thread();
+
+        if search.is_empty() {
+            return string.to_owned();
+        }
+
+        let string_replace = self.get().string_replace.unwrap();
+
+        let c_string = LossyCString::new(string);
+        let search = LossyCString::new(search);
+        let replace = LossyCString::new(replace);
+
+        unsafe {
+            let result = string_replace(c_string.as_ptr(), search.as_ptr(), replace.as_ptr());
+            take_weechat_string(result).unwrap_or_else(|| string.to_owned())
+        }
+    }
+
+    /// Check if a string contains one of the given highlight words.
+    ///
+    /// This uses the same logic Weechat uses to highlight messages, a word
+    /// only matches if it's surrounded by word boundaries. The words are
+    /// matched case insensitively, a word may start or end with `*` to match
+    /// a part of a word.
+    ///
+    /// # Arguments
+    ///
+    /// * `string` - The string that should be checked.
+    ///
+    /// * `highlight_words` - A comma separated list of highlight words.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use weechat::Weechat;
+    /// # let weechat = unsafe { Weechat::weechat() };
+    /// assert!(weechat.string_has_highlight("hello mynick!", "mynick"));
+    /// assert!(!weechat.string_has_highlight("hello mynickname", "mynick"));
+    /// ```
+    pub fn string_has_highlight(&self, string: &str, highlight_words: &str) -> bool {
+        Weechat::check_thread();
+
+        let has_highlight = self.get().string_has_highlight.unwrap();
+
+        let string = LossyCString::new(string);
+        let highlight_words = LossyCString::new(highlight_words);
+
+        unsafe { has_highlight(string.as_ptr(), highlight_words.as_ptr()) == 1 }
+    }
+
+    /// Check if a string contains a highlight using a regular expression.
+    ///
+    /// Like for the `weechat.look.highlight_regex` option, the match needs to
+    /// be surrounded by word boundaries. An invalid regular expression never
+    /// matches.
+    ///
+    /// # Arguments
+    ///
+    /// * `string` - The string that should be checked.
+    ///
+    /// * `regex` - A POSIX extended regular expression, it may start with
+    ///   flags, e.g. `(?-i)` to match case sensitively.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    pub fn string_has_highlight_regex(&self, string: &str, regex: &str) -> bool {
+        Weechat::check_thread();
+
+        let has_highlight_regex = self.get().string_has_highlight_regex.unwrap();
+
+        let string = LossyCString::new(string);
+        let regex = LossyCString::new(regex);
+
+        unsafe { has_highlight_regex(string.as_ptr(), regex.as_ptr()) == 1 }
+    }
+
+    /// Replace the matches of a regular expression using a replacement
+    /// template.
+    ///
+    /// Returns an error if the regular expression is invalid.
+    ///
+    /// # Arguments
+    ///
+    /// * `string` - The string in which the matches should be replaced.
+    ///
+    /// * `regex` - A POSIX extended regular expression, it may start with
+    ///   flags, e.g. `(?i)` to ignore case.
+    ///
+    /// * `replace` - The replacement template, `$0` refers to the whole match
+    ///   and `$1` to `$99` to the groups of the match. `$+` refers to the last
+    ///   group and `$.*N` replaces all the chars of group `N` with `*`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use weechat::Weechat;
+    /// # let weechat = unsafe { Weechat::weechat() };
+    /// let message = weechat
+    ///     .string_replace_regex("password=hunter2", "(password=)(.*)", "$1$.*2")
+    ///     .expect("Invalid regex");
+    /// ```
+    pub fn string_replace_regex(
+        &self,
+        string: &str,
+        regex: &str,
+        replace: &str,
+    ) -> Result<String, ()> {
+        Weechat::check_thread();
+
+        let string_regcomp = self.get().string_regcomp.unwrap();
+        let string_replace_regex = self.get().string_replace_regex.unwrap();
+
+        let c_regex = LossyCString::new(regex);
+        let mut compiled = mem::MaybeUninit::<libc::regex_t>::uninit();
+
+        let ret = unsafe {
+            string_regcomp(
+                compiled.as_mut_ptr() as *mut c_void,
+                c_regex.as_ptr(),
+                libc::REG_EXTENDED,
+            )
+        };
+
+        if ret != 0 {
+            return Err(());
+        }
+
+        let c_string = LossyCString::new(string);
+        let replace = LossyCString::new(replace);
+
+        unsafe {
+            let result = string_replace_regex(
+                c_string.as_ptr(),
+                compiled.as_mut_ptr() as *mut c_void,
+                replace.as_ptr(),
+                b'$' as c_char,
+                None,
+                ptr::null_mut(),
+            );
+
+            libc::regfree(compiled.as_mut_ptr());
+
+            Ok(take_weechat_string(result).unwrap_or_else(|| string.to_owned()))
+        }
+    }
+
+    /// Join a list of strings with a separator, the inverse of
+    /// `Weechat::string_split()`.
+    ///
+    /// # Arguments
+    ///
+    /// * `items` - The strings that should be joined.
+    ///
+    /// * `separator` - The separator that is put between the items.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    pub fn string_join(&self, items: &[&str], separator: &str) -> String {
+        Weechat::check_thread();
+
+        let string_build = self.get().string_build_with_split_string.unwrap();
+
+        let items: Vec<CString> = items.iter().map(LossyCString::new).collect();
+        let mut item_pointers: Vec<*const c_char> = items.iter().map(|i| i.as_ptr()).collect();
+        item_pointers.push(ptr::null());
+
+        let separator = LossyCString::new(separator);
+
+        unsafe {
+            let result = string_build(item_pointers.as_mut_ptr(), separator.as_ptr());
+
+            take_weechat_string(result).unwrap_or_default()
+        }
+    }
+
+    /// Execute a modifier.
+    ///
+    /// A modifier takes a string and modifies it in some way, Weechat has a
+    /// list of defined modifiers. For example to parse a string with some color
+    /// format (ansi, irc...) and to convert it to another format.
+    ///
+    /// Returns the modified string or an empty error if the string couldn't be
     /// modified.
     ///
     /// # Arguments
@@ -555,16 +1619,183 @@ impl Weechat {
             let result =
                 exec(weechat.ptr, modifier.as_ptr(), modifier_data.as_ptr(), input_string.as_ptr());
 
-            if result.is_null() {
-                Err(())
-            } else {
-                Ok(CStr::from_ptr(result).to_string_lossy().to_string())
-            }
+            take_weechat_string(result).ok_or(())
+        }
+    }
+
+    /// Execute a chain of modifiers in order.
+    ///
+    /// The output of every modifier is passed as the input to the next one,
+    /// this makes the order of the modifiers deterministic regardless of the
+    /// priorities of the hooks. Modifiers that don't exist or that fail are
+    /// skipped and the string is passed on unchanged.
+    ///
+    /// # Arguments
+    ///
+    /// * `modifiers` - The names of the modifiers in the order they should be
+    ///   executed.
+    ///
+    /// * `modifier_data` - Data that will be passed to every modifier.
+    ///
+    /// * `input_string` - The string that should be modified.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use weechat::Weechat;
+    /// let formatted = Weechat::exec_modifier_list(
+    ///     &["irc_color_decode", "myplugin_format"],
+    ///     "1",
+    ///     "\x0304red",
+    /// );
+    /// ```
+    pub fn exec_modifier_list(
+        modifiers: &[&str],
+        modifier_data: &str,
+        input_string: &str,
+    ) -> String {
+        modifiers.iter().fold(input_string.to_owned(), |string, modifier| {
+            Weechat::execute_modifier(modifier, modifier_data, &string).unwrap_or(string)
+        })
+    }
+
+    /// Convert IRC color codes in a string to Weechat color codes.
+    ///
+    /// This uses the `irc_color_decode` modifier of the IRC plugin, if the IRC
+    /// plugin isn't loaded the string is returned unchanged.
+    ///
+    /// # Arguments
+    ///
+    /// * `string` - The string containing IRC color codes.
+    ///
+    /// * `keep_colors` - If false all the colors are removed from the string
+    ///   instead of being converted.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use weechat::Weechat;
+    /// # let weechat = unsafe { Weechat::weechat() };
+    /// let message = weechat.irc_color_decode("\x0304red", true);
+    /// ```
+    pub fn irc_color_decode(&self, string: &str, keep_colors: bool) -> String {
+        let modifier_data = if keep_colors { "1" } else { "0" };
+        Weechat::execute_modifier("irc_color_decode", modifier_data, string)
+            .unwrap_or_else(|_| string.to_owned())
+    }
+
+    /// Convert Weechat color codes in a string to IRC color codes.
+    ///
+    /// This uses the `irc_color_encode` modifier of the IRC plugin, if the IRC
+    /// plugin isn't loaded the string is returned unchanged.
+    ///
+    /// # Arguments
+    ///
+    /// * `string` - The string containing Weechat color codes.
+    ///
+    /// * `keep_colors` - If false all the colors are removed from the string
+    ///   instead of being converted.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    pub fn irc_color_encode(&self, string: &str, keep_colors: bool) -> String {
+        let modifier_data = if keep_colors { "1" } else { "0" };
+        Weechat::execute_modifier("irc_color_encode", modifier_data, string)
+            .unwrap_or_else(|_| string.to_owned())
+    }
+
+    /// Convert Weechat color codes in a string to ANSI escape sequences.
+    ///
+    /// This uses the `color_encode_ansi` modifier of Weechat, colors are
+    /// converted to the 256 color ANSI codes and attributes that have no ANSI
+    /// equivalent are dropped. The modifier was added in Weechat 2.7, on older
+    /// versions the colors are removed instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `string` - The string containing Weechat color codes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use weechat::Weechat;
+    /// # let weechat = unsafe { Weechat::weechat() };
+    /// let message = format!("{}red", Weechat::color("red"));
+    /// let ansi = weechat.weechat_color_to_ansi(&message);
+    /// ```
+    pub fn weechat_color_to_ansi(&self, string: &str) -> String {
+        // Weechat returns the unchanged string for modifiers that nobody
+        // handles, so the modifier can't tell us that it doesn't exist.
+        if Unsupported::check("color_encode_ansi", (2, 7, 0)).is_err() {
+            return Weechat::remove_color(string);
         }
+
+        Weechat::execute_modifier("color_encode_ansi", "", string)
+            .unwrap_or_else(|_| Weechat::remove_color(string))
+    }
+
+    /// Fold the case of a nick so that nicks the server considers equal
+    /// compare equal.
+    ///
+    /// # Arguments
+    ///
+    /// * `nick` - The nick that should be folded.
+    ///
+    /// * `casemapping` - The casemapping the server announced, characters that
+    ///   aren't part of the casemapping are left as they are.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use weechat::{Casemapping, Weechat};
+    /// # let weechat = unsafe { Weechat::weechat() };
+    /// assert_eq!(
+    ///     weechat.nick_casefold("[Nick]", Casemapping::Rfc1459),
+    ///     weechat.nick_casefold("{nick}", Casemapping::Rfc1459),
+    /// );
+    /// ```
+    pub fn nick_casefold(&self, nick: &str, casemapping: Casemapping) -> String {
+        nick.chars().map(|c| casemapping.fold(c)).collect()
+    }
+
+    /// Refresh the content of a bar on the screen.
+    ///
+    /// Nothing happens if no bar with the given name exists.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the bar that should be refreshed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method is not called from the main Weechat thread.
+    pub fn bar_update(name: &str) {
+        Weechat::check_thread();
+        let weechat = unsafe { Weechat::weechat() };
+
+        let bar_update = weechat.get().bar_update.unwrap();
+        let name = LossyCString::new(name);
+
+        unsafe { bar_update(name.as_ptr()) };
     }
 
     /// Update the content of a bar item, by calling its build callback.
     ///
+    /// Nothing happens if no bar item with the given name exists.
+    ///
     /// # Arguments
     ///
     /// * `name` - The name of the bar item that should be updated.
@@ -583,12 +1814,11 @@ impl Weechat {
     ///
     /// # Panics
     ///
-    /// Panics if the method is not called from the main Weechat thread or if
-    /// the method is called in a buffer close callback when Weechat is shutting
-    /// down.
+    /// Panics if the method is not called from the main Weechat thread.
     ///
-    /// The buffer close callback gets called after the plugin is dropped and
-    /// the executor will be stopped at that point.
+    /// When the plugin is unloaded the buffer close callbacks get called after
+    /// the plugin is dropped, futures that are spawned there are dropped
+    /// without running since the executor is stopped right afterwards.
     ///
     /// # Example
     ///